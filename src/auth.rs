@@ -0,0 +1,371 @@
+//! Optional API key authentication with per-router scopes.
+//!
+//! When no keys are configured every request is accepted, preserving the
+//! original open behaviour. Once at least one key exists, requests to scoped
+//! routes must present a key via `Authorization: Bearer <key>` or
+//! `X-API-Key: <key>`, and the key must grant the scope of the route group.
+//! `/health`, the docs, and the `OpenAPI` document stay public so liveness
+//! probes and API discovery keep working.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use std::{fmt, sync::Arc};
+use tracing::debug;
+
+use crate::pixoo::error::{PixooHttpErrorKind, PixooHttpErrorResponse};
+
+/// Header carrying an API key as an alternative to a bearer token.
+pub const API_KEY_HEADER: &str = "X-API-Key";
+
+/// Permission granted by an API key; one per router group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    Draw,
+    Tools,
+    Manage,
+    System,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Draw, Scope::Tools, Scope::Manage, Scope::System];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Draw => "draw",
+            Scope::Tools => "tools",
+            Scope::Manage => "manage",
+            Scope::System => "system",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scope| scope.as_str().eq_ignore_ascii_case(value.trim()))
+    }
+
+    /// Returns the scope protecting `path`, or `None` for public paths.
+    ///
+    /// Only the root redirect, health, and API docs are public. Unknown
+    /// prefixes fall back to [`Scope::System`] so new routes are protected
+    /// until they are mapped.
+    pub fn for_path(path: &str) -> Option<Self> {
+        let first = path
+            .trim_start_matches('/')
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        match first {
+            "" | "health" | "docs" | "api-docs" => None,
+            "draw" | "jobs" | "notify" => Some(Scope::Draw),
+            "tools" => Some(Scope::Tools),
+            "manage" => Some(Scope::Manage),
+            // `reboot`, `metrics`, `events`, and anything not mapped yet.
+            _ => Some(Scope::System),
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey {
+    token: String,
+    scopes: Vec<Scope>,
+}
+
+impl ApiKey {
+    pub fn new(token: impl Into<String>, scopes: Vec<Scope>) -> Self {
+        Self {
+            token: token.into(),
+            scopes,
+        }
+    }
+
+    /// Parses `token` or `token:scope,scope`. A key without scopes grants all
+    /// of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a message when the token is empty or a scope is unknown.
+    pub fn parse(entry: &str) -> Result<Self, String> {
+        let (token, scopes) = match entry.trim().split_once(':') {
+            Some((token, scopes)) => (token.trim(), Some(scopes)),
+            None => (entry.trim(), None),
+        };
+        if token.is_empty() {
+            return Err("API key token must not be empty".to_string());
+        }
+        let scopes = match scopes {
+            Some(raw) => raw
+                .split(',')
+                .filter(|scope| !scope.trim().is_empty())
+                .map(|scope| {
+                    Scope::parse(scope).ok_or_else(|| format!("unknown scope '{}'", scope.trim()))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+            None => Scope::ALL.to_vec(),
+        };
+        Ok(Self::new(token, scopes))
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ApiKey")
+            .field("token", &"<redacted>")
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuthConfig {
+    keys: Vec<ApiKey>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthDecision {
+    Allowed,
+    MissingCredentials,
+    InvalidCredentials,
+    MissingScope,
+}
+
+impl AuthConfig {
+    pub fn new(keys: Vec<ApiKey>) -> Self {
+        Self { keys }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn authorize(&self, token: Option<&str>, scope: Scope) -> AuthDecision {
+        if !self.is_enabled() {
+            return AuthDecision::Allowed;
+        }
+        let Some(token) = token else {
+            return AuthDecision::MissingCredentials;
+        };
+        match self
+            .keys
+            .iter()
+            .find(|key| constant_time_eq(key.token.as_bytes(), token.as_bytes()))
+        {
+            Some(key) if key.scopes.contains(&scope) => AuthDecision::Allowed,
+            Some(_) => AuthDecision::MissingScope,
+            None => AuthDecision::InvalidCredentials,
+        }
+    }
+}
+
+/// Middleware enforcing [`AuthConfig`] on scoped routes.
+pub async fn require_api_key(
+    State(auth): State<Arc<AuthConfig>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if req.method() == Method::OPTIONS {
+        return next.run(req).await;
+    }
+    let Some(scope) = Scope::for_path(req.uri().path()) else {
+        return next.run(req).await;
+    };
+
+    match auth.authorize(presented_token(req.headers()), scope) {
+        AuthDecision::Allowed => next.run(req).await,
+        AuthDecision::MissingCredentials => unauthorized("missing API key"),
+        AuthDecision::InvalidCredentials => {
+            debug!(path = %req.uri().path(), "rejected invalid API key");
+            unauthorized("invalid API key")
+        }
+        AuthDecision::MissingScope => PixooHttpErrorResponse::with_details(
+            StatusCode::FORBIDDEN,
+            PixooHttpErrorKind::Forbidden,
+            format!("API key lacks the '{scope}' scope"),
+            serde_json::json!({ "required_scope": scope.as_str() }),
+        )
+        .into_response(),
+    }
+}
+
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| {
+            let (scheme, token) = value.trim().split_once(' ')?;
+            scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+        });
+    bearer.or_else(|| {
+        headers
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+    })
+}
+
+fn unauthorized(message: &str) -> Response {
+    let mut response = PixooHttpErrorResponse::new(
+        StatusCode::UNAUTHORIZED,
+        PixooHttpErrorKind::Unauthorized,
+        message,
+    )
+    .into_response();
+    response
+        .headers_mut()
+        .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+    response
+}
+
+/// Compares secrets without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0_u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware::from_fn_with_state, routing::post, Router};
+    use tower::util::ServiceExt;
+
+    fn app(auth: AuthConfig) -> Router {
+        Router::new()
+            .route("/draw/fill", post(|| async { StatusCode::OK }))
+            .route("/reboot", post(|| async { StatusCode::OK }))
+            .route("/health", axum::routing::get(|| async { StatusCode::OK }))
+            .layer(from_fn_with_state(Arc::new(auth), require_api_key))
+    }
+
+    async fn send(
+        app: Router,
+        method: Method,
+        uri: &str,
+        header: Option<(&str, &str)>,
+    ) -> Response {
+        let mut builder = Request::builder().method(method).uri(uri);
+        if let Some((name, value)) = header {
+            builder = builder.header(name, value);
+        }
+        app.oneshot(builder.body(Body::empty()).unwrap())
+            .await
+            .expect("response")
+    }
+
+    fn draw_only() -> AuthConfig {
+        AuthConfig::new(vec![ApiKey::parse("drawer:draw").unwrap()])
+    }
+
+    #[test]
+    fn parses_keys_with_and_without_scopes() {
+        let key = ApiKey::parse(" abc : draw , tools ").unwrap();
+        assert_eq!(key.scopes, vec![Scope::Draw, Scope::Tools]);
+        assert_eq!(ApiKey::parse("abc").unwrap().scopes, Scope::ALL.to_vec());
+        assert!(ApiKey::parse("abc:paint").is_err());
+        assert!(ApiKey::parse(":draw").is_err());
+    }
+
+    #[test]
+    fn maps_paths_to_scopes() {
+        assert_eq!(Scope::for_path("/draw/fill"), Some(Scope::Draw));
+        assert_eq!(Scope::for_path("/jobs/42"), Some(Scope::Draw));
+        assert_eq!(Scope::for_path("/notify"), Some(Scope::Draw));
+        assert_eq!(Scope::for_path("/tools/timer/start"), Some(Scope::Tools));
+        assert_eq!(Scope::for_path("/manage/display/on"), Some(Scope::Manage));
+        assert_eq!(Scope::for_path("/reboot"), Some(Scope::System));
+        assert_eq!(Scope::for_path("/metrics"), Some(Scope::System));
+        assert_eq!(Scope::for_path("/events"), Some(Scope::System));
+        assert_eq!(Scope::for_path("/future/route"), Some(Scope::System));
+        assert_eq!(Scope::for_path("/"), None);
+        assert_eq!(Scope::for_path("/health"), None);
+        assert_eq!(Scope::for_path("/docs/index.html"), None);
+        assert_eq!(Scope::for_path("/api-docs/openapi.json"), None);
+    }
+
+    #[test]
+    fn debug_output_redacts_tokens() {
+        let output = format!("{:?}", draw_only());
+        assert!(!output.contains("drawer"));
+    }
+
+    #[tokio::test]
+    async fn disabled_auth_allows_everything() {
+        let response = send(app(AuthConfig::default()), Method::POST, "/reboot", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn missing_key_returns_401() {
+        let response = send(app(draw_only()), Method::POST, "/draw/fill", None).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+    }
+
+    #[tokio::test]
+    async fn invalid_key_returns_401() {
+        let response = send(
+            app(draw_only()),
+            Method::POST,
+            "/draw/fill",
+            Some(("Authorization", "Bearer nope")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn accepts_bearer_and_api_key_header() {
+        let response = send(
+            app(draw_only()),
+            Method::POST,
+            "/draw/fill",
+            Some(("Authorization", "Bearer drawer")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(
+            app(draw_only()),
+            Method::POST,
+            "/draw/fill",
+            Some((API_KEY_HEADER, "drawer")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn key_without_scope_returns_403() {
+        let response = send(
+            app(draw_only()),
+            Method::POST,
+            "/reboot",
+            Some(("Authorization", "Bearer drawer")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn public_paths_skip_auth() {
+        let response = send(app(draw_only()), Method::GET, "/health", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
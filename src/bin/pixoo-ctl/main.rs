@@ -0,0 +1,277 @@
+//! Command-line client for the Pixoo bridge.
+//!
+//! Every subcommand mirrors a bridge endpoint. By default requests go to a
+//! running bridge; with `--device` they are sent straight to the panel
+//! through the library's `PixooClient`.
+
+mod output;
+mod target;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use output::{Outcome, OutputMode};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use target::{CtlError, Target};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "pixoo-ctl",
+    version,
+    about = "Control a Pixoo 64 through the bridge or directly"
+)]
+struct Cli {
+    /// Base URL of the bridge.
+    #[arg(
+        long,
+        env = "PIXOO_CTL_BRIDGE_URL",
+        default_value = "http://localhost:4000",
+        global = true
+    )]
+    bridge: String,
+
+    /// Talk to the device at this URL instead of a bridge.
+    #[arg(long, env = "PIXOO_CTL_DEVICE_URL", global = true)]
+    device: Option<String>,
+
+    /// API key sent to the bridge as a bearer token.
+    #[arg(long, env = "PIXOO_CTL_API_KEY", hide_env_values = true, global = true)]
+    api_key: Option<String>,
+
+    /// Output format.
+    #[arg(long, short, value_enum, default_value_t = OutputMode::Human, global = true)]
+    output: OutputMode,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Draw on the display.
+    #[command(subcommand)]
+    Draw(DrawCommand),
+    /// Change display settings.
+    #[command(subcommand)]
+    Display(DisplayCommand),
+    /// Control the built-in tools.
+    #[command(subcommand)]
+    Tools(ToolsCommand),
+    /// Show the current device settings.
+    Settings,
+}
+
+#[derive(Debug, Subcommand)]
+enum DrawCommand {
+    /// Upload an image or animation (PNG, JPEG, GIF, or WebP).
+    Upload {
+        /// Image file to show.
+        file: PathBuf,
+    },
+    /// Show a text item over the current image.
+    Text(TextArgs),
+}
+
+#[derive(Debug, Args)]
+struct TextArgs {
+    /// Text to show.
+    text: String,
+    /// Text slot; sending the same id replaces the item.
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..=20))]
+    id: u16,
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..64))]
+    x: u16,
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..64))]
+    y: u16,
+    /// Color as `#RRGGBB`.
+    #[arg(long, default_value = "#FFFFFF", value_parser = parse_color)]
+    color: Rgb,
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..=7))]
+    font: u16,
+    /// Width of the text box in pixels.
+    #[arg(long, default_value_t = 64, value_parser = clap::value_parser!(u16).range(16..=64))]
+    width: u16,
+    /// Scroll speed (0–100).
+    #[arg(long, default_value_t = 0, value_parser = clap::value_parser!(u16).range(0..=100))]
+    speed: u16,
+    #[arg(long, value_enum, default_value_t = Direction::Left)]
+    direction: Direction,
+    #[arg(long, value_enum, default_value_t = Align::Left)]
+    align: Align,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rgb {
+    red: u8,
+    green: u8,
+    blue: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Direction {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Align {
+    Left,
+    Middle,
+    Right,
+}
+
+#[derive(Debug, Subcommand)]
+enum DisplayCommand {
+    /// Set the brightness (0–100).
+    Brightness {
+        #[arg(value_parser = clap::value_parser!(u8).range(0..=100))]
+        value: u8,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ToolsCommand {
+    /// Start or stop the countdown timer.
+    #[command(subcommand)]
+    Timer(TimerCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum TimerCommand {
+    /// Start a countdown.
+    Start {
+        #[arg(value_parser = clap::value_parser!(u32).range(0..60))]
+        minutes: u32,
+        #[arg(value_parser = clap::value_parser!(u32).range(0..60))]
+        seconds: u32,
+    },
+    /// Stop the countdown.
+    Stop,
+}
+
+fn parse_color(value: &str) -> Result<Rgb, String> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    let channel = |range: std::ops::Range<usize>| {
+        hex.get(range)
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+    };
+    match (hex.len(), channel(0..2), channel(2..4), channel(4..6)) {
+        (6, Some(red), Some(green), Some(blue)) => Ok(Rgb { red, green, blue }),
+        _ => Err(format!("'{value}' is not a #RRGGBB color")),
+    }
+}
+
+async fn execute(target: &Target, command: Command) -> Result<Outcome, CtlError> {
+    match command {
+        Command::Draw(DrawCommand::Upload { file }) => {
+            let bytes = tokio::fs::read(&file)
+                .await
+                .map_err(|source| CtlError::Read {
+                    path: file.clone(),
+                    source,
+                })?;
+            target.upload(&file, bytes).await?;
+            Ok(Outcome::Done(format!("Uploaded {}", file.display())))
+        }
+        Command::Draw(DrawCommand::Text(args)) => {
+            target.text(&args).await?;
+            Ok(Outcome::Done(format!("Text {} shown", args.id)))
+        }
+        Command::Display(DisplayCommand::Brightness { value }) => {
+            target.brightness(value).await?;
+            Ok(Outcome::Done(format!("Brightness set to {value}")))
+        }
+        Command::Tools(ToolsCommand::Timer(TimerCommand::Start { minutes, seconds })) => {
+            target.timer_start(minutes, seconds).await?;
+            Ok(Outcome::Done(format!(
+                "Timer started for {minutes:02}:{seconds:02}"
+            )))
+        }
+        Command::Tools(ToolsCommand::Timer(TimerCommand::Stop)) => {
+            target.timer_stop().await?;
+            Ok(Outcome::Done("Timer stopped".to_string()))
+        }
+        Command::Settings => Ok(Outcome::Settings(target.settings().await?)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match Target::from_urls(&cli.bridge, cli.device.as_deref(), cli.api_key) {
+        Ok(target) => execute(&target, cli.command).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(outcome) => {
+            println!("{}", outcome.render(cli.output));
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}", output::render_error(&err, cli.output));
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn command_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn parses_nested_subcommands_and_global_flags() {
+        let cli = Cli::try_parse_from([
+            "pixoo-ctl",
+            "draw",
+            "text",
+            "hello",
+            "--color",
+            "#00ff7f",
+            "--align",
+            "middle",
+            "--device",
+            "http://pixoo.local",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+
+        assert_eq!(cli.device.as_deref(), Some("http://pixoo.local"));
+        assert_eq!(cli.output, OutputMode::Json);
+        let Command::Draw(DrawCommand::Text(args)) = cli.command else {
+            panic!("expected draw text");
+        };
+        assert_eq!(args.text, "hello");
+        assert_eq!(
+            args.color,
+            Rgb {
+                red: 0,
+                green: 255,
+                blue: 127
+            }
+        );
+        assert_eq!(args.align, Align::Middle);
+        assert_eq!(args.width, 64);
+    }
+
+    #[test]
+    fn rejects_out_of_range_values() {
+        assert!(Cli::try_parse_from(["pixoo-ctl", "display", "brightness", "101"]).is_err());
+        assert!(Cli::try_parse_from(["pixoo-ctl", "tools", "timer", "start", "1", "60"]).is_err());
+        assert!(parse_color("#12345").is_err());
+        assert!(parse_color("zzzzzz").is_err());
+        assert_eq!(
+            parse_color("0A0B0C"),
+            Ok(Rgb {
+                red: 10,
+                green: 11,
+                blue: 12
+            })
+        );
+    }
+}
@@ -0,0 +1,115 @@
+//! Human and JSON rendering of command results.
+
+use crate::target::CtlError;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputMode {
+    Human,
+    Json,
+}
+
+/// Device settings in the shape of the bridge's `GET /manage/settings`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Settings {
+    pub display_on: bool,
+    pub brightness: i64,
+    pub time_mode: String,
+    pub rotation_angle: i64,
+    pub mirrored: bool,
+    pub temperature_unit: String,
+    pub current_clock_id: i64,
+}
+
+pub enum Outcome {
+    /// A command that only reports success.
+    Done(String),
+    Settings(Settings),
+}
+
+impl Outcome {
+    pub fn render(&self, mode: OutputMode) -> String {
+        match (self, mode) {
+            (Outcome::Done(message), OutputMode::Human) => message.clone(),
+            (Outcome::Done(message), OutputMode::Json) => {
+                json!({ "status": "ok", "message": message }).to_string()
+            }
+            (Outcome::Settings(settings), OutputMode::Human) => {
+                let on_off = |flag: bool| if flag { "on" } else { "off" };
+                [
+                    ("Display", on_off(settings.display_on).to_string()),
+                    ("Brightness", settings.brightness.to_string()),
+                    ("Time mode", settings.time_mode.clone()),
+                    ("Rotation", format!("{}°", settings.rotation_angle)),
+                    ("Mirrored", on_off(settings.mirrored).to_string()),
+                    ("Temperature", settings.temperature_unit.clone()),
+                    ("Clock id", settings.current_clock_id.to_string()),
+                ]
+                .iter()
+                .map(|(label, value)| format!("{label:<12} {value}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+            }
+            (Outcome::Settings(settings), OutputMode::Json) => {
+                serde_json::to_string_pretty(settings).unwrap_or_default()
+            }
+        }
+    }
+}
+
+pub fn render_error(err: &CtlError, mode: OutputMode) -> String {
+    match mode {
+        OutputMode::Human => format!("error: {err}"),
+        OutputMode::Json => json!({ "status": "error", "message": err.to_string() }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn settings() -> Settings {
+        Settings {
+            display_on: true,
+            brightness: 80,
+            time_mode: "TWENTY_FOUR".to_string(),
+            rotation_angle: 90,
+            mirrored: false,
+            temperature_unit: "CELSIUS".to_string(),
+            current_clock_id: 5,
+        }
+    }
+
+    #[test]
+    fn renders_settings_for_humans_and_scripts() {
+        let outcome = Outcome::Settings(settings());
+
+        let human = outcome.render(OutputMode::Human);
+        assert!(human.contains("Brightness   80"));
+        assert!(human.contains("Rotation     90°"));
+
+        let parsed: Value = serde_json::from_str(&outcome.render(OutputMode::Json)).unwrap();
+        assert_eq!(parsed["displayOn"], true);
+        assert_eq!(parsed["timeMode"], "TWENTY_FOUR");
+    }
+
+    #[test]
+    fn renders_results_and_errors_as_json() {
+        let done: Value =
+            serde_json::from_str(&Outcome::Done("ok".to_string()).render(OutputMode::Json))
+                .unwrap();
+        assert_eq!(done["status"], "ok");
+
+        let err = CtlError::Image("unsupported image format".to_string());
+        let parsed: Value = serde_json::from_str(&render_error(&err, OutputMode::Json)).unwrap();
+        assert_eq!(parsed["status"], "error");
+        assert_eq!(
+            render_error(&err, OutputMode::Human),
+            "error: unsupported image format"
+        );
+    }
+}
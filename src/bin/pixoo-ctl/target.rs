@@ -0,0 +1,473 @@
+//! Where commands go: a running bridge over its REST API, or the device
+//! itself through `PixooClient`.
+
+use crate::output::Settings;
+use crate::{Align, Direction, TextArgs};
+use pixoo_bridge::pixels::{decode_upload, encode_pic_data, ImageError, PIXOO_FRAME_DIM};
+use pixoo_bridge::pixoo::error::PixooError;
+use pixoo_bridge::pixoo::protocol::{
+    GetAllConf, GetGifId, SendGif, SendText, SetBrightness, SetTimer, TextAlign, TextDirection,
+};
+use pixoo_bridge::pixoo::{PixooClient, PixooClientConfig};
+use reqwest::multipart::{Form, Part};
+use reqwest::{RequestBuilder, Url};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Frame duration the bridge uses for still images.
+const SINGLE_FRAME_PIC_SPEED_MS: u32 = 9999;
+
+#[derive(Debug, Error)]
+pub enum CtlError {
+    #[error("invalid URL '{url}': {reason}")]
+    InvalidUrl { url: String, reason: String },
+
+    #[error("cannot read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+
+    #[error("{0}")]
+    Image(String),
+
+    #[error("bridge request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("bridge returned {status} ({kind}): {message}")]
+    Bridge {
+        status: u16,
+        kind: String,
+        message: String,
+    },
+
+    #[error("device request failed: {0}")]
+    Device(#[from] PixooError),
+}
+
+pub enum Target {
+    Bridge(Bridge),
+    Device(PixooClient),
+}
+
+pub struct Bridge {
+    base_url: Url,
+    http: reqwest::Client,
+    api_key: Option<String>,
+}
+
+/// Error envelope returned by the bridge.
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error_kind: String,
+    message: String,
+}
+
+impl Target {
+    /// A device target when `device` is set, otherwise the bridge.
+    pub fn from_urls(
+        bridge: &str,
+        device: Option<&str>,
+        api_key: Option<String>,
+    ) -> Result<Self, CtlError> {
+        if let Some(device) = device {
+            return PixooClient::new(device, PixooClientConfig::default())
+                .map(Target::Device)
+                .map_err(|err| CtlError::InvalidUrl {
+                    url: device.to_string(),
+                    reason: err.to_string(),
+                });
+        }
+        let base_url = Url::parse(bridge).map_err(|err| CtlError::InvalidUrl {
+            url: bridge.to_string(),
+            reason: err.to_string(),
+        })?;
+        Ok(Target::Bridge(Bridge {
+            base_url,
+            http: reqwest::Client::new(),
+            api_key,
+        }))
+    }
+
+    pub async fn upload(&self, file: &Path, bytes: Vec<u8>) -> Result<(), CtlError> {
+        match self {
+            Target::Bridge(bridge) => {
+                let file_name = file
+                    .file_name()
+                    .map_or_else(|| "image".to_string(), |name| name.to_string_lossy().into());
+                let form = Form::new().part("file", Part::bytes(bytes).file_name(file_name));
+                bridge
+                    .send(bridge.post("/draw/upload").multipart(form))
+                    .await
+            }
+            Target::Device(client) => upload_to_device(client, &bytes).await,
+        }
+    }
+
+    pub async fn text(&self, args: &TextArgs) -> Result<(), CtlError> {
+        match self {
+            Target::Bridge(bridge) => {
+                let body = json!({
+                    "id": args.id,
+                    "position": { "x": args.x, "y": args.y },
+                    "scrollDirection": match args.direction {
+                        Direction::Left => "LEFT",
+                        Direction::Right => "RIGHT",
+                    },
+                    "font": args.font,
+                    "textWidth": args.width,
+                    "text": args.text,
+                    "scrollSpeed": args.speed,
+                    "color": {
+                        "red": args.color.red,
+                        "green": args.color.green,
+                        "blue": args.color.blue,
+                    },
+                    "textAlignment": match args.align {
+                        Align::Left => "LEFT",
+                        Align::Middle => "MIDDLE",
+                        Align::Right => "RIGHT",
+                    },
+                });
+                bridge.send(bridge.post("/draw/text").json(&body)).await
+            }
+            Target::Device(client) => {
+                let request = SendText {
+                    lcd_id: 0,
+                    text_id: args.id,
+                    x: args.x,
+                    y: args.y,
+                    dir: match args.direction {
+                        Direction::Left => TextDirection::Left,
+                        Direction::Right => TextDirection::Right,
+                    },
+                    font: args.font,
+                    text_width: args.width,
+                    speed: args.speed,
+                    text_string: args.text.clone(),
+                    color: format!(
+                        "#{:02X}{:02X}{:02X}",
+                        args.color.red, args.color.green, args.color.blue
+                    ),
+                    align: match args.align {
+                        Align::Left => TextAlign::Left,
+                        Align::Middle => TextAlign::Middle,
+                        Align::Right => TextAlign::Right,
+                    },
+                };
+                client.execute(&request).await?;
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn brightness(&self, value: u8) -> Result<(), CtlError> {
+        match self {
+            Target::Bridge(bridge) => {
+                let path = format!("/manage/display/brightness/{value}");
+                bridge.send(bridge.post(&path)).await
+            }
+            Target::Device(client) => {
+                client.execute(&SetBrightness { brightness: value }).await?;
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn timer_start(&self, minutes: u32, seconds: u32) -> Result<(), CtlError> {
+        match self {
+            Target::Bridge(bridge) => {
+                let body = json!({ "minute": minutes, "second": seconds });
+                bridge
+                    .send(bridge.post("/tools/timer/start").json(&body))
+                    .await
+            }
+            Target::Device(client) => {
+                client.execute(&SetTimer::start(minutes, seconds)).await?;
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn timer_stop(&self) -> Result<(), CtlError> {
+        match self {
+            Target::Bridge(bridge) => bridge.send(bridge.post("/tools/timer/stop")).await,
+            Target::Device(client) => {
+                client.execute(&SetTimer::stop()).await?;
+                Ok(())
+            }
+        }
+    }
+
+    pub async fn settings(&self) -> Result<Settings, CtlError> {
+        match self {
+            Target::Bridge(bridge) => bridge.get_json("/manage/settings").await,
+            Target::Device(client) => {
+                let settings = client.execute(&GetAllConf).await?;
+                Ok(Settings {
+                    display_on: settings.light_switch,
+                    brightness: settings.brightness,
+                    time_mode: if settings.time_24_flag {
+                        "TWENTY_FOUR"
+                    } else {
+                        "TWELVE"
+                    }
+                    .to_string(),
+                    rotation_angle: settings.rotation_flag * 90,
+                    mirrored: settings.mirror_flag,
+                    temperature_unit: if settings.temperature_mode {
+                        "FAHRENHEIT"
+                    } else {
+                        "CELSIUS"
+                    }
+                    .to_string(),
+                    current_clock_id: settings.cur_clock_id,
+                })
+            }
+        }
+    }
+}
+
+impl Bridge {
+    fn url(&self, path: &str) -> Url {
+        let mut url = self.base_url.clone();
+        let base = url.path().trim_end_matches('/').to_string();
+        url.set_path(&format!("{base}{path}"));
+        url
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.api_key {
+            Some(key) => request.bearer_auth(key),
+            None => request,
+        }
+    }
+
+    fn post(&self, path: &str) -> RequestBuilder {
+        self.authorize(self.http.post(self.url(path)))
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<(), CtlError> {
+        let response = request.send().await?;
+        check_status(response).await.map(drop)
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, path: &str) -> Result<T, CtlError> {
+        let request = self.authorize(self.http.get(self.url(path)));
+        let response = check_status(request.send().await?).await?;
+        Ok(response.json().await?)
+    }
+}
+
+/// Turns a bridge error envelope into [`CtlError::Bridge`].
+async fn check_status(response: reqwest::Response) -> Result<reqwest::Response, CtlError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let (kind, message) = match serde_json::from_str::<ErrorEnvelope>(&body) {
+        Ok(envelope) => (envelope.error_kind, envelope.message),
+        Err(_) => ("unknown".to_string(), body),
+    };
+    Err(CtlError::Bridge {
+        status: status.as_u16(),
+        kind,
+        message,
+    })
+}
+
+/// Decodes the image locally and sends its frames the way the bridge's
+/// `/draw/upload` does.
+async fn upload_to_device(client: &PixooClient, bytes: &[u8]) -> Result<(), CtlError> {
+    let frames = decode_upload(bytes, None).map_err(|err| {
+        CtlError::Image(match err {
+            ImageError::UnsupportedFormat => "unsupported image format".to_string(),
+            ImageError::DecodeFailed(reason) => format!("failed to process image: {reason}"),
+        })
+    })?;
+    // Animations are capped at 60 frames while decoding.
+    let pic_num = u32::try_from(frames.len()).unwrap_or(u32::MAX);
+    let pic_id = client.execute(&GetGifId).await?.pic_id;
+    for (offset, frame) in frames.iter().enumerate() {
+        let request = SendGif {
+            pic_id,
+            pic_num,
+            pic_offset: u32::try_from(offset).unwrap_or(u32::MAX),
+            pic_width: PIXOO_FRAME_DIM,
+            pic_speed: if frame.delay_ms == 0 {
+                SINGLE_FRAME_PIC_SPEED_MS
+            } else {
+                frame.delay_ms
+            },
+            pic_data: encode_pic_data(&frame.rgb_buffer).map_err(CtlError::Image)?,
+        };
+        client.execute(&request).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Rgb;
+    use httpmock::Method::{GET, POST};
+    use httpmock::MockServer;
+    use image::{ImageBuffer, ImageFormat, Rgb as Pixel};
+    use std::io::Cursor;
+
+    fn text_args() -> TextArgs {
+        TextArgs {
+            text: "hi".to_string(),
+            id: 3,
+            x: 0,
+            y: 10,
+            color: Rgb {
+                red: 255,
+                green: 0,
+                blue: 16,
+            },
+            font: 2,
+            width: 64,
+            speed: 0,
+            direction: Direction::Left,
+            align: Align::Middle,
+        }
+    }
+
+    fn png() -> Vec<u8> {
+        let image = ImageBuffer::from_pixel(8, 8, Pixel([10u8, 20, 30]));
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, ImageFormat::Png).unwrap();
+        bytes.into_inner()
+    }
+
+    #[tokio::test]
+    async fn bridge_requests_carry_api_key_and_api_shape() {
+        let server = MockServer::start_async().await;
+        let brightness = server.mock(|when, then| {
+            when.method(POST)
+                .path("/manage/display/brightness/40")
+                .header("authorization", "Bearer secret");
+            then.status(200);
+        });
+        let text = server.mock(|when, then| {
+            when.method(POST).path("/draw/text").json_body_includes(
+                json!({
+                    "id": 3,
+                    "color": { "red": 255, "green": 0, "blue": 16 },
+                    "textAlignment": "MIDDLE",
+                })
+                .to_string(),
+            );
+            then.status(200);
+        });
+
+        let target =
+            Target::from_urls(&server.base_url(), None, Some("secret".to_string())).unwrap();
+        target.brightness(40).await.unwrap();
+        target.text(&text_args()).await.unwrap();
+
+        brightness.assert();
+        text.assert();
+    }
+
+    #[tokio::test]
+    async fn bridge_errors_surface_the_envelope() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/manage/settings");
+            then.status(504).json_body(json!({
+                "error_kind": "timeout",
+                "message": "Pixoo Channel/GetAllConf command timed out",
+                "details": {},
+            }));
+        });
+
+        let target = Target::from_urls(&server.base_url(), None, None).unwrap();
+        let err = target.settings().await.unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "bridge returned 504 (timeout): Pixoo Channel/GetAllConf command timed out"
+        );
+    }
+
+    #[tokio::test]
+    async fn device_target_sends_protocol_commands() {
+        let server = MockServer::start_async().await;
+        let timer = server.mock(|when, then| {
+            when.method(POST).path("/post").json_body(json!({
+                "Command": "Tools/SetTimer", "Minute": 2, "Second": 30, "Status": 1
+            }));
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let text = server.mock(|when, then| {
+            when.method(POST).path("/post").json_body_includes(
+                json!({ "Command": "Draw/SendHttpText", "color": "#FF0010", "align": 2 })
+                    .to_string(),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let target = Target::from_urls("unused", Some(&server.base_url()), None).unwrap();
+        target.timer_start(2, 30).await.unwrap();
+        target.text(&text_args()).await.unwrap();
+
+        timer.assert();
+        text.assert();
+    }
+
+    #[tokio::test]
+    async fn device_upload_decodes_and_sends_frames() {
+        let server = MockServer::start_async().await;
+        let gif_id = server.mock(|when, then| {
+            when.method(POST)
+                .path("/post")
+                .json_body(json!({ "Command": "Draw/GetHttpGifId" }));
+            then.status(200).body(r#"{"error_code":0,"PicId":7}"#);
+        });
+        let frame = server.mock(|when, then| {
+            when.method(POST).path("/post").json_body_includes(
+                json!({ "Command": "Draw/SendHttpGif", "PicId": 7, "PicNum": 1, "PicSpeed": 9999 })
+                    .to_string(),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let target = Target::from_urls("unused", Some(&server.base_url()), None).unwrap();
+        target.upload(Path::new("still.png"), png()).await.unwrap();
+
+        gif_id.assert();
+        frame.assert();
+
+        let err = target
+            .upload(Path::new("notes.txt"), b"plain text".to_vec())
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "unsupported image format");
+    }
+
+    #[tokio::test]
+    async fn device_settings_match_the_bridge_shape() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(POST).path("/post");
+            then.status(200).json_body(json!({
+                "error_code": 0, "LightSwitch": 1, "Brightness": 55, "Time24Flag": 1,
+                "RotationFlag": 1, "MirrorFlag": 0, "TemperatureMode": 0, "CurClockId": 12,
+            }));
+        });
+
+        let target = Target::from_urls("unused", Some(&server.base_url()), None).unwrap();
+        let settings = target.settings().await.unwrap();
+
+        assert_eq!(settings.brightness, 55);
+        assert_eq!(settings.time_mode, "TWENTY_FOUR");
+        assert_eq!(settings.rotation_angle, 90);
+        assert_eq!(settings.temperature_unit, "CELSIUS");
+    }
+}
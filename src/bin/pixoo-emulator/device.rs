@@ -0,0 +1,875 @@
+//! In-memory model of a Pixoo 64 and its `/post` command handling.
+//!
+//! Commands are applied to [`Device`] one at a time, the same way the panel
+//! processes them. Responses mirror the device: every reply carries
+//! `error_code`, queries add their fields next to it, and invalid commands or
+//! arguments answer `error_code` [`ERROR_INVALID`] instead of failing at the
+//! HTTP level.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{Duration as ChronoDuration, Utc};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+pub const SCREEN_DIM: usize = 64;
+pub const FRAME_BYTES: usize = SCREEN_DIM * SCREEN_DIM * 3;
+
+/// `error_code` for unknown commands and missing or out-of-range arguments.
+pub const ERROR_INVALID: i64 = 1;
+
+/// Frames per animation accepted by the panel.
+const MAX_FRAMES: i64 = 60;
+/// Text items are addressed by `TextId` below this bound.
+const MAX_TEXT_ITEMS: i64 = 20;
+/// `ItemList` entries are addressed by `TextId` below this bound.
+const MAX_LIST_ITEMS: i64 = 40;
+/// The emulator's weather never changes.
+const WEATHER: &str = "Sunny";
+
+pub type Reply = Map<String, Value>;
+
+/// One RGB frame, `FRAME_BYTES` long, row-major.
+pub type Frame = Vec<u8>;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::struct_excessive_bools)] // one per device flag
+pub struct Settings {
+    pub display_on: bool,
+    pub brightness: u8,
+    /// Quarter turns clockwise (`RotationFlag`).
+    pub rotation: u8,
+    pub mirrored: bool,
+    pub time_24h: bool,
+    pub fahrenheit: bool,
+    pub overclock: bool,
+    pub white_balance: [u8; 3],
+    pub clock_id: i64,
+    /// `SelectIndex`: 0 faces, 1 cloud, 2 visualizer, 3 custom.
+    pub channel: u8,
+    pub custom_page: u8,
+    pub eq_position: i64,
+    pub cloud_index: u8,
+    /// `TimeZoneValue` as last sent, e.g. `GMT-2`.
+    pub timezone: String,
+    pub longitude: Option<String>,
+    pub latitude: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            display_on: true,
+            brightness: 100,
+            rotation: 0,
+            mirrored: false,
+            time_24h: true,
+            fahrenheit: false,
+            overclock: false,
+            white_balance: [100, 100, 100],
+            clock_id: 0,
+            channel: 0,
+            custom_page: 0,
+            eq_position: 0,
+            cloud_index: 0,
+            timezone: "GMT+0".to_string(),
+            longitude: None,
+            latitude: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TextItem {
+    pub x: i64,
+    pub y: i64,
+    pub text: String,
+    /// `#RRGGBB`.
+    pub color: String,
+    pub font: i64,
+    pub width: i64,
+    pub speed: i64,
+    /// 0 scrolls left, 1 scrolls right.
+    pub direction: i64,
+    /// 1 left, 2 middle, 3 right.
+    pub align: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub frames: Vec<Frame>,
+    pub speed: Duration,
+    pub started: Instant,
+}
+
+impl Animation {
+    /// Frame shown at `now`, looping over the animation.
+    pub fn frame_at(&self, now: Instant) -> &Frame {
+        let step = self.speed.as_millis().max(1);
+        let elapsed = now.saturating_duration_since(self.started).as_millis();
+        let len = self.frames.len() as u128;
+        // The index is below the frame count, which is at most 60.
+        let index = usize::try_from((elapsed / step) % len).unwrap_or(0);
+        &self.frames[index]
+    }
+}
+
+/// What the panel currently shows.
+#[derive(Debug, Clone)]
+pub enum Screen {
+    Clock,
+    Animation(Animation),
+    Timer {
+        ends_at: Option<Instant>,
+        remaining: Duration,
+    },
+    Stopwatch {
+        running_since: Option<Instant>,
+        elapsed: Duration,
+    },
+    Scoreboard {
+        red: i64,
+        blue: i64,
+    },
+    SoundMeter,
+}
+
+impl Screen {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Screen::Clock => "clock",
+            Screen::Animation(_) => "animation",
+            Screen::Timer { .. } => "timer",
+            Screen::Stopwatch { .. } => "stopwatch",
+            Screen::Scoreboard { .. } => "scoreboard",
+            Screen::SoundMeter => "sound-meter",
+        }
+    }
+}
+
+/// Animation being uploaded frame by frame.
+#[derive(Debug)]
+struct PendingAnimation {
+    pic_id: i64,
+    frames: Vec<Option<Frame>>,
+    speed: Duration,
+}
+
+#[derive(Debug)]
+pub struct Device {
+    pub settings: Settings,
+    /// Id returned by `Draw/GetHttpGifId`; bumped after each completed upload.
+    pub next_gif_id: i64,
+    pub screen: Screen,
+    pub texts: BTreeMap<i64, TextItem>,
+    /// Seconds added to the host clock by `Device/SetUTC`.
+    utc_offset: i64,
+    pending: Option<PendingAnimation>,
+}
+
+impl Default for Device {
+    fn default() -> Self {
+        Self {
+            settings: Settings::default(),
+            next_gif_id: 1,
+            screen: Screen::Clock,
+            texts: BTreeMap::new(),
+            utc_offset: 0,
+            pending: None,
+        }
+    }
+}
+
+impl Device {
+    /// Applies one `/post` payload at `now` and returns the device reply.
+    pub fn handle(&mut self, payload: &Map<String, Value>, now: Instant) -> Reply {
+        let Some(command) = payload.get("Command").and_then(Value::as_str) else {
+            return error_reply();
+        };
+        let args = Args(payload);
+        let result = match command {
+            "Device/SysReboot" => {
+                *self = Device {
+                    settings: self.settings.clone(),
+                    utc_offset: self.utc_offset,
+                    ..Device::default()
+                };
+                Ok(Reply::new())
+            }
+            "Channel/GetAllConf" => Ok(self.all_conf()),
+            "Channel/GetIndex" => Ok(reply([("SelectIndex", json!(self.settings.channel))])),
+            "Channel/GetClockInfo" => Ok(reply([
+                ("ClockId", json!(self.settings.clock_id)),
+                ("Brightness", json!(self.settings.brightness)),
+            ])),
+            "Channel/SetIndex"
+            | "Channel/SetClockSelectId"
+            | "Channel/SetCustomPageIndex"
+            | "Channel/SetEqPosition"
+            | "Channel/CloudIndex" => self.select_channel(command, &args),
+            "Device/GetDeviceTime" => Ok(self.device_time()),
+            "Device/GetWeatherInfo" => Ok(self.weather()),
+            "Draw/GetHttpGifId" => Ok(reply([("PicId", json!(self.next_gif_id))])),
+            "Draw/ResetHttpGifId" => {
+                self.next_gif_id = 1;
+                self.pending = None;
+                Ok(Reply::new())
+            }
+            "Draw/SendHttpGif" => self.send_gif(&args, now),
+            "Draw/SendHttpText" => self.send_text(&args, now),
+            "Draw/SendHttpItemList" => self.send_item_list(&args, now),
+            "Draw/ClearHttpText" => {
+                self.texts.clear();
+                Ok(Reply::new())
+            }
+            "Tools/SetTimer" => self.set_timer(&args, now),
+            "Tools/SetStopWatch" => self.set_stopwatch(&args, now),
+            "Tools/SetScoreBoard" => self.set_scoreboard(&args),
+            "Device/PlayBuzzer" => play_buzzer(&args),
+            "Tools/SetNoiseStatus" => args.flag("NoiseStatus").map(|on| {
+                self.screen = if on {
+                    Screen::SoundMeter
+                } else {
+                    Screen::Clock
+                };
+                Reply::new()
+            }),
+            _ => self.apply_setting(command, &args),
+        };
+        match result {
+            Ok(mut reply) => {
+                reply.insert("error_code".to_string(), json!(0));
+                reply
+            }
+            Err(Invalid) => error_reply(),
+        }
+    }
+
+    /// Channels are not rendered; switching to any of them leaves drawing
+    /// mode and shows the clock.
+    fn select_channel(&mut self, command: &str, args: &Args) -> Result<Reply, Invalid> {
+        let settings = &mut self.settings;
+        match command {
+            "Channel/SetIndex" => settings.channel = args.bounded("SelectIndex", 0, 3)?,
+            "Channel/SetClockSelectId" => {
+                settings.clock_id = args.bounded("ClockId", 0, i64::MAX)?;
+                settings.channel = 0;
+            }
+            "Channel/CloudIndex" => {
+                settings.cloud_index = args.bounded("Index", 0, 2)?;
+                settings.channel = 1;
+            }
+            "Channel/SetEqPosition" => {
+                settings.eq_position = args.bounded("EqPosition", 0, i64::MAX)?;
+                settings.channel = 2;
+            }
+            "Channel/SetCustomPageIndex" => {
+                settings.custom_page = args.bounded("CustomPageIndex", 0, 2)?;
+                settings.channel = 3;
+            }
+            _ => return Err(Invalid),
+        }
+        self.screen = Screen::Clock;
+        self.texts.clear();
+        Ok(Reply::new())
+    }
+
+    fn apply_setting(&mut self, command: &str, args: &Args) -> Result<Reply, Invalid> {
+        let settings = &mut self.settings;
+        match command {
+            "Channel/OnOffScreen" => settings.display_on = args.flag("OnOff")?,
+            "Channel/SetBrightness" => settings.brightness = args.bounded("Brightness", 0, 100)?,
+            "Device/SetScreenRotationAngle" => settings.rotation = args.bounded("Mode", 0, 3)?,
+            "Device/SetMirrorMode" => settings.mirrored = args.flag("Mode")?,
+            "Device/SetTime24Flag" => settings.time_24h = args.flag("Mode")?,
+            "Device/SetDisTempMode" => settings.fahrenheit = args.flag("Mode")?,
+            "Device/SetHighLightMode" => settings.overclock = args.flag("Mode")?,
+            "Device/SetWhiteBalance" => {
+                settings.white_balance = [
+                    args.bounded("RValue", 0, 100)?,
+                    args.bounded("GValue", 0, 100)?,
+                    args.bounded("BValue", 0, 100)?,
+                ];
+            }
+            "Sys/TimeZone" => {
+                let value = args.string("TimeZoneValue")?;
+                parse_timezone(&value).ok_or(Invalid)?;
+                settings.timezone = value;
+            }
+            "Sys/LogAndLat" => {
+                settings.longitude = Some(args.string("Longitude")?);
+                settings.latitude = Some(args.string("Latitude")?);
+            }
+            "Device/SetUTC" => {
+                self.utc_offset = args.int("Utc")? - Utc::now().timestamp();
+            }
+            _ => return Err(Invalid),
+        }
+        Ok(Reply::new())
+    }
+
+    fn all_conf(&self) -> Reply {
+        let settings = &self.settings;
+        reply([
+            ("LightSwitch", json!(i64::from(settings.display_on))),
+            ("Brightness", json!(settings.brightness)),
+            ("RotationFlag", json!(settings.rotation)),
+            ("MirrorFlag", json!(i64::from(settings.mirrored))),
+            ("Time24Flag", json!(i64::from(settings.time_24h))),
+            ("TemperatureMode", json!(i64::from(settings.fahrenheit))),
+            ("CurClockId", json!(settings.clock_id)),
+            ("GyrateAngle", json!(0)),
+            ("PowerOnChannelId", json!(0)),
+        ])
+    }
+
+    /// Seconds east of UTC for the configured timezone.
+    pub fn local_offset_secs(&self) -> i64 {
+        parse_timezone(&self.settings.timezone).unwrap_or(0) * 3600
+    }
+
+    /// Device clock in UTC, including any `Device/SetUTC` adjustment.
+    pub fn utc_now(&self) -> chrono::DateTime<Utc> {
+        Utc::now() + ChronoDuration::seconds(self.utc_offset)
+    }
+
+    fn device_time(&self) -> Reply {
+        let utc = self.utc_now();
+        let local = utc + ChronoDuration::seconds(self.local_offset_secs());
+        reply([
+            ("UTCTime", json!(utc.timestamp())),
+            (
+                "LocalTime",
+                json!(local.format("%Y-%m-%d %H:%M:%S").to_string()),
+            ),
+        ])
+    }
+
+    /// Current, minimum, and maximum temperature in the display unit.
+    fn temperatures(&self) -> [f64; 3] {
+        let celsius = [21.5, 14.0, 24.0];
+        if self.settings.fahrenheit {
+            celsius.map(|value: f64| value * 9.0 / 5.0 + 32.0)
+        } else {
+            celsius
+        }
+    }
+
+    fn weather(&self) -> Reply {
+        let [current, min, max] = self.temperatures();
+        reply([
+            ("Weather", json!(WEATHER)),
+            ("CurTemp", json!(current)),
+            ("MinTemp", json!(min)),
+            ("MaxTemp", json!(max)),
+            ("Pressure", json!(1013)),
+            ("Humidity", json!(45)),
+            ("Visibility", json!(10000)),
+            ("WindSpeed", json!(2.5)),
+        ])
+    }
+
+    fn send_gif(&mut self, args: &Args, now: Instant) -> Result<Reply, Invalid> {
+        let pic_id = args.int("PicId")?;
+        let pic_num = args.int("PicNum")?;
+        let offset = args.int("PicOffset")?;
+        let width = args.int("PicWidth")?;
+        let speed = args.int("PicSpeed")?;
+        if !(1..=MAX_FRAMES).contains(&pic_num) || !(0..pic_num).contains(&offset) {
+            return Err(Invalid);
+        }
+        let frame = decode_frame(&args.string("PicData")?, width)?;
+        let speed = Duration::from_millis(u64::try_from(speed).map_err(|_| Invalid)?);
+        let frame_count = usize::try_from(pic_num).map_err(|_| Invalid)?;
+
+        // A new PicId (or a different frame count) starts a new upload; the
+        // device silently drops any incomplete previous one.
+        let restart = self
+            .pending
+            .as_ref()
+            .is_none_or(|pending| pending.pic_id != pic_id || pending.frames.len() != frame_count);
+        if restart {
+            self.pending = Some(PendingAnimation {
+                pic_id,
+                frames: vec![None; frame_count],
+                speed,
+            });
+        }
+        let pending = self.pending.as_mut().ok_or(Invalid)?;
+        pending.frames[usize::try_from(offset).map_err(|_| Invalid)?] = Some(frame);
+
+        if pending.frames.iter().all(Option::is_some) {
+            let pending = self.pending.take().ok_or(Invalid)?;
+            self.screen = Screen::Animation(Animation {
+                frames: pending.frames.into_iter().flatten().collect(),
+                speed: pending.speed,
+                started: now,
+            });
+            self.texts.clear();
+            self.next_gif_id = pic_id + 1;
+        }
+        Ok(Reply::new())
+    }
+
+    fn send_text(&mut self, args: &Args, now: Instant) -> Result<Reply, Invalid> {
+        let id = args.int("TextId")?;
+        if !(0..MAX_TEXT_ITEMS).contains(&id) {
+            return Err(Invalid);
+        }
+        let item = text_item(args, args.string("TextString")?)?;
+        self.show_text_layer(now);
+        self.texts.insert(id, item);
+        Ok(Reply::new())
+    }
+
+    /// Adds every entry of `ItemList`. Live items show their value at the
+    /// time they arrive; the emulator does not refresh them.
+    fn send_item_list(&mut self, args: &Args, now: Instant) -> Result<Reply, Invalid> {
+        let Some(Value::Array(entries)) = args.0.get("ItemList") else {
+            return Err(Invalid);
+        };
+        let mut items = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let Value::Object(fields) = entry else {
+                return Err(Invalid);
+            };
+            let entry = Args(fields);
+            let id = entry.int("TextId")?;
+            if !(0..MAX_LIST_ITEMS).contains(&id) {
+                return Err(Invalid);
+            }
+            items.push((id, text_item(&entry, self.item_text(&entry)?)?));
+        }
+        self.show_text_layer(now);
+        self.texts.extend(items);
+        Ok(Reply::new())
+    }
+
+    /// Text shown by an `ItemList` entry, by its `type`.
+    fn item_text(&self, entry: &Args) -> Result<String, Invalid> {
+        let local = self.utc_now() + ChronoDuration::seconds(self.local_offset_secs());
+        let hour = if self.settings.time_24h { "%H" } else { "%I" };
+        let [current, low, high] = self.temperatures();
+        let unit = if self.settings.fahrenheit { 'F' } else { 'C' };
+        let text = match entry.int("type")? {
+            1 => local.format("%S").to_string(),
+            2 => local.format("%M").to_string(),
+            3 => local.format(hour).to_string(),
+            4 => local.format("%p").to_string(),
+            5 => local.format(&format!("{hour}:%M")).to_string(),
+            6 => local.format(&format!("{hour}:%M:%S")).to_string(),
+            7 => local.format("%Y").to_string(),
+            8 => local.format("%d").to_string(),
+            9 => local.format("%m").to_string(),
+            10 => local.format("%m-%Y").to_string(),
+            11 => local.format("%b %d %Y").to_string(),
+            14 => format!("{current:.0}{unit}"),
+            15 => format!("{high:.0}{unit}"),
+            16 => format!("{low:.0}{unit}"),
+            17 => WEATHER.to_string(),
+            22 => entry.string("TextString")?,
+            _ => return Err(Invalid),
+        };
+        Ok(text)
+    }
+
+    /// Text is drawn over the HTTP animation layer, so the device switches
+    /// to it; without a prior upload the background is black.
+    fn show_text_layer(&mut self, now: Instant) {
+        if !matches!(self.screen, Screen::Animation(_)) {
+            self.screen = Screen::Animation(Animation {
+                frames: vec![vec![0; FRAME_BYTES]],
+                speed: Duration::from_secs(1),
+                started: now,
+            });
+        }
+    }
+
+    fn set_timer(&mut self, args: &Args, now: Instant) -> Result<Reply, Invalid> {
+        let minutes = args.int("Minute")?;
+        let seconds = args.int("Second")?;
+        let remaining =
+            Duration::from_secs(u64::try_from(minutes * 60 + seconds).map_err(|_| Invalid)?);
+        self.screen = if args.flag("Status")? {
+            Screen::Timer {
+                ends_at: Some(now + remaining),
+                remaining,
+            }
+        } else {
+            Screen::Timer {
+                ends_at: None,
+                remaining,
+            }
+        };
+        Ok(Reply::new())
+    }
+
+    fn set_stopwatch(&mut self, args: &Args, now: Instant) -> Result<Reply, Invalid> {
+        let (running_since, elapsed) = match &self.screen {
+            Screen::Stopwatch {
+                running_since,
+                elapsed,
+            } => (*running_since, *elapsed),
+            _ => (None, Duration::ZERO),
+        };
+        let total = elapsed + running_since.map_or(Duration::ZERO, |since| now - since);
+        self.screen = match args.int("Status")? {
+            0 => Screen::Stopwatch {
+                running_since: None,
+                elapsed: total,
+            },
+            1 => Screen::Stopwatch {
+                running_since: Some(running_since.unwrap_or(now)),
+                elapsed,
+            },
+            2 => Screen::Stopwatch {
+                running_since: None,
+                elapsed: Duration::ZERO,
+            },
+            _ => return Err(Invalid),
+        };
+        Ok(Reply::new())
+    }
+
+    fn set_scoreboard(&mut self, args: &Args) -> Result<Reply, Invalid> {
+        let red: i64 = args.bounded("RedScore", 0, 999)?;
+        let blue: i64 = args.bounded("BlueScore", 0, 999)?;
+        self.screen = Screen::Scoreboard { red, blue };
+        Ok(Reply::new())
+    }
+}
+
+/// A command or argument the device does not accept.
+#[derive(Debug)]
+struct Invalid;
+
+/// Typed access to command arguments. Like the device, numbers sent as
+/// strings are accepted.
+struct Args<'a>(&'a Map<String, Value>);
+
+impl Args<'_> {
+    fn int(&self, key: &str) -> Result<i64, Invalid> {
+        match self.0.get(key) {
+            Some(Value::Number(number)) => number.as_i64().ok_or(Invalid),
+            Some(Value::String(text)) => text.trim().parse().map_err(|_| Invalid),
+            _ => Err(Invalid),
+        }
+    }
+
+    fn bounded<T: TryFrom<i64>>(&self, key: &str, min: i64, max: i64) -> Result<T, Invalid> {
+        let value = self.int(key)?;
+        if !(min..=max).contains(&value) {
+            return Err(Invalid);
+        }
+        T::try_from(value).map_err(|_| Invalid)
+    }
+
+    fn flag(&self, key: &str) -> Result<bool, Invalid> {
+        self.bounded::<u8>(key, 0, 1).map(|value| value == 1)
+    }
+
+    fn string(&self, key: &str) -> Result<String, Invalid> {
+        match self.0.get(key) {
+            Some(Value::String(text)) => Ok(text.clone()),
+            Some(Value::Number(number)) => Ok(number.to_string()),
+            _ => Err(Invalid),
+        }
+    }
+}
+
+/// Builds a text item from the layout fields shared by `SendHttpText` and
+/// `ItemList` entries.
+fn text_item(args: &Args, text: String) -> Result<TextItem, Invalid> {
+    let color = args.string("color")?;
+    parse_hex_color(&color).ok_or(Invalid)?;
+    Ok(TextItem {
+        x: args.int("x")?,
+        y: args.int("y")?,
+        text,
+        color,
+        font: args.int("font")?,
+        width: args.int("TextWidth")?,
+        speed: args.int("speed")?,
+        direction: args.int("dir")?,
+        align: args.int("align")?,
+    })
+}
+
+fn reply<const N: usize>(fields: [(&str, Value); N]) -> Reply {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key.to_string(), value))
+        .collect()
+}
+
+fn error_reply() -> Reply {
+    reply([("error_code", json!(ERROR_INVALID))])
+}
+
+/// Decodes base64 `PicData` of a `width`×`width` frame and scales it to the
+/// 64×64 screen.
+fn decode_frame(data: &str, width: i64) -> Result<Frame, Invalid> {
+    let width = match width {
+        16 | 32 | 64 => usize::try_from(width).map_err(|_| Invalid)?,
+        _ => return Err(Invalid),
+    };
+    let bytes = STANDARD.decode(data).map_err(|_| Invalid)?;
+    if bytes.len() != width * width * 3 {
+        return Err(Invalid);
+    }
+    let factor = SCREEN_DIM / width;
+    let mut frame = vec![0; FRAME_BYTES];
+    for y in 0..SCREEN_DIM {
+        for x in 0..SCREEN_DIM {
+            let source = ((y / factor) * width + x / factor) * 3;
+            let target = (y * SCREEN_DIM + x) * 3;
+            frame[target..target + 3].copy_from_slice(&bytes[source..source + 3]);
+        }
+    }
+    Ok(frame)
+}
+
+/// Hours east of UTC for a Pixoo `TimeZoneValue`. The device uses POSIX sign
+/// semantics, so `GMT-2` is two hours ahead of UTC.
+/// The emulator has no speaker; the pattern is only validated.
+fn play_buzzer(args: &Args) -> Result<Reply, Invalid> {
+    for key in ["ActiveTimeInCycle", "OffTimeInCycle", "PlayTotalTime"] {
+        args.bounded::<u32>(key, 0, i64::from(u32::MAX))?;
+    }
+    Ok(Reply::new())
+}
+
+fn parse_timezone(value: &str) -> Option<i64> {
+    let hours: i64 = value.strip_prefix("GMT")?.parse().ok()?;
+    (-14..=14).contains(&hours).then_some(-hours)
+}
+
+pub fn parse_hex_color(value: &str) -> Option<[u8; 3]> {
+    let hex = value.strip_prefix('#').unwrap_or(value);
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |range| u8::from_str_radix(&hex[range], 16).ok();
+    Some([channel(0..2)?, channel(2..4)?, channel(4..6)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(device: &mut Device, payload: &Value) -> Reply {
+        device.handle(payload.as_object().unwrap(), Instant::now())
+    }
+
+    fn frame_data(rgb: [u8; 3], width: usize) -> String {
+        STANDARD.encode(rgb.repeat(width * width))
+    }
+
+    fn gif(pic_id: i64, num: i64, offset: i64, rgb: [u8; 3]) -> Value {
+        json!({
+            "Command": "Draw/SendHttpGif",
+            "PicId": pic_id,
+            "PicNum": num,
+            "PicOffset": offset,
+            "PicWidth": 64,
+            "PicSpeed": 100,
+            "PicData": frame_data(rgb, 64),
+        })
+    }
+
+    #[test]
+    fn assembles_frames_and_advances_gif_id() {
+        let mut device = Device::default();
+        let reply = command(&mut device, &json!({ "Command": "Draw/GetHttpGifId" }));
+        assert_eq!(reply["PicId"], 1);
+
+        assert_eq!(
+            command(&mut device, &gif(1, 2, 1, [0, 0, 255]))["error_code"],
+            0
+        );
+        assert!(matches!(device.screen, Screen::Clock), "incomplete upload");
+        command(&mut device, &gif(1, 2, 0, [255, 0, 0]));
+
+        let Screen::Animation(animation) = &device.screen else {
+            panic!("expected animation");
+        };
+        assert_eq!(animation.frames.len(), 2);
+        assert_eq!(&animation.frames[0][..3], &[255, 0, 0]);
+        assert_eq!(&animation.frames[1][..3], &[0, 0, 255]);
+        assert_eq!(device.next_gif_id, 2);
+    }
+
+    #[test]
+    fn scales_small_frames_to_screen() {
+        let mut device = Device::default();
+        let mut payload = gif(1, 1, 0, [9, 9, 9]);
+        payload["PicWidth"] = json!(16);
+        payload["PicData"] = json!(frame_data([9, 9, 9], 16));
+        assert_eq!(command(&mut device, &payload)["error_code"], 0);
+
+        let Screen::Animation(animation) = &device.screen else {
+            panic!("expected animation");
+        };
+        assert_eq!(animation.frames[0].len(), FRAME_BYTES);
+    }
+
+    #[test]
+    fn rejects_invalid_commands_with_error_code() {
+        let mut device = Device::default();
+        let bad = [
+            json!({ "Command": "Device/Explode" }),
+            json!({ "Command": "Channel/SetBrightness", "Brightness": 101 }),
+            json!({ "Command": "Channel/OnOffScreen" }),
+            json!({ "NoCommand": true }),
+            gif(1, 1, 1, [0, 0, 0]),
+        ];
+        for payload in &bad {
+            assert_eq!(command(&mut device, payload)["error_code"], ERROR_INVALID);
+        }
+
+        let mut short = gif(1, 1, 0, [0, 0, 0]);
+        short["PicData"] = json!(frame_data([0, 0, 0], 8));
+        assert_eq!(command(&mut device, &short)["error_code"], ERROR_INVALID);
+    }
+
+    #[test]
+    fn settings_round_trip_through_get_all_conf() {
+        let mut device = Device::default();
+        command(
+            &mut device,
+            &json!({ "Command": "Channel/SetBrightness", "Brightness": 40 }),
+        );
+        command(
+            &mut device,
+            &json!({ "Command": "Channel/OnOffScreen", "OnOff": 0 }),
+        );
+        command(
+            &mut device,
+            &json!({ "Command": "Device/SetScreenRotationAngle", "Mode": 3 }),
+        );
+        command(
+            &mut device,
+            &json!({ "Command": "Device/SetDisTempMode", "Mode": "1" }),
+        );
+
+        let conf = command(&mut device, &json!({ "Command": "Channel/GetAllConf" }));
+        assert_eq!(conf["error_code"], 0);
+        assert_eq!(conf["Brightness"], 40);
+        assert_eq!(conf["LightSwitch"], 0);
+        assert_eq!(conf["RotationFlag"], 3);
+        assert_eq!(conf["TemperatureMode"], 1);
+    }
+
+    #[test]
+    fn switching_channels_leaves_drawing_mode() {
+        let mut device = Device::default();
+        command(&mut device, &gif(1, 1, 0, [1, 2, 3]));
+        assert!(matches!(device.screen, Screen::Animation(_)));
+
+        let reply = command(
+            &mut device,
+            &json!({ "Command": "Channel/SetClockSelectId", "ClockId": 182 }),
+        );
+        assert_eq!(reply["error_code"], 0);
+        assert!(matches!(device.screen, Screen::Clock));
+        let clock = command(&mut device, &json!({ "Command": "Channel/GetClockInfo" }));
+        assert_eq!(clock["ClockId"], 182);
+
+        command(
+            &mut device,
+            &json!({ "Command": "Channel/SetEqPosition", "EqPosition": 3 }),
+        );
+        let index = command(&mut device, &json!({ "Command": "Channel/GetIndex" }));
+        assert_eq!(index["SelectIndex"], 2);
+        assert_eq!(
+            command(
+                &mut device,
+                &json!({ "Command": "Channel/SetIndex", "SelectIndex": 4 })
+            )["error_code"],
+            ERROR_INVALID
+        );
+    }
+
+    #[test]
+    fn reports_local_time_for_timezone() {
+        let mut device = Device::default();
+        command(
+            &mut device,
+            &json!({ "Command": "Sys/TimeZone", "TimeZoneValue": "GMT-2" }),
+        );
+        assert_eq!(device.local_offset_secs(), 7200);
+
+        let time = command(&mut device, &json!({ "Command": "Device/GetDeviceTime" }));
+        assert!(time["UTCTime"].as_i64().unwrap() > 0);
+        assert_eq!(time["LocalTime"].as_str().unwrap().len(), 19);
+    }
+
+    #[test]
+    fn stores_and_clears_text_items() {
+        let mut device = Device::default();
+        let text = json!({
+            "Command": "Draw/SendHttpText",
+            "LcdId": 0, "TextId": 3, "x": 0, "y": 40, "dir": 0, "font": 2,
+            "TextWidth": 64, "speed": 10, "TextString": "hi", "color": "#FFFF00", "align": 1,
+        });
+        assert_eq!(command(&mut device, &text)["error_code"], 0);
+        assert_eq!(device.texts[&3].text, "hi");
+        assert!(matches!(device.screen, Screen::Animation(_)));
+
+        command(&mut device, &json!({ "Command": "Draw/ClearHttpText" }));
+        assert!(device.texts.is_empty());
+    }
+
+    #[test]
+    fn item_list_renders_live_items() {
+        let mut device = Device::default();
+        let entry = |id: i64, kind: i64| {
+            json!({
+                "TextId": id, "type": kind, "x": 0, "y": 0, "dir": 0, "font": 2,
+                "TextWidth": 64, "Textheight": 16, "speed": 100, "color": "#FFFFFF",
+                "align": 1, "TextString": "hello",
+            })
+        };
+        let list = json!({
+            "Command": "Draw/SendHttpItemList",
+            "ItemList": [entry(0, 22), entry(25, 17), entry(3, 14), entry(4, 7)],
+        });
+        assert_eq!(command(&mut device, &list)["error_code"], 0);
+        assert_eq!(device.texts[&0].text, "hello");
+        assert_eq!(device.texts[&25].text, "Sunny");
+        assert_eq!(device.texts[&3].text, "22C");
+        assert_eq!(device.texts[&4].text.len(), 4);
+
+        let unknown = json!({ "Command": "Draw/SendHttpItemList", "ItemList": [entry(1, 99)] });
+        assert_eq!(command(&mut device, &unknown)["error_code"], ERROR_INVALID);
+        assert!(!device.texts.contains_key(&1));
+    }
+
+    #[test]
+    fn stopwatch_accumulates_across_pauses() {
+        let mut device = Device::default();
+        let start = Instant::now();
+        let stopwatch = |status| {
+            json!({ "Command": "Tools/SetStopWatch", "Status": status })
+                .as_object()
+                .unwrap()
+                .clone()
+        };
+        device.handle(&stopwatch(1), start);
+        device.handle(&stopwatch(0), start + Duration::from_secs(5));
+
+        let Screen::Stopwatch {
+            running_since,
+            elapsed,
+        } = device.screen
+        else {
+            panic!("expected stopwatch");
+        };
+        assert_eq!(running_since, None);
+        assert_eq!(elapsed, Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,113 @@
+//! Pixoo 64 emulator for developing and testing the bridge without a panel.
+//!
+//! Serves the device HTTP protocol on `POST /post` and `GET /get`, keeps the
+//! device state in memory, and renders the screen at `/screen.png` and as a
+//! live page at `/`. Point the bridge at it with
+//! `PIXOO_BASE_URL=http://localhost:8080`.
+
+mod device;
+mod font;
+mod render;
+mod web;
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
+use web::{Emulator, Faults};
+
+const DEFAULT_PORT: u16 = 8080;
+
+#[derive(Debug, PartialEq)]
+struct EmulatorConfig {
+    port: u16,
+    log_level: LevelFilter,
+    faults: Faults,
+}
+
+impl EmulatorConfig {
+    /// Reads `PIXOO_EMULATOR_*` settings through `lookup`.
+    fn load(lookup: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        fn parse<T: std::str::FromStr>(
+            lookup: &impl Fn(&str) -> Option<String>,
+            key: &str,
+            default: T,
+        ) -> Result<T, String> {
+            match lookup(key).map(|raw| raw.trim().to_string()) {
+                Some(raw) if !raw.is_empty() => raw
+                    .parse()
+                    .map_err(|_| format!("invalid value '{raw}' for {key}")),
+                _ => Ok(default),
+            }
+        }
+
+        let faults = Faults {
+            latency_ms: parse(&lookup, "PIXOO_EMULATOR_LATENCY_MS", 0)?,
+            error_rate: parse(&lookup, "PIXOO_EMULATOR_ERROR_RATE", 0.0)?,
+            http_error_rate: parse(&lookup, "PIXOO_EMULATOR_HTTP_ERROR_RATE", 0.0)?,
+        };
+        if !faults.is_valid() {
+            return Err("error rates must be between 0 and 1".to_string());
+        }
+        Ok(Self {
+            port: parse(&lookup, "PIXOO_EMULATOR_PORT", DEFAULT_PORT)?,
+            log_level: parse(&lookup, "PIXOO_EMULATOR_LOG_LEVEL", LevelFilter::INFO)?,
+            faults,
+        })
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let config = EmulatorConfig::load(|key| std::env::var(key).ok())?;
+    tracing_subscriber::fmt()
+        .with_max_level(config.log_level)
+        .init();
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
+    let app = web::router(Arc::new(Emulator::new(config.faults)));
+    info!(address = %addr, faults = ?config.faults, "Pixoo emulator listening");
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(vars: &[(&str, &str)]) -> Result<EmulatorConfig, String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(key, value)| ((*key).to_string(), (*value).to_string()))
+            .collect();
+        EmulatorConfig::load(|key| vars.get(key).cloned())
+    }
+
+    #[test]
+    fn defaults_without_environment() {
+        let config = load(&[]).unwrap();
+        assert_eq!(config.port, DEFAULT_PORT);
+        assert_eq!(config.faults, Faults::default());
+    }
+
+    #[test]
+    fn reads_faults_and_rejects_invalid_values() {
+        let config = load(&[
+            ("PIXOO_EMULATOR_LATENCY_MS", "250"),
+            ("PIXOO_EMULATOR_ERROR_RATE", "0.1"),
+        ])
+        .unwrap();
+        assert_eq!(config.faults.latency_ms, 250);
+        assert!((config.faults.error_rate - 0.1).abs() < f64::EPSILON);
+
+        assert!(load(&[("PIXOO_EMULATOR_PORT", "http")]).is_err());
+        assert!(load(&[("PIXOO_EMULATOR_HTTP_ERROR_RATE", "1.5")]).is_err());
+    }
+}
@@ -0,0 +1,237 @@
+//! Renders the emulated screen to RGB pixels and PNG.
+//!
+//! The panel layer (animation plus text items, or a tool/clock screen) is
+//! drawn first; display settings are applied on top the way the hardware
+//! does: power, brightness and white balance scale the colors, rotation and
+//! mirroring move pixels.
+
+use crate::device::{parse_hex_color, Device, Frame, Screen, TextItem, FRAME_BYTES, SCREEN_DIM};
+use crate::font;
+use chrono::{Duration as ChronoDuration, Timelike};
+use image::{ImageBuffer, ImageFormat, Rgb};
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+const WHITE: [u8; 3] = [255, 255, 255];
+const RED: [u8; 3] = [255, 48, 48];
+const BLUE: [u8; 3] = [48, 96, 255];
+const GREEN: [u8; 3] = [48, 220, 96];
+
+/// Pixels of the physical screen at `now`.
+pub fn screen(device: &Device, now: Instant) -> Frame {
+    let settings = &device.settings;
+    if !settings.display_on {
+        return vec![0; FRAME_BYTES];
+    }
+    let mut canvas = Canvas::new();
+    match &device.screen {
+        Screen::Animation(animation) => {
+            canvas.pixels.clone_from(animation.frame_at(now));
+            for item in device.texts.values() {
+                canvas.text_item(item);
+            }
+        }
+        Screen::Clock => {
+            let local = device.utc_now() + ChronoDuration::seconds(device.local_offset_secs());
+            let hour = if settings.time_24h {
+                local.hour()
+            } else {
+                local.hour12().1
+            };
+            canvas.centered(29, &format!("{hour:02}:{:02}", local.minute()), WHITE);
+        }
+        Screen::Timer { ends_at, remaining } => {
+            let left = ends_at.map_or(*remaining, |end| end.saturating_duration_since(now));
+            canvas.centered(29, &minutes_seconds(left), WHITE);
+        }
+        Screen::Stopwatch {
+            running_since,
+            elapsed,
+        } => {
+            let total = *elapsed + running_since.map_or(Duration::ZERO, |since| now - since);
+            canvas.centered(29, &minutes_seconds(total), WHITE);
+        }
+        Screen::Scoreboard { red, blue } => {
+            canvas.text(8, 29, &red.to_string(), RED);
+            canvas.centered(29, "-", WHITE);
+            let blue = blue.to_string();
+            canvas.text(56 - i64_len(font::text_width(&blue)), 29, &blue, BLUE);
+        }
+        Screen::SoundMeter => {
+            for (index, height) in [6, 14, 24, 18, 30, 12, 20, 8].into_iter().enumerate() {
+                let x = 8 + i64_len(index) * 6;
+                for y in 0..height {
+                    for dx in 0..4 {
+                        canvas.set(x + dx, 52 - y, GREEN);
+                    }
+                }
+            }
+        }
+    }
+    apply_display_settings(device, &canvas.pixels)
+}
+
+/// `screen` as a PNG with every pixel drawn as a `scale`×`scale` block.
+pub fn png(pixels: &[u8], scale: u32) -> Vec<u8> {
+    let dim = u32::try_from(SCREEN_DIM).unwrap_or(64);
+    let image = ImageBuffer::from_fn(dim * scale, dim * scale, |x, y| {
+        let index = ((y / scale) * dim + x / scale) as usize * 3;
+        Rgb([pixels[index], pixels[index + 1], pixels[index + 2]])
+    });
+    let mut bytes = Cursor::new(Vec::new());
+    image
+        .write_to(&mut bytes, ImageFormat::Png)
+        .expect("encoding an in-memory RGB image cannot fail");
+    bytes.into_inner()
+}
+
+fn apply_display_settings(device: &Device, pixels: &[u8]) -> Frame {
+    let settings = &device.settings;
+    let levels = settings
+        .white_balance
+        .map(|channel| u32::from(channel) * u32::from(settings.brightness));
+    let mut output = vec![0; FRAME_BYTES];
+    for y in 0..SCREEN_DIM {
+        for x in 0..SCREEN_DIM {
+            let source_x = if settings.mirrored {
+                SCREEN_DIM - 1 - x
+            } else {
+                x
+            };
+            // Rotating the content clockwise means each output pixel reads
+            // from the counter-rotated source position.
+            let (sx, sy) = match settings.rotation {
+                1 => (y, SCREEN_DIM - 1 - source_x),
+                2 => (SCREEN_DIM - 1 - source_x, SCREEN_DIM - 1 - y),
+                3 => (SCREEN_DIM - 1 - y, source_x),
+                _ => (source_x, y),
+            };
+            let source = (sy * SCREEN_DIM + sx) * 3;
+            let target = (y * SCREEN_DIM + x) * 3;
+            for channel in 0..3 {
+                let scaled = u32::from(pixels[source + channel]) * levels[channel] / 10_000;
+                output[target + channel] = u8::try_from(scaled).unwrap_or(u8::MAX);
+            }
+        }
+    }
+    output
+}
+
+fn minutes_seconds(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+fn i64_len(value: usize) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+struct Canvas {
+    pixels: Frame,
+}
+
+impl Canvas {
+    fn new() -> Self {
+        Self {
+            pixels: vec![0; FRAME_BYTES],
+        }
+    }
+
+    /// Sets one pixel; coordinates outside the screen are clipped.
+    fn set(&mut self, x: i64, y: i64, rgb: [u8; 3]) {
+        let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y)) else {
+            return;
+        };
+        if x < SCREEN_DIM && y < SCREEN_DIM {
+            let index = (y * SCREEN_DIM + x) * 3;
+            self.pixels[index..index + 3].copy_from_slice(&rgb);
+        }
+    }
+
+    fn text(&mut self, x: i64, y: i64, text: &str, rgb: [u8; 3]) {
+        for (dx, dy) in font::pixels(text) {
+            self.set(x + i64_len(dx), y + i64_len(dy), rgb);
+        }
+    }
+
+    fn centered(&mut self, y: i64, text: &str, rgb: [u8; 3]) {
+        let width = i64_len(font::text_width(text));
+        self.text((i64_len(SCREEN_DIM) - width) / 2, y, text, rgb);
+    }
+
+    /// Draws a text item inside its box. Scrolling is not animated.
+    fn text_item(&mut self, item: &TextItem) {
+        let rgb = parse_hex_color(&item.color).unwrap_or(WHITE);
+        let slack = item.width - i64_len(font::text_width(&item.text));
+        let x = match item.align {
+            2 => item.x + slack / 2,
+            3 => item.x + slack,
+            _ => item.x,
+        };
+        self.text(x, item.y, &item.text, rgb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn pixel(frame: &[u8], x: usize, y: usize) -> [u8; 3] {
+        let index = (y * SCREEN_DIM + x) * 3;
+        [frame[index], frame[index + 1], frame[index + 2]]
+    }
+
+    /// Device showing a black screen with one red pixel at the top-left.
+    fn device_with_corner_pixel() -> Device {
+        let mut device = Device::default();
+        let mut frame = vec![0; FRAME_BYTES];
+        frame[0] = 255;
+        device.screen = Screen::Animation(crate::device::Animation {
+            frames: vec![frame],
+            speed: Duration::from_secs(1),
+            started: Instant::now(),
+        });
+        device
+    }
+
+    #[test]
+    fn applies_rotation_and_mirror() {
+        let mut device = device_with_corner_pixel();
+        let now = Instant::now();
+        assert_eq!(pixel(&screen(&device, now), 0, 0), [255, 0, 0]);
+
+        device.settings.rotation = 1;
+        assert_eq!(pixel(&screen(&device, now), 63, 0), [255, 0, 0]);
+
+        device.settings.rotation = 0;
+        device.settings.mirrored = true;
+        assert_eq!(pixel(&screen(&device, now), 63, 0), [255, 0, 0]);
+    }
+
+    #[test]
+    fn applies_brightness_and_power() {
+        let mut device = device_with_corner_pixel();
+        let now = Instant::now();
+        device.settings.brightness = 50;
+        assert_eq!(pixel(&screen(&device, now), 0, 0), [127, 0, 0]);
+
+        device.handle(
+            json!({ "Command": "Channel/OnOffScreen", "OnOff": 0 })
+                .as_object()
+                .unwrap(),
+            now,
+        );
+        assert!(screen(&device, now).iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn encodes_scaled_png() {
+        let device = device_with_corner_pixel();
+        let bytes = png(&screen(&device, Instant::now()), 4);
+        let image = image::load_from_memory(&bytes).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (256, 256));
+        assert_eq!(image.get_pixel(3, 3).0, [255, 0, 0]);
+        assert_eq!(image.get_pixel(4, 0).0, [0, 0, 0]);
+    }
+}
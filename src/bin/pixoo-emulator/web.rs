@@ -0,0 +1,331 @@
+//! HTTP surface of the emulator: the device protocol (`POST /post`,
+//! `GET /get`), a PNG of the screen, a JSON state dump, fault injection, and
+//! a small live view page.
+
+use crate::device::{Device, TextItem};
+use crate::render;
+use axum::body::Bytes;
+use axum::extract::{Query, State};
+use axum::http::{header::CONTENT_TYPE, StatusCode};
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+const DEFAULT_SCALE: u32 = 8;
+const MAX_SCALE: u32 = 16;
+
+/// Injected misbehaviour, applied to `/post` and `/get`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Faults {
+    /// Delay before every reply.
+    pub latency_ms: u64,
+    /// Share of commands (0–1) answered with a non-zero `error_code`.
+    pub error_rate: f64,
+    /// Share of requests (0–1) answered with HTTP 500.
+    pub http_error_rate: f64,
+}
+
+impl Faults {
+    pub fn is_valid(&self) -> bool {
+        (0.0..=1.0).contains(&self.error_rate) && (0.0..=1.0).contains(&self.http_error_rate)
+    }
+}
+
+/// `error_code` returned for injected device errors.
+const INJECTED_ERROR_CODE: i64 = 500;
+
+pub struct Emulator {
+    device: Mutex<Device>,
+    faults: Mutex<Faults>,
+    rng: Mutex<u64>,
+}
+
+impl Emulator {
+    pub fn new(faults: Faults) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0x2545_f491_4f6c_dd1d, |elapsed| {
+                u64::try_from(elapsed.as_nanos() & u128::from(u64::MAX)).unwrap_or(1)
+            });
+        Self {
+            device: Mutex::new(Device::default()),
+            faults: Mutex::new(faults),
+            rng: Mutex::new(seed | 1),
+        }
+    }
+
+    fn device(&self) -> std::sync::MutexGuard<'_, Device> {
+        self.device.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn faults(&self) -> Faults {
+        *self.faults.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether an event with probability `rate` happens this time.
+    fn roll(&self, rate: f64) -> bool {
+        if rate <= 0.0 {
+            return false;
+        }
+        // xorshift64: plenty for fault injection and needs no extra crate.
+        let mut state = self.rng.lock().unwrap_or_else(PoisonError::into_inner);
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        #[allow(clippy::cast_precision_loss)]
+        let sample = (*state >> 11) as f64 / (1u64 << 53) as f64;
+        sample < rate
+    }
+
+    /// Applies latency and returns an HTTP 500 when one is injected.
+    async fn inject(&self) -> Option<Response> {
+        let faults = self.faults();
+        if faults.latency_ms > 0 {
+            tokio::time::sleep(Duration::from_millis(faults.latency_ms)).await;
+        }
+        self.roll(faults.http_error_rate)
+            .then(|| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+    }
+}
+
+pub fn router(emulator: Arc<Emulator>) -> Router {
+    Router::new()
+        .route("/", get(index))
+        .route("/post", post(device_post))
+        .route("/get", get(device_get))
+        .route("/screen.png", get(screen_png))
+        .route("/state", get(state))
+        .route("/faults", get(get_faults).put(put_faults))
+        .with_state(emulator)
+}
+
+async fn device_post(State(emulator): State<Arc<Emulator>>, body: Bytes) -> Response {
+    if let Some(response) = emulator.inject().await {
+        return response;
+    }
+    // The panel answers malformed JSON with a textual error code.
+    let Ok(Value::Object(payload)) = serde_json::from_slice::<Value>(&body) else {
+        return Json(json!({ "error_code": "Request data illegal json" })).into_response();
+    };
+    let reply = if emulator.roll(emulator.faults().error_rate) {
+        let mut reply = Map::new();
+        reply.insert("error_code".to_string(), json!(INJECTED_ERROR_CODE));
+        reply
+    } else {
+        emulator.device().handle(&payload, Instant::now())
+    };
+    debug!(command = ?payload.get("Command"), error_code = %reply["error_code"], "command handled");
+    Json(reply).into_response()
+}
+
+async fn device_get(State(emulator): State<Arc<Emulator>>) -> Response {
+    if let Some(response) = emulator.inject().await {
+        return response;
+    }
+    Html("<html><body>Pixoo emulator</body></html>").into_response()
+}
+
+#[derive(Deserialize)]
+struct ScreenQuery {
+    scale: Option<u32>,
+}
+
+async fn screen_png(
+    State(emulator): State<Arc<Emulator>>,
+    Query(query): Query<ScreenQuery>,
+) -> Response {
+    let scale = query.scale.unwrap_or(DEFAULT_SCALE).clamp(1, MAX_SCALE);
+    let pixels = render::screen(&emulator.device(), Instant::now());
+    ([(CONTENT_TYPE, "image/png")], render::png(&pixels, scale)).into_response()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StateView {
+    screen: &'static str,
+    next_gif_id: i64,
+    settings: crate::device::Settings,
+    texts: BTreeMap<i64, TextItem>,
+    faults: Faults,
+}
+
+async fn state(State(emulator): State<Arc<Emulator>>) -> Response {
+    let view = {
+        let device = emulator.device();
+        StateView {
+            screen: device.screen.name(),
+            next_gif_id: device.next_gif_id,
+            settings: device.settings.clone(),
+            texts: device.texts.clone(),
+            faults: emulator.faults(),
+        }
+    };
+    Json(view).into_response()
+}
+
+async fn get_faults(State(emulator): State<Arc<Emulator>>) -> Response {
+    Json(emulator.faults()).into_response()
+}
+
+async fn put_faults(State(emulator): State<Arc<Emulator>>, Json(faults): Json<Faults>) -> Response {
+    if !faults.is_valid() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "message": "rates must be between 0 and 1" })),
+        )
+            .into_response();
+    }
+    *emulator
+        .faults
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = faults;
+    Json(faults).into_response()
+}
+
+async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+const INDEX_HTML: &str = r#"<!doctype html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Pixoo emulator</title>
+<style>
+  body { background: #111; color: #ddd; font-family: sans-serif; display: flex; gap: 2rem; padding: 2rem; }
+  img { image-rendering: pixelated; width: 512px; height: 512px; border: 8px solid #222; border-radius: 8px; }
+  pre { font-size: 12px; }
+</style>
+</head>
+<body>
+<img id="screen" src="screen.png" alt="Emulated screen">
+<pre id="state"></pre>
+<script>
+  async function refresh() {
+    document.getElementById("screen").src = "screen.png?t=" + Date.now();
+    const state = await fetch("state").then((r) => r.json()).catch(() => null);
+    if (state) document.getElementById("state").textContent = JSON.stringify(state, null, 2);
+  }
+  setInterval(refresh, 500);
+  refresh();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Method, Request};
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use tower::ServiceExt;
+
+    async fn send(app: &Router, method: Method, uri: &str, body: Value) -> (StatusCode, Vec<u8>) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, body.to_vec())
+    }
+
+    async fn command(app: &Router, payload: Value) -> Value {
+        let (status, body) = send(app, Method::POST, "/post", payload).await;
+        assert_eq!(status, StatusCode::OK);
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn draws_uploaded_frame_to_png() {
+        let app = router(Arc::new(Emulator::new(Faults::default())));
+        let pic_id =
+            command(&app, json!({ "Command": "Draw/GetHttpGifId" })).await["PicId"].clone();
+        let reply = command(
+            &app,
+            json!({
+                "Command": "Draw/SendHttpGif",
+                "PicId": pic_id, "PicNum": 1, "PicOffset": 0, "PicWidth": 64, "PicSpeed": 9999,
+                "PicData": STANDARD.encode([0u8, 200, 0].repeat(64 * 64)),
+            }),
+        )
+        .await;
+        assert_eq!(reply["error_code"], 0);
+
+        let (status, png) = send(&app, Method::GET, "/screen.png?scale=1", Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        let image = image::load_from_memory(&png).unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (64, 64));
+        assert_eq!(image.get_pixel(10, 10).0, [0, 200, 0]);
+    }
+
+    #[tokio::test]
+    async fn answers_malformed_json_like_the_device() {
+        let app = router(Arc::new(Emulator::new(Faults::default())));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/post")
+            .body(Body::from("{not json"))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let reply: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(reply["error_code"], "Request data illegal json");
+    }
+
+    #[tokio::test]
+    async fn injects_configured_faults() {
+        let app = router(Arc::new(Emulator::new(Faults::default())));
+        let (status, _) = send(
+            &app,
+            Method::PUT,
+            "/faults",
+            json!({ "errorRate": 1.0, "httpErrorRate": 0.0 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let reply = command(&app, json!({ "Command": "Channel/GetAllConf" })).await;
+        assert_eq!(reply["error_code"], INJECTED_ERROR_CODE);
+
+        send(
+            &app,
+            Method::PUT,
+            "/faults",
+            json!({ "httpErrorRate": 1.0 }),
+        )
+        .await;
+        let (status, _) = send(&app, Method::GET, "/get", Value::Null).await;
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+        let (status, _) = send(&app, Method::PUT, "/faults", json!({ "errorRate": 2.0 })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn exposes_state_snapshot() {
+        let app = router(Arc::new(Emulator::new(Faults::default())));
+        command(
+            &app,
+            json!({ "Command": "Channel/SetBrightness", "Brightness": 30 }),
+        )
+        .await;
+
+        let (_, body) = send(&app, Method::GET, "/state", Value::Null).await;
+        let state: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(state["screen"], "clock");
+        assert_eq!(state["settings"]["brightness"], 30);
+        assert_eq!(state["nextGifId"], 1);
+    }
+}
@@ -0,0 +1,171 @@
+//! TOML configuration file source.
+//!
+//! The file uses the same settings as the environment, with or without the
+//! `PIXOO_BRIDGE_`/`PIXOO_` prefix and in any case. Nested tables are joined
+//! with `_`, so all of these set `PIXOO_BRIDGE_REMOTE_TIMEOUT_MS`:
+//!
+//! ```toml
+//! remote_timeout_ms = 5000
+//! PIXOO_BRIDGE_REMOTE_TIMEOUT_MS = 5000
+//!
+//! [remote]
+//! timeout_ms = 5000
+//! ```
+//!
+//! Arrays are joined with newlines, which every list setting accepts as a
+//! separator.
+
+use super::{ConfigError, ConfigSource};
+use std::collections::HashMap;
+use std::path::Path;
+use toml::{Table, Value};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileConfigSource {
+    values: HashMap<String, String>,
+}
+
+impl FileConfigSource {
+    /// Reads and parses the TOML file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::InvalidConfigFile`] when the file cannot be read
+    /// or is not valid TOML.
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            ConfigError::InvalidConfigFile(format!("cannot read {}: {err}", path.display()))
+        })?;
+        Self::parse(&contents)
+            .map_err(|err| ConfigError::InvalidConfigFile(format!("{}: {err}", path.display())))
+    }
+
+    /// Parses TOML `contents` into flattened settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns the parser message when `contents` is not valid TOML.
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let table: Table = contents
+            .parse()
+            .map_err(|err: toml::de::Error| err.message().to_string())?;
+        let mut values = HashMap::new();
+        flatten("", &table, &mut values);
+        Ok(Self { values })
+    }
+
+    /// Keys (normalised, without prefix) whose values differ from `other`.
+    pub fn changed_keys(&self, other: &Self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .values
+            .keys()
+            .chain(other.values.keys())
+            .filter(|key| self.values.get(*key) != other.values.get(*key))
+            .cloned()
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+}
+
+impl ConfigSource for FileConfigSource {
+    fn get(&self, key: &str) -> Option<String> {
+        self.values.get(&normalize_key(key)).cloned()
+    }
+}
+
+/// Upper-cases `key` and strips the `PIXOO_BRIDGE_`/`PIXOO_` prefix.
+pub(super) fn normalize_key(key: &str) -> String {
+    let upper = key.to_ascii_uppercase();
+    upper
+        .strip_prefix("PIXOO_BRIDGE_")
+        .or_else(|| upper.strip_prefix("PIXOO_"))
+        .unwrap_or(&upper)
+        .to_string()
+}
+
+fn flatten(prefix: &str, table: &Table, values: &mut HashMap<String, String>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{prefix}_{key}")
+        };
+        match value {
+            Value::Table(nested) => flatten(&key, nested, values),
+            other => {
+                values.insert(normalize_key(&key), scalar_to_string(other));
+            }
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(scalar_to_string)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flattens_tables_and_strips_prefixes() {
+        let source = FileConfigSource::parse(
+            r#"
+            base_url = "http://10.0.0.5"
+            animation_speed_factor = 1.2
+            PIXOO_BRIDGE_HEALTH_FORWARD = false
+
+            [remote]
+            timeout_ms = 2500
+            allowed_hosts = ["*.example.com", "cam.lan"]
+            "#,
+        )
+        .expect("valid toml");
+
+        assert_eq!(
+            source.get("PIXOO_BASE_URL").as_deref(),
+            Some("http://10.0.0.5")
+        );
+        assert_eq!(
+            source.get("PIXOO_ANIMATION_SPEED_FACTOR").as_deref(),
+            Some("1.2")
+        );
+        assert_eq!(
+            source.get("PIXOO_BRIDGE_HEALTH_FORWARD").as_deref(),
+            Some("false")
+        );
+        assert_eq!(
+            source.get("PIXOO_BRIDGE_REMOTE_TIMEOUT_MS").as_deref(),
+            Some("2500")
+        );
+        assert_eq!(
+            source.get("PIXOO_BRIDGE_REMOTE_ALLOWED_HOSTS").as_deref(),
+            Some("*.example.com\ncam.lan")
+        );
+        assert!(source.get("PIXOO_BRIDGE_PORT").is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_toml() {
+        assert!(FileConfigSource::parse("port = ").is_err());
+    }
+
+    #[test]
+    fn reports_changed_keys() {
+        let before = FileConfigSource::parse("port = 4000\nmax_image_size = \"1MB\"").unwrap();
+        let after =
+            FileConfigSource::parse("port = 4001\nmax_image_size = \"1MB\"\nlog_level = \"debug\"")
+                .unwrap();
+        assert_eq!(before.changed_keys(&after), vec!["LOG_LEVEL", "PORT"]);
+    }
+}
@@ -0,0 +1,1350 @@
+mod file;
+mod reload;
+
+pub use file::FileConfigSource;
+pub use reload::{spawn_config_reloader, RuntimeSettings, SharedSettings, RELOAD_POLL_INTERVAL};
+
+use crate::auth::{ApiKey, AuthConfig};
+use crate::cors::CorsConfig;
+use crate::events::EventType;
+use crate::mqtt::MqttConfig;
+use crate::pixoo::{PixooClientConfig, RetryConfig, RetryPolicy};
+use crate::remote::{HostPattern, RemotePolicy};
+use crate::routes::DrawConflictPolicy;
+use crate::webhooks::WebhookConfig;
+use std::{env, error::Error, fmt, path::PathBuf, str::FromStr, time::Duration};
+use tracing::warn;
+
+const DEFAULT_LISTENER_PORT: u16 = 4000;
+const MIN_LISTENER_PORT: u16 = 1024;
+const MAX_LISTENER_PORT: u16 = 65535;
+const DEFAULT_REMOTE_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_DEVICE_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_ANIMATION_SPEED_FACTOR: f64 = 1.4;
+const DEFAULT_MAX_IMAGE_SIZE: usize = 5 * 1024 * 1024; // 5 MB
+const DEFAULT_REMOTE_CACHE_ENTRIES: usize = 16;
+const DEFAULT_REMOTE_MAX_REDIRECTS: usize = 5;
+const DEFAULT_HEALTH_POLL_SECS: u64 = 30;
+const DEFAULT_WEBHOOK_RETRIES: usize = 3;
+const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 5_000;
+const WEBHOOK_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MQTT_PORT: u16 = 1883;
+const DEFAULT_MQTT_CLIENT_ID: &str = "pixoo-bridge";
+const DEFAULT_MQTT_TOPIC_PREFIX: &str = "pixoo";
+const DEFAULT_MQTT_DISCOVERY_PREFIX: &str = "homeassistant";
+const DEFAULT_MQTT_STATE_INTERVAL_SECS: u64 = 30;
+
+/// Source for configuration values.
+///
+/// This trait abstracts environment variable access, allowing configuration
+/// functions to be tested with mock values instead of manipulating global
+/// process state. In production, use [`EnvConfigSource`] which reads from
+/// `std::env`. In tests, implement this trait with a simple `HashMap`.
+pub trait ConfigSource {
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+impl<T: ConfigSource + ?Sized> ConfigSource for &T {
+    fn get(&self, key: &str) -> Option<String> {
+        (**self).get(key)
+    }
+}
+
+/// Configuration source that reads from environment variables.
+pub struct EnvConfigSource;
+
+impl ConfigSource for EnvConfigSource {
+    fn get(&self, key: &str) -> Option<String> {
+        env::var(key).ok()
+    }
+}
+
+/// Reads from `primary` first and falls back to `fallback`, e.g. environment
+/// variables layered over a config file.
+pub struct LayeredConfigSource<P, F> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: ConfigSource, F: ConfigSource> LayeredConfigSource<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        Self { primary, fallback }
+    }
+
+    pub fn into_fallback(self) -> F {
+        self.fallback
+    }
+}
+
+impl<P: ConfigSource, F: ConfigSource> ConfigSource for LayeredConfigSource<P, F> {
+    fn get(&self, key: &str) -> Option<String> {
+        self.primary.get(key).or_else(|| self.fallback.get(key))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub pixoo_base_url: String,
+    pub pixoo_client: PixooClientConfig,
+    pub health_forward: bool,
+    pub listener_port: u16,
+    pub animation_speed_factor: f64,
+    pub max_image_size: usize,
+    pub remote_timeout: Duration,
+    pub remote_cache_entries: usize,
+    pub remote_cache_dir: Option<PathBuf>,
+    pub remote_policy: RemotePolicy,
+    pub auth: AuthConfig,
+    pub cors: CorsConfig,
+    pub mqtt: Option<MqttConfig>,
+    /// Background health check interval; `None` disables polling.
+    pub health_poll_interval: Option<Duration>,
+    pub webhooks: Option<WebhookConfig>,
+    pub draw_conflict: DrawConflictPolicy,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    MissingPixooBaseUrl,
+    InvalidPixooBaseUrl(String),
+    InvalidApiKeys(String),
+    InvalidCors(String),
+    InvalidConfigFile(String),
+    InvalidWebhooks(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingPixooBaseUrl => {
+                write!(f, "PIXOO_BASE_URL is required but was not set")
+            }
+            ConfigError::InvalidPixooBaseUrl(err) => {
+                write!(f, "PIXOO_BASE_URL is invalid: {err}")
+            }
+            ConfigError::InvalidApiKeys(err) => {
+                write!(f, "API key configuration is invalid: {err}")
+            }
+            ConfigError::InvalidCors(err) => {
+                write!(f, "CORS configuration is invalid: {err}")
+            }
+            ConfigError::InvalidConfigFile(err) => {
+                write!(f, "config file is invalid: {err}")
+            }
+            ConfigError::InvalidWebhooks(err) => {
+                write!(f, "webhook configuration is invalid: {err}")
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl AppConfig {
+    /// Settings that can be hot-reloaded from the config file.
+    pub fn runtime_settings(&self) -> RuntimeSettings {
+        RuntimeSettings {
+            animation_speed_factor: self.animation_speed_factor,
+            max_image_size: self.max_image_size,
+            remote_timeout: self.remote_timeout,
+            device_timeout: self.pixoo_client.timeout,
+        }
+    }
+
+    pub fn load_from(source: &impl ConfigSource) -> Result<Self, ConfigError> {
+        let health_forward = read_bool(source, "PIXOO_BRIDGE_HEALTH_FORWARD", true);
+        let pixoo_base_url = resolve_pixoo_base_url(source)?;
+        let remote_timeout = resolve_remote_timeout(source);
+        let pixoo_client = resolve_pixoo_client_config(source);
+        let listener_port = resolve_listener_port(source);
+        let animation_speed_factor = resolve_animation_speed_factor(source);
+        let max_image_size = resolve_max_image_size(source);
+        let remote_cache_entries = resolve_remote_cache_entries(source);
+        let remote_cache_dir = resolve_remote_cache_dir(source);
+        let remote_policy = resolve_remote_policy(source);
+        let auth = resolve_auth(source)?;
+        let cors = resolve_cors(source)?;
+        let mqtt = resolve_mqtt(source);
+        let health_poll_interval = resolve_health_poll_interval(source);
+        let webhooks = resolve_webhooks(source)?;
+        let draw_conflict = resolve_draw_conflict_policy(source);
+
+        Ok(Self {
+            pixoo_base_url,
+            pixoo_client,
+            health_forward,
+            listener_port,
+            animation_speed_factor,
+            max_image_size,
+            remote_timeout,
+            remote_cache_entries,
+            remote_cache_dir,
+            remote_policy,
+            auth,
+            cors,
+            mqtt,
+            health_poll_interval,
+            webhooks,
+            draw_conflict,
+        })
+    }
+}
+
+/// Path of the optional TOML config file layered under the environment.
+pub fn resolve_config_file(source: &impl ConfigSource) -> Option<PathBuf> {
+    source
+        .get("PIXOO_BRIDGE_CONFIG_FILE")
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// `PIXOO_BASE_URL` used to be optional and led to runtime 503s; now it is required at startup.
+fn resolve_pixoo_base_url(source: &impl ConfigSource) -> Result<String, ConfigError> {
+    let raw = source
+        .get("PIXOO_BASE_URL")
+        .ok_or(ConfigError::MissingPixooBaseUrl)?;
+    let value = raw.trim();
+    if value.is_empty() {
+        return Err(ConfigError::MissingPixooBaseUrl);
+    }
+    reqwest::Url::parse(value).map_err(|err| ConfigError::InvalidPixooBaseUrl(err.to_string()))?;
+    Ok(value.to_string())
+}
+
+/// Device timeout and retry policies. Both policies share the backoff
+/// settings; commands that are not idempotent have their own retry count and
+/// only ever retry failed connections.
+fn resolve_pixoo_client_config(source: &impl ConfigSource) -> PixooClientConfig {
+    let defaults = RetryConfig::default();
+    let millis = |key: &str, default: Duration| {
+        read_parsed::<u64>(source, key, |_| true).map_or(default, Duration::from_millis)
+    };
+    let base_delay = millis(
+        "PIXOO_BRIDGE_DEVICE_RETRY_BACKOFF_MS",
+        defaults.idempotent.base_delay,
+    );
+    let max_delay = millis(
+        "PIXOO_BRIDGE_DEVICE_RETRY_MAX_BACKOFF_MS",
+        defaults.idempotent.max_delay,
+    );
+    let jitter = read_parsed(
+        source,
+        "PIXOO_BRIDGE_DEVICE_RETRY_JITTER",
+        |jitter: &f64| (0.0..=1.0).contains(jitter),
+    )
+    .unwrap_or(defaults.idempotent.jitter);
+
+    PixooClientConfig {
+        timeout: resolve_device_timeout(source),
+        retry: RetryConfig {
+            idempotent: RetryPolicy {
+                max_retries: read_parsed(source, "PIXOO_BRIDGE_DEVICE_RETRIES", |_| true)
+                    .unwrap_or(defaults.idempotent.max_retries),
+                base_delay,
+                max_delay,
+                jitter,
+                ..defaults.idempotent
+            },
+            non_idempotent: RetryPolicy {
+                max_retries: read_parsed(
+                    source,
+                    "PIXOO_BRIDGE_DEVICE_NON_IDEMPOTENT_RETRIES",
+                    |_| true,
+                )
+                .unwrap_or(defaults.non_idempotent.max_retries),
+                base_delay,
+                max_delay,
+                jitter,
+                ..defaults.non_idempotent
+            },
+            budget_ratio: read_parsed(source, "PIXOO_BRIDGE_DEVICE_RETRY_BUDGET", |ratio: &f64| {
+                *ratio >= 0.0 && ratio.is_finite()
+            })
+            .unwrap_or(defaults.budget_ratio),
+        },
+    }
+}
+
+fn resolve_device_timeout(source: &impl ConfigSource) -> Duration {
+    read_parsed::<u64>(source, "PIXOO_BRIDGE_DEVICE_TIMEOUT_MS", |millis| {
+        *millis > 0
+    })
+    .map_or(
+        Duration::from_millis(DEFAULT_DEVICE_TIMEOUT_MS),
+        Duration::from_millis,
+    )
+}
+
+fn resolve_remote_timeout(source: &impl ConfigSource) -> Duration {
+    source
+        .get("PIXOO_BRIDGE_REMOTE_TIMEOUT_MS")
+        .and_then(|value| value.parse::<u64>().ok())
+        .map_or_else(
+            || Duration::from_millis(DEFAULT_REMOTE_TIMEOUT_MS),
+            Duration::from_millis,
+        )
+}
+
+fn resolve_remote_cache_entries(source: &impl ConfigSource) -> usize {
+    match source.get("PIXOO_BRIDGE_REMOTE_CACHE_ENTRIES") {
+        Some(raw) => {
+            if let Ok(entries) = raw.trim().parse::<usize>() {
+                entries
+            } else {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_REMOTE_CACHE_ENTRIES,
+                    "Invalid PIXOO_BRIDGE_REMOTE_CACHE_ENTRIES; falling back to default"
+                );
+                DEFAULT_REMOTE_CACHE_ENTRIES
+            }
+        }
+        None => DEFAULT_REMOTE_CACHE_ENTRIES,
+    }
+}
+
+fn resolve_remote_cache_dir(source: &impl ConfigSource) -> Option<PathBuf> {
+    source
+        .get("PIXOO_BRIDGE_REMOTE_CACHE_DIR")
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Outbound rules for remote fetches. Private and loopback targets are blocked
+/// unless `PIXOO_BRIDGE_REMOTE_ALLOW_PRIVATE` is set or the host is allowlisted.
+fn resolve_remote_policy(source: &impl ConfigSource) -> RemotePolicy {
+    let max_redirects = match source.get("PIXOO_BRIDGE_REMOTE_MAX_REDIRECTS") {
+        Some(raw) => {
+            if let Ok(value) = raw.trim().parse::<usize>() {
+                value
+            } else {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_REMOTE_MAX_REDIRECTS,
+                    "Invalid PIXOO_BRIDGE_REMOTE_MAX_REDIRECTS; falling back to default"
+                );
+                DEFAULT_REMOTE_MAX_REDIRECTS
+            }
+        }
+        None => DEFAULT_REMOTE_MAX_REDIRECTS,
+    };
+
+    RemotePolicy {
+        allowed_hosts: read_host_list(source, "PIXOO_BRIDGE_REMOTE_ALLOWED_HOSTS"),
+        denied_hosts: read_host_list(source, "PIXOO_BRIDGE_REMOTE_DENIED_HOSTS"),
+        block_private: !read_bool(source, "PIXOO_BRIDGE_REMOTE_ALLOW_PRIVATE", false),
+        max_redirects,
+    }
+}
+
+/// API keys come from `PIXOO_BRIDGE_API_KEYS` (entries separated by `;`) and
+/// `PIXOO_BRIDGE_API_KEYS_FILE` (one entry per line, `#` starts a comment).
+/// Each entry is `token` or `token:scope,scope`. Invalid entries fail startup
+/// instead of silently leaving routes unprotected.
+fn resolve_auth(source: &impl ConfigSource) -> Result<AuthConfig, ConfigError> {
+    let mut entries: Vec<String> = source
+        .get("PIXOO_BRIDGE_API_KEYS")
+        .map(|raw| raw.split([';', '\n']).map(str::to_string).collect())
+        .unwrap_or_default();
+
+    if let Some(path) = source
+        .get("PIXOO_BRIDGE_API_KEYS_FILE")
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+    {
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|err| ConfigError::InvalidApiKeys(format!("cannot read {path}: {err}")))?;
+        entries.extend(
+            contents
+                .lines()
+                .map(|line| line.split('#').next().unwrap_or_default().to_string()),
+        );
+    }
+
+    let keys = entries
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(ApiKey::parse)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ConfigError::InvalidApiKeys)?;
+    Ok(AuthConfig::new(keys))
+}
+
+/// CORS allow-lists. Each variable is a comma-separated list; unset, empty, or
+/// `*` keeps that dimension permissive.
+fn resolve_cors(source: &impl ConfigSource) -> Result<CorsConfig, ConfigError> {
+    let origins = read_cors_list(source, "PIXOO_BRIDGE_CORS_ALLOWED_ORIGINS", |value| {
+        value
+            .parse::<axum::http::HeaderValue>()
+            .map_err(|_| format!("invalid origin '{value}'"))
+    })?;
+    let methods = read_cors_list(source, "PIXOO_BRIDGE_CORS_ALLOWED_METHODS", |value| {
+        axum::http::Method::from_bytes(value.to_ascii_uppercase().as_bytes())
+            .map_err(|_| format!("invalid method '{value}'"))
+    })?;
+    let headers = read_cors_list(source, "PIXOO_BRIDGE_CORS_ALLOWED_HEADERS", |value| {
+        value
+            .parse::<axum::http::HeaderName>()
+            .map_err(|_| format!("invalid header '{value}'"))
+    })?;
+
+    Ok(CorsConfig {
+        origins,
+        methods,
+        headers,
+    })
+}
+
+/// `PIXOO_BRIDGE_HEALTH_POLL_SECS=0` disables background health checks.
+fn resolve_health_poll_interval(source: &impl ConfigSource) -> Option<Duration> {
+    let secs = match source.get("PIXOO_BRIDGE_HEALTH_POLL_SECS") {
+        Some(raw) => {
+            if let Ok(secs) = raw.trim().parse::<u64>() {
+                secs
+            } else {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_HEALTH_POLL_SECS,
+                    "Invalid PIXOO_BRIDGE_HEALTH_POLL_SECS; falling back to default"
+                );
+                DEFAULT_HEALTH_POLL_SECS
+            }
+        }
+        None => DEFAULT_HEALTH_POLL_SECS,
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn resolve_draw_conflict_policy(source: &impl ConfigSource) -> DrawConflictPolicy {
+    read_parsed(source, "PIXOO_BRIDGE_DRAW_CONFLICT", |_| true).unwrap_or_default()
+}
+
+/// Webhooks are enabled by `PIXOO_BRIDGE_WEBHOOK_URLS`. Invalid URLs or event
+/// names fail startup so notifications are not silently lost.
+fn resolve_webhooks(source: &impl ConfigSource) -> Result<Option<WebhookConfig>, ConfigError> {
+    let urls = split_list(source, "PIXOO_BRIDGE_WEBHOOK_URLS")
+        .iter()
+        .map(|url| {
+            reqwest::Url::parse(url)
+                .ok()
+                .filter(|parsed| matches!(parsed.scheme(), "http" | "https"))
+                .ok_or_else(|| ConfigError::InvalidWebhooks(format!("invalid URL '{url}'")))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if urls.is_empty() {
+        return Ok(None);
+    }
+
+    // `*` stands for the default event types; `command` must be named.
+    let events = split_list(source, "PIXOO_BRIDGE_WEBHOOK_EVENTS");
+    let wildcard = events.iter().any(|event| event == "*");
+    let mut named = events
+        .iter()
+        .filter(|event| *event != "*")
+        .map(|event| event.parse::<EventType>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ConfigError::InvalidWebhooks)?;
+    let events = if named.is_empty() {
+        None
+    } else {
+        if wildcard {
+            for event_type in EventType::ALL.into_iter().filter(|t| t.is_default()) {
+                if !named.contains(&event_type) {
+                    named.push(event_type);
+                }
+            }
+        }
+        Some(named)
+    };
+
+    let retries = match source.get("PIXOO_BRIDGE_WEBHOOK_RETRIES") {
+        Some(raw) => {
+            if let Ok(retries) = raw.trim().parse::<usize>() {
+                retries
+            } else {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_WEBHOOK_RETRIES,
+                    "Invalid PIXOO_BRIDGE_WEBHOOK_RETRIES; falling back to default"
+                );
+                DEFAULT_WEBHOOK_RETRIES
+            }
+        }
+        None => DEFAULT_WEBHOOK_RETRIES,
+    };
+    let timeout = source
+        .get("PIXOO_BRIDGE_WEBHOOK_TIMEOUT_MS")
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|millis| *millis > 0)
+        .map_or(
+            Duration::from_millis(DEFAULT_WEBHOOK_TIMEOUT_MS),
+            Duration::from_millis,
+        );
+
+    Ok(Some(WebhookConfig {
+        urls,
+        events,
+        secret: source
+            .get("PIXOO_BRIDGE_WEBHOOK_SECRET")
+            .filter(|secret| !secret.is_empty()),
+        retries,
+        backoff: WEBHOOK_BACKOFF,
+        timeout,
+    }))
+}
+
+/// Splits a comma- or newline-separated setting into trimmed, non-empty items.
+fn split_list(source: &impl ConfigSource, key: &str) -> Vec<String> {
+    source
+        .get(key)
+        .map(|raw| {
+            raw.split([',', '\n'])
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// MQTT is enabled by setting `PIXOO_BRIDGE_MQTT_HOST`. An empty
+/// `PIXOO_BRIDGE_MQTT_DISCOVERY_PREFIX` disables Home Assistant discovery.
+fn resolve_mqtt(source: &impl ConfigSource) -> Option<MqttConfig> {
+    let host = read_trimmed(source, "PIXOO_BRIDGE_MQTT_HOST")?;
+    let port = match source.get("PIXOO_BRIDGE_MQTT_PORT") {
+        Some(raw) => match raw.trim().parse::<u16>() {
+            Ok(port) if port > 0 => port,
+            _ => {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_MQTT_PORT,
+                    "Invalid PIXOO_BRIDGE_MQTT_PORT; falling back to default"
+                );
+                DEFAULT_MQTT_PORT
+            }
+        },
+        None => DEFAULT_MQTT_PORT,
+    };
+    let state_interval = match source.get("PIXOO_BRIDGE_MQTT_STATE_INTERVAL_SECS") {
+        Some(raw) => match raw.trim().parse::<u64>() {
+            Ok(secs) if secs > 0 => Duration::from_secs(secs),
+            _ => {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_MQTT_STATE_INTERVAL_SECS,
+                    "Invalid PIXOO_BRIDGE_MQTT_STATE_INTERVAL_SECS; falling back to default"
+                );
+                Duration::from_secs(DEFAULT_MQTT_STATE_INTERVAL_SECS)
+            }
+        },
+        None => Duration::from_secs(DEFAULT_MQTT_STATE_INTERVAL_SECS),
+    };
+    let discovery_prefix = match source.get("PIXOO_BRIDGE_MQTT_DISCOVERY_PREFIX") {
+        Some(raw) => Some(raw.trim().to_string()).filter(|prefix| !prefix.is_empty()),
+        None => Some(DEFAULT_MQTT_DISCOVERY_PREFIX.to_string()),
+    };
+
+    Some(MqttConfig {
+        host,
+        port,
+        client_id: read_trimmed(source, "PIXOO_BRIDGE_MQTT_CLIENT_ID")
+            .unwrap_or_else(|| DEFAULT_MQTT_CLIENT_ID.to_string()),
+        username: read_trimmed(source, "PIXOO_BRIDGE_MQTT_USERNAME"),
+        password: source.get("PIXOO_BRIDGE_MQTT_PASSWORD"),
+        topic_prefix: read_trimmed(source, "PIXOO_BRIDGE_MQTT_TOPIC_PREFIX")
+            .unwrap_or_else(|| DEFAULT_MQTT_TOPIC_PREFIX.to_string()),
+        discovery_prefix,
+        state_interval,
+    })
+}
+
+fn read_trimmed(source: &impl ConfigSource, key: &str) -> Option<String> {
+    source
+        .get(key)
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn read_cors_list<T>(
+    source: &impl ConfigSource,
+    key: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<Vec<T>>, ConfigError> {
+    let Some(raw) = source.get(key) else {
+        return Ok(None);
+    };
+    let values: Vec<&str> = raw
+        .split([',', '\n'])
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .collect();
+    if values.is_empty() || values.contains(&"*") {
+        return Ok(None);
+    }
+    values
+        .into_iter()
+        .map(|value| parse(value).map_err(|err| ConfigError::InvalidCors(format!("{key}: {err}"))))
+        .collect::<Result<Vec<_>, _>>()
+        .map(Some)
+}
+
+fn read_host_list(source: &impl ConfigSource, key: &str) -> Vec<HostPattern> {
+    source
+        .get(key)
+        .map(|raw| {
+            raw.split([',', '\n'])
+                .map(str::trim)
+                .filter(|host| !host.is_empty())
+                .map(HostPattern::new)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parses `key`; a value that does not parse or fails `valid` is logged and
+/// treated as unset.
+fn read_parsed<T: FromStr>(
+    source: &impl ConfigSource,
+    key: &str,
+    valid: impl Fn(&T) -> bool,
+) -> Option<T> {
+    let raw = source.get(key)?;
+    let parsed = raw.trim().parse::<T>().ok().filter(|value| valid(value));
+    if parsed.is_none() {
+        warn!(provided = %raw.trim(), "Invalid {key}; falling back to default");
+    }
+    parsed
+}
+
+fn read_bool(source: &impl ConfigSource, key: &str, default: bool) -> bool {
+    match source.get(key) {
+        Some(value) => match value.trim().to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => default,
+        },
+        None => default,
+    }
+}
+
+fn resolve_listener_port(source: &impl ConfigSource) -> u16 {
+    match source.get("PIXOO_BRIDGE_PORT") {
+        Some(raw) => {
+            let value = raw.trim();
+            match value.parse::<u16>() {
+                Ok(port) if (MIN_LISTENER_PORT..=MAX_LISTENER_PORT).contains(&port) => port,
+                _ => {
+                    warn!(
+                        provided = %value,
+                        min = MIN_LISTENER_PORT,
+                        max = MAX_LISTENER_PORT,
+                        default_port = DEFAULT_LISTENER_PORT,
+                        "Invalid PIXOO_BRIDGE_PORT; falling back to default port {}",
+                        DEFAULT_LISTENER_PORT
+                    );
+                    DEFAULT_LISTENER_PORT
+                }
+            }
+        }
+        None => DEFAULT_LISTENER_PORT,
+    }
+}
+
+fn resolve_animation_speed_factor(source: &impl ConfigSource) -> f64 {
+    match source.get("PIXOO_ANIMATION_SPEED_FACTOR") {
+        Some(raw) => {
+            let value = raw.trim();
+            match value.parse::<f64>() {
+                Ok(factor) if factor > 0.0 && factor.is_finite() => factor,
+                _ => {
+                    warn!(
+                        provided = %value,
+                        default = DEFAULT_ANIMATION_SPEED_FACTOR,
+                        "Invalid PIXOO_ANIMATION_SPEED_FACTOR; falling back to default"
+                    );
+                    DEFAULT_ANIMATION_SPEED_FACTOR
+                }
+            }
+        }
+        None => DEFAULT_ANIMATION_SPEED_FACTOR,
+    }
+}
+
+fn resolve_max_image_size(source: &impl ConfigSource) -> usize {
+    match source.get("PIXOO_BRIDGE_MAX_IMAGE_SIZE") {
+        Some(raw) => match parse_byte_size(raw.trim()) {
+            Some(size) if size > 0 => size,
+            _ => {
+                warn!(
+                    provided = %raw.trim(),
+                    default = DEFAULT_MAX_IMAGE_SIZE,
+                    "Invalid PIXOO_BRIDGE_MAX_IMAGE_SIZE; falling back to default"
+                );
+                DEFAULT_MAX_IMAGE_SIZE
+            }
+        },
+        None => DEFAULT_MAX_IMAGE_SIZE,
+    }
+}
+
+/// Parses a human-readable byte size string into bytes.
+///
+/// Accepts formats like `5MB`, `128KB`, `1024B`, `5M`, `128K` (case-insensitive).
+/// Uses binary units: 1 KB = 1024 bytes, 1 MB = 1024 * 1024 bytes.
+fn parse_byte_size(input: &str) -> Option<usize> {
+    let input = input.trim();
+    if input.is_empty() {
+        return None;
+    }
+
+    let upper = input.to_ascii_uppercase();
+
+    // Find where the numeric part ends and the suffix begins
+    let num_end = upper
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(upper.len());
+
+    let (num_str, suffix) = upper.split_at(num_end);
+    let number: usize = num_str.parse().ok()?;
+
+    let multiplier: usize = match suffix {
+        "" | "B" => 1,
+        "K" | "KB" => 1024,
+        "M" | "MB" => 1024 * 1024,
+        "G" | "GB" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    number.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::ConfigSource;
+    use std::collections::HashMap;
+
+    pub struct MockConfig(HashMap<&'static str, &'static str>);
+
+    impl MockConfig {
+        pub fn new() -> Self {
+            Self(HashMap::new())
+        }
+
+        pub fn with(mut self, key: &'static str, value: &'static str) -> Self {
+            self.0.insert(key, value);
+            self
+        }
+    }
+
+    impl ConfigSource for MockConfig {
+        fn get(&self, key: &str) -> Option<String> {
+            self.0.get(key).map(|s| (*s).to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::MockConfig;
+    use super::*;
+    use crate::auth::{AuthDecision, Scope};
+
+    #[test]
+    fn pixoo_base_url_missing_is_error() {
+        let config = MockConfig::new();
+        let err = resolve_pixoo_base_url(&config).expect_err("expected missing base url error");
+        assert_eq!(err, ConfigError::MissingPixooBaseUrl);
+    }
+
+    #[test]
+    fn pixoo_base_url_invalid_is_error() {
+        let config = MockConfig::new().with("PIXOO_BASE_URL", "not a url");
+        let err = resolve_pixoo_base_url(&config).expect_err("expected invalid base url error");
+        assert!(matches!(err, ConfigError::InvalidPixooBaseUrl(_)));
+    }
+
+    #[test]
+    fn pixoo_base_url_valid_is_loaded() {
+        let config = MockConfig::new().with("PIXOO_BASE_URL", "http://127.0.0.1");
+        let value = resolve_pixoo_base_url(&config).expect("expected base url");
+        assert_eq!(value, "http://127.0.0.1");
+    }
+
+    #[test]
+    fn remote_timeout_uses_env_override() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_REMOTE_TIMEOUT_MS", "250");
+        let timeout = resolve_remote_timeout(&config);
+        let client_config = resolve_pixoo_client_config(&config);
+        assert_eq!(timeout, Duration::from_millis(250));
+        assert_eq!(
+            client_config.timeout,
+            Duration::from_millis(DEFAULT_DEVICE_TIMEOUT_MS)
+        );
+    }
+
+    #[test]
+    fn device_retry_policy_uses_env_overrides() {
+        let config = MockConfig::new()
+            .with("PIXOO_BRIDGE_DEVICE_TIMEOUT_MS", "1500")
+            .with("PIXOO_BRIDGE_DEVICE_RETRIES", "4")
+            .with("PIXOO_BRIDGE_DEVICE_NON_IDEMPOTENT_RETRIES", "0")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_BACKOFF_MS", "50")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_MAX_BACKOFF_MS", "400")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_JITTER", "0.5")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_BUDGET", "1");
+        let client_config = resolve_pixoo_client_config(&config);
+        let retry = client_config.retry;
+
+        assert_eq!(client_config.timeout, Duration::from_millis(1500));
+        assert_eq!(retry.idempotent.max_retries, 4);
+        assert_eq!(retry.non_idempotent.max_retries, 0);
+        assert_eq!(retry.non_idempotent.base_delay, Duration::from_millis(50));
+        assert_eq!(retry.idempotent.max_delay, Duration::from_millis(400));
+        assert!((retry.idempotent.jitter - 0.5).abs() < f64::EPSILON);
+        assert!((retry.budget_ratio - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn device_retry_policy_falls_back_on_invalid_values() {
+        let config = MockConfig::new()
+            .with("PIXOO_BRIDGE_DEVICE_TIMEOUT_MS", "0")
+            .with("PIXOO_BRIDGE_DEVICE_RETRIES", "-1")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_JITTER", "1.5")
+            .with("PIXOO_BRIDGE_DEVICE_RETRY_BUDGET", "-0.1");
+        let client_config = resolve_pixoo_client_config(&config);
+
+        assert_eq!(
+            client_config.timeout,
+            Duration::from_millis(DEFAULT_DEVICE_TIMEOUT_MS)
+        );
+        assert_eq!(client_config.retry, RetryConfig::default());
+    }
+
+    #[test]
+    fn listener_port_defaults_to_4000_when_env_missing() {
+        let config = MockConfig::new();
+        let port = resolve_listener_port(&config);
+        assert_eq!(port, DEFAULT_LISTENER_PORT);
+    }
+
+    #[test]
+    fn listener_port_uses_custom_override_when_valid() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_PORT", "5050");
+        let port = resolve_listener_port(&config);
+        assert_eq!(port, 5050);
+    }
+
+    #[test]
+    fn listener_port_falls_back_on_invalid_values() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_PORT", "not-a-port");
+        let port = resolve_listener_port(&config);
+        assert_eq!(port, DEFAULT_LISTENER_PORT);
+    }
+
+    #[test]
+    fn remote_cache_defaults_to_memory_only() {
+        let config = MockConfig::new();
+        assert_eq!(
+            resolve_remote_cache_entries(&config),
+            DEFAULT_REMOTE_CACHE_ENTRIES
+        );
+        assert!(resolve_remote_cache_dir(&config).is_none());
+    }
+
+    #[test]
+    fn remote_cache_uses_env_overrides() {
+        let config = MockConfig::new()
+            .with("PIXOO_BRIDGE_REMOTE_CACHE_ENTRIES", "0")
+            .with("PIXOO_BRIDGE_REMOTE_CACHE_DIR", "/var/cache/pixoo");
+        assert_eq!(resolve_remote_cache_entries(&config), 0);
+        assert_eq!(
+            resolve_remote_cache_dir(&config),
+            Some(PathBuf::from("/var/cache/pixoo"))
+        );
+    }
+
+    #[test]
+    fn remote_cache_entries_fall_back_on_invalid() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_REMOTE_CACHE_ENTRIES", "many");
+        assert_eq!(
+            resolve_remote_cache_entries(&config),
+            DEFAULT_REMOTE_CACHE_ENTRIES
+        );
+    }
+
+    // --- parse_byte_size ---
+
+    #[test]
+    fn parse_byte_size_megabytes() {
+        assert_eq!(parse_byte_size("5MB"), Some(5 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_megabytes_without_b() {
+        assert_eq!(parse_byte_size("5M"), Some(5 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_kilobytes() {
+        assert_eq!(parse_byte_size("128KB"), Some(128 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_kilobytes_without_b() {
+        assert_eq!(parse_byte_size("128K"), Some(128 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_bytes() {
+        assert_eq!(parse_byte_size("1024B"), Some(1024));
+    }
+
+    #[test]
+    fn parse_byte_size_plain_number() {
+        assert_eq!(parse_byte_size("2048"), Some(2048));
+    }
+
+    #[test]
+    fn parse_byte_size_case_insensitive() {
+        assert_eq!(parse_byte_size("10mb"), Some(10 * 1024 * 1024));
+        assert_eq!(parse_byte_size("10Mb"), Some(10 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_gigabytes() {
+        assert_eq!(parse_byte_size("1GB"), Some(1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn parse_byte_size_invalid_returns_none() {
+        assert_eq!(parse_byte_size("lots"), None);
+        assert_eq!(parse_byte_size(""), None);
+        assert_eq!(parse_byte_size("MB"), None);
+        assert_eq!(parse_byte_size("5TB"), None);
+    }
+
+    // --- animation speed factor ---
+
+    #[test]
+    fn animation_speed_factor_defaults_when_missing() {
+        let config = MockConfig::new();
+        let factor = resolve_animation_speed_factor(&config);
+        assert!((factor - DEFAULT_ANIMATION_SPEED_FACTOR).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn animation_speed_factor_uses_valid_override() {
+        let config = MockConfig::new().with("PIXOO_ANIMATION_SPEED_FACTOR", "2.0");
+        let factor = resolve_animation_speed_factor(&config);
+        assert!((factor - 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn animation_speed_factor_falls_back_on_invalid() {
+        let config = MockConfig::new().with("PIXOO_ANIMATION_SPEED_FACTOR", "abc");
+        let factor = resolve_animation_speed_factor(&config);
+        assert!((factor - DEFAULT_ANIMATION_SPEED_FACTOR).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn animation_speed_factor_falls_back_on_zero() {
+        let config = MockConfig::new().with("PIXOO_ANIMATION_SPEED_FACTOR", "0");
+        let factor = resolve_animation_speed_factor(&config);
+        assert!((factor - DEFAULT_ANIMATION_SPEED_FACTOR).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn animation_speed_factor_falls_back_on_negative() {
+        let config = MockConfig::new().with("PIXOO_ANIMATION_SPEED_FACTOR", "-1.0");
+        let factor = resolve_animation_speed_factor(&config);
+        assert!((factor - DEFAULT_ANIMATION_SPEED_FACTOR).abs() < f64::EPSILON);
+    }
+
+    // --- max image size ---
+
+    #[test]
+    fn max_image_size_defaults_when_missing() {
+        let config = MockConfig::new();
+        let size = resolve_max_image_size(&config);
+        assert_eq!(size, DEFAULT_MAX_IMAGE_SIZE);
+    }
+
+    #[test]
+    fn max_image_size_uses_valid_megabyte_override() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_MAX_IMAGE_SIZE", "10MB");
+        let size = resolve_max_image_size(&config);
+        assert_eq!(size, 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn max_image_size_uses_valid_kilobyte_override() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_MAX_IMAGE_SIZE", "128K");
+        let size = resolve_max_image_size(&config);
+        assert_eq!(size, 128 * 1024);
+    }
+
+    #[test]
+    fn max_image_size_falls_back_on_invalid() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_MAX_IMAGE_SIZE", "lots");
+        let size = resolve_max_image_size(&config);
+        assert_eq!(size, DEFAULT_MAX_IMAGE_SIZE);
+    }
+
+    #[test]
+    fn remote_policy_blocks_private_by_default() {
+        let policy = resolve_remote_policy(&MockConfig::new());
+        assert!(policy.block_private);
+        assert!(policy.allowed_hosts.is_empty());
+        assert!(policy.denied_hosts.is_empty());
+        assert_eq!(policy.max_redirects, DEFAULT_REMOTE_MAX_REDIRECTS);
+    }
+
+    #[test]
+    fn remote_policy_uses_env_overrides() {
+        let config = MockConfig::new()
+            .with(
+                "PIXOO_BRIDGE_REMOTE_ALLOWED_HOSTS",
+                "*.example.com, cam.lan ,",
+            )
+            .with("PIXOO_BRIDGE_REMOTE_DENIED_HOSTS", "admin.example.com")
+            .with("PIXOO_BRIDGE_REMOTE_ALLOW_PRIVATE", "true")
+            .with("PIXOO_BRIDGE_REMOTE_MAX_REDIRECTS", "0");
+        let policy = resolve_remote_policy(&config);
+        assert_eq!(
+            policy.allowed_hosts,
+            vec![
+                HostPattern::new("*.example.com"),
+                HostPattern::new("cam.lan")
+            ]
+        );
+        assert_eq!(
+            policy.denied_hosts,
+            vec![HostPattern::new("admin.example.com")]
+        );
+        assert!(!policy.block_private);
+        assert_eq!(policy.max_redirects, 0);
+    }
+
+    #[test]
+    fn remote_max_redirects_fall_back_on_invalid() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_REMOTE_MAX_REDIRECTS", "lots");
+        assert_eq!(
+            resolve_remote_policy(&config).max_redirects,
+            DEFAULT_REMOTE_MAX_REDIRECTS
+        );
+    }
+
+    #[test]
+    fn auth_disabled_without_keys() {
+        let auth = resolve_auth(&MockConfig::new()).expect("auth");
+        assert!(!auth.is_enabled());
+    }
+
+    #[test]
+    fn auth_reads_keys_from_env() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_API_KEYS", "admin; drawer:draw,tools ;");
+        let auth = resolve_auth(&config).expect("auth");
+        assert_eq!(auth.key_count(), 2);
+        assert_eq!(
+            auth.authorize(Some("drawer"), Scope::Tools),
+            AuthDecision::Allowed
+        );
+        assert_eq!(
+            auth.authorize(Some("drawer"), Scope::System),
+            AuthDecision::MissingScope
+        );
+        assert_eq!(
+            auth.authorize(Some("admin"), Scope::System),
+            AuthDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn auth_reads_keys_from_file() {
+        let path = env::temp_dir().join(format!("pixoo-keys-{}.txt", std::process::id()));
+        std::fs::write(&path, "# bridge keys\nops:manage,system # ops team\n\n").unwrap();
+        let path_value: &'static str = Box::leak(path.to_string_lossy().into_owned().into());
+        let config = MockConfig::new()
+            .with("PIXOO_BRIDGE_API_KEYS", "drawer:draw")
+            .with("PIXOO_BRIDGE_API_KEYS_FILE", path_value);
+        let auth = resolve_auth(&config).expect("auth");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(auth.key_count(), 2);
+        assert_eq!(
+            auth.authorize(Some("ops"), Scope::Manage),
+            AuthDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn auth_rejects_unknown_scopes_and_missing_files() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_API_KEYS", "key:everything");
+        assert!(matches!(
+            resolve_auth(&config),
+            Err(ConfigError::InvalidApiKeys(_))
+        ));
+
+        let config =
+            MockConfig::new().with("PIXOO_BRIDGE_API_KEYS_FILE", "/nonexistent/pixoo-keys");
+        assert!(matches!(
+            resolve_auth(&config),
+            Err(ConfigError::InvalidApiKeys(_))
+        ));
+    }
+
+    #[test]
+    fn cors_defaults_to_permissive() {
+        let cors = resolve_cors(&MockConfig::new()).expect("cors");
+        assert!(cors.is_permissive());
+        assert!(cors.methods.is_none());
+        assert!(cors.headers.is_none());
+    }
+
+    #[test]
+    fn cors_uses_env_overrides() {
+        let config = MockConfig::new()
+            .with(
+                "PIXOO_BRIDGE_CORS_ALLOWED_ORIGINS",
+                "http://dashboard.lan, https://home.example",
+            )
+            .with("PIXOO_BRIDGE_CORS_ALLOWED_METHODS", "get,POST")
+            .with("PIXOO_BRIDGE_CORS_ALLOWED_HEADERS", "*");
+        let cors = resolve_cors(&config).expect("cors");
+        assert!(!cors.is_permissive());
+        assert_eq!(cors.origins.as_ref().map(Vec::len), Some(2));
+        assert_eq!(
+            cors.methods,
+            Some(vec![axum::http::Method::GET, axum::http::Method::POST])
+        );
+        assert!(cors.headers.is_none());
+    }
+
+    #[test]
+    fn cors_rejects_invalid_values() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_CORS_ALLOWED_HEADERS", "bad header");
+        assert!(matches!(
+            resolve_cors(&config),
+            Err(ConfigError::InvalidCors(_))
+        ));
+    }
+
+    #[test]
+    fn health_poll_defaults_and_can_be_disabled() {
+        assert_eq!(
+            resolve_health_poll_interval(&MockConfig::new()),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            resolve_health_poll_interval(
+                &MockConfig::new().with("PIXOO_BRIDGE_HEALTH_POLL_SECS", "0")
+            ),
+            None
+        );
+        assert_eq!(
+            resolve_health_poll_interval(
+                &MockConfig::new().with("PIXOO_BRIDGE_HEALTH_POLL_SECS", "often")
+            ),
+            Some(Duration::from_secs(30))
+        );
+    }
+
+    #[test]
+    fn webhooks_disabled_without_urls() {
+        assert_eq!(resolve_webhooks(&MockConfig::new()), Ok(None));
+    }
+
+    #[test]
+    fn webhooks_use_env_overrides() {
+        let config = resolve_webhooks(
+            &MockConfig::new()
+                .with(
+                    "PIXOO_BRIDGE_WEBHOOK_URLS",
+                    "https://hooks.example.com/pixoo, http://nas.lan:8080/in",
+                )
+                .with(
+                    "PIXOO_BRIDGE_WEBHOOK_EVENTS",
+                    "device-offline,device-online",
+                )
+                .with("PIXOO_BRIDGE_WEBHOOK_SECRET", "s3cret")
+                .with("PIXOO_BRIDGE_WEBHOOK_RETRIES", "5")
+                .with("PIXOO_BRIDGE_WEBHOOK_TIMEOUT_MS", "1500"),
+        )
+        .unwrap()
+        .expect("webhooks enabled");
+
+        assert_eq!(config.urls.len(), 2);
+        assert_eq!(
+            config.events,
+            Some(vec![EventType::DeviceOffline, EventType::DeviceOnline])
+        );
+        assert_eq!(config.secret.as_deref(), Some("s3cret"));
+        assert_eq!(config.retries, 5);
+        assert_eq!(config.timeout, Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn webhooks_default_to_all_events() {
+        let config = resolve_webhooks(
+            &MockConfig::new().with("PIXOO_BRIDGE_WEBHOOK_URLS", "https://hooks.example.com"),
+        )
+        .unwrap()
+        .expect("webhooks enabled");
+        assert_eq!(config.events, None);
+        assert_eq!(config.retries, 3);
+        assert_eq!(config.secret, None);
+    }
+
+    #[test]
+    fn webhooks_deliver_commands_only_when_named() {
+        let resolve = |events: &'static str| {
+            resolve_webhooks(
+                &MockConfig::new()
+                    .with("PIXOO_BRIDGE_WEBHOOK_URLS", "https://hooks.example.com")
+                    .with("PIXOO_BRIDGE_WEBHOOK_EVENTS", events),
+            )
+            .unwrap()
+            .expect("webhooks enabled")
+        };
+
+        let all = resolve("*");
+        assert_eq!(all.events, None);
+        assert!(!all.accepts(EventType::Command));
+
+        let with_commands = resolve("*,command");
+        assert!(with_commands.accepts(EventType::Command));
+        assert!(with_commands.accepts(EventType::DrawCompleted));
+        assert_eq!(with_commands.events.unwrap().len(), EventType::ALL.len());
+    }
+
+    #[test]
+    fn webhooks_reject_invalid_urls_and_events() {
+        assert!(matches!(
+            resolve_webhooks(&MockConfig::new().with("PIXOO_BRIDGE_WEBHOOK_URLS", "ftp://nas")),
+            Err(ConfigError::InvalidWebhooks(_))
+        ));
+        assert!(matches!(
+            resolve_webhooks(
+                &MockConfig::new()
+                    .with("PIXOO_BRIDGE_WEBHOOK_URLS", "https://hooks.example.com")
+                    .with("PIXOO_BRIDGE_WEBHOOK_EVENTS", "device-exploded")
+            ),
+            Err(ConfigError::InvalidWebhooks(_))
+        ));
+    }
+
+    #[test]
+    fn mqtt_disabled_without_host() {
+        assert!(resolve_mqtt(&MockConfig::new()).is_none());
+    }
+
+    #[test]
+    fn mqtt_uses_defaults() {
+        let config = resolve_mqtt(&MockConfig::new().with("PIXOO_BRIDGE_MQTT_HOST", "broker.lan"))
+            .expect("mqtt enabled");
+        assert_eq!(config.host, "broker.lan");
+        assert_eq!(config.port, 1883);
+        assert_eq!(config.client_id, "pixoo-bridge");
+        assert_eq!(config.topic_prefix, "pixoo");
+        assert_eq!(config.discovery_prefix.as_deref(), Some("homeassistant"));
+        assert_eq!(config.state_interval, Duration::from_secs(30));
+        assert!(config.username.is_none());
+    }
+
+    #[test]
+    fn mqtt_uses_env_overrides() {
+        let config = resolve_mqtt(
+            &MockConfig::new()
+                .with("PIXOO_BRIDGE_MQTT_HOST", "broker.lan")
+                .with("PIXOO_BRIDGE_MQTT_PORT", "8883")
+                .with("PIXOO_BRIDGE_MQTT_USERNAME", "bridge")
+                .with("PIXOO_BRIDGE_MQTT_PASSWORD", "secret")
+                .with("PIXOO_BRIDGE_MQTT_CLIENT_ID", "living-room")
+                .with("PIXOO_BRIDGE_MQTT_TOPIC_PREFIX", "home/pixoo")
+                .with("PIXOO_BRIDGE_MQTT_DISCOVERY_PREFIX", "")
+                .with("PIXOO_BRIDGE_MQTT_STATE_INTERVAL_SECS", "10"),
+        )
+        .expect("mqtt enabled");
+        assert_eq!(config.port, 8883);
+        assert_eq!(config.username.as_deref(), Some("bridge"));
+        assert_eq!(config.password.as_deref(), Some("secret"));
+        assert_eq!(config.client_id, "living-room");
+        assert_eq!(config.topic_prefix, "home/pixoo");
+        assert!(config.discovery_prefix.is_none());
+        assert_eq!(config.state_interval, Duration::from_secs(10));
+    }
+
+    #[test]
+    fn mqtt_falls_back_on_invalid_numbers() {
+        let config = resolve_mqtt(
+            &MockConfig::new()
+                .with("PIXOO_BRIDGE_MQTT_HOST", "broker.lan")
+                .with("PIXOO_BRIDGE_MQTT_PORT", "0")
+                .with("PIXOO_BRIDGE_MQTT_STATE_INTERVAL_SECS", "soon"),
+        )
+        .expect("mqtt enabled");
+        assert_eq!(config.port, 1883);
+        assert_eq!(config.state_interval, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn draw_conflict_policy_defaults_to_queue() {
+        assert_eq!(
+            resolve_draw_conflict_policy(&MockConfig::new()),
+            DrawConflictPolicy::Queue
+        );
+        let config = MockConfig::new().with("PIXOO_BRIDGE_DRAW_CONFLICT", "Reject");
+        assert_eq!(
+            resolve_draw_conflict_policy(&config),
+            DrawConflictPolicy::Reject
+        );
+        let config = MockConfig::new().with("PIXOO_BRIDGE_DRAW_CONFLICT", "newest");
+        assert_eq!(
+            resolve_draw_conflict_policy(&config),
+            DrawConflictPolicy::Queue
+        );
+    }
+
+    #[test]
+    fn layered_source_prefers_primary() {
+        let env = MockConfig::new().with("PIXOO_BRIDGE_PORT", "5000");
+        let file = FileConfigSource::parse("port = 6000\nmax_image_size = \"1MB\"").unwrap();
+        let layered = LayeredConfigSource::new(&env, &file);
+
+        assert_eq!(resolve_listener_port(&layered), 5000);
+        assert_eq!(resolve_max_image_size(&layered), 1024 * 1024);
+    }
+
+    #[test]
+    fn app_config_loads_from_file_source() {
+        let file = FileConfigSource::parse(
+            r#"
+            base_url = "http://10.0.0.5"
+            api_keys = ["admin", "drawer:draw"]
+
+            [remote]
+            denied_hosts = ["a.example", "b.example"]
+            "#,
+        )
+        .unwrap();
+        let config =
+            AppConfig::load_from(&LayeredConfigSource::new(MockConfig::new(), file)).unwrap();
+
+        assert_eq!(config.pixoo_base_url, "http://10.0.0.5");
+        assert_eq!(config.auth.key_count(), 2);
+        assert_eq!(config.remote_policy.denied_hosts.len(), 2);
+    }
+
+    #[test]
+    fn config_file_path_from_env() {
+        assert!(resolve_config_file(&MockConfig::new()).is_none());
+        let config = MockConfig::new().with("PIXOO_BRIDGE_CONFIG_FILE", "/etc/pixoo.toml");
+        assert_eq!(
+            resolve_config_file(&config),
+            Some(PathBuf::from("/etc/pixoo.toml"))
+        );
+    }
+}
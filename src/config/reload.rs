@@ -0,0 +1,197 @@
+//! Hot reload of the settings that are safe to change while running.
+//!
+//! The config file is polled rather than watched through OS notifications so
+//! reloads also work for bind-mounted files in containers, where editors and
+//! config management replace the file instead of writing to it.
+
+use super::file::FileConfigSource;
+use super::{
+    resolve_animation_speed_factor, resolve_device_timeout, resolve_max_image_size,
+    resolve_remote_timeout, ConfigSource, EnvConfigSource, LayeredConfigSource,
+};
+use crate::state::AppState;
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How often the config file is checked for changes.
+pub const RELOAD_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Normalised keys (see [`FileConfigSource`]) applied without a restart.
+const RELOADABLE_KEYS: [&str; 4] = [
+    "ANIMATION_SPEED_FACTOR",
+    "MAX_IMAGE_SIZE",
+    "REMOTE_TIMEOUT_MS",
+    "DEVICE_TIMEOUT_MS",
+];
+
+/// Settings that may change at runtime.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuntimeSettings {
+    pub animation_speed_factor: f64,
+    pub max_image_size: usize,
+    pub remote_timeout: Duration,
+    pub device_timeout: Duration,
+}
+
+impl RuntimeSettings {
+    pub fn from_source(source: &impl ConfigSource) -> Self {
+        Self {
+            animation_speed_factor: resolve_animation_speed_factor(source),
+            max_image_size: resolve_max_image_size(source),
+            remote_timeout: resolve_remote_timeout(source),
+            device_timeout: resolve_device_timeout(source),
+        }
+    }
+}
+
+/// Shared handle to the current [`RuntimeSettings`].
+#[derive(Debug, Clone)]
+pub struct SharedSettings(Arc<RwLock<RuntimeSettings>>);
+
+impl SharedSettings {
+    pub fn new(settings: RuntimeSettings) -> Self {
+        Self(Arc::new(RwLock::new(settings)))
+    }
+
+    pub fn get(&self) -> RuntimeSettings {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn set(&self, settings: RuntimeSettings) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = settings;
+    }
+}
+
+/// Polls `path` and applies changed runtime settings to `state`.
+pub fn spawn_config_reloader(
+    path: PathBuf,
+    initial: FileConfigSource,
+    state: Arc<AppState>,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut current = initial;
+        loop {
+            tokio::time::sleep(interval).await;
+            let next = match FileConfigSource::load(&path) {
+                Ok(next) => next,
+                Err(err) => {
+                    warn!(error = %err, "Config reload failed; keeping previous settings");
+                    continue;
+                }
+            };
+            if next == current {
+                continue;
+            }
+            apply_file_change(&current, &next, &EnvConfigSource, &state);
+            current = next;
+        }
+    })
+}
+
+/// Applies the runtime settings of `next` (under `env` overrides) and warns
+/// about changed keys that need a restart.
+fn apply_file_change(
+    previous: &FileConfigSource,
+    next: &FileConfigSource,
+    env: &impl ConfigSource,
+    state: &AppState,
+) {
+    let restart_required: Vec<String> = previous
+        .changed_keys(next)
+        .into_iter()
+        .filter(|key| !RELOADABLE_KEYS.contains(&key.as_str()))
+        .collect();
+    if !restart_required.is_empty() {
+        warn!(
+            keys = ?restart_required,
+            "Config file changed settings that only apply after a restart"
+        );
+    }
+
+    let settings = RuntimeSettings::from_source(&LayeredConfigSource::new(env, next));
+    let before = state.settings.get();
+    if settings != before {
+        state.apply_settings(settings);
+        info!(
+            animation_speed_factor = settings.animation_speed_factor,
+            max_image_size = settings.max_image_size,
+            remote_timeout = ?settings.remote_timeout,
+            device_timeout = ?settings.device_timeout,
+            "Reloaded runtime settings from config file"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::testing::MockConfig;
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+
+    fn state() -> AppState {
+        let client =
+            PixooClient::new("http://127.0.0.1", PixooClientConfig::default()).expect("client");
+        AppState::with_client(client)
+    }
+
+    #[test]
+    fn applies_reloadable_settings() {
+        let state = state();
+        let previous = FileConfigSource::default();
+        let next = FileConfigSource::parse(
+            "animation_speed_factor = 2.0\nmax_image_size = \"1MB\"\nremote_timeout_ms = 750\n\
+             device_timeout_ms = 1500",
+        )
+        .unwrap();
+
+        apply_file_change(&previous, &next, &MockConfig::new(), &state);
+
+        let settings = state.settings.get();
+        assert!((settings.animation_speed_factor - 2.0).abs() < f64::EPSILON);
+        assert_eq!(settings.max_image_size, 1024 * 1024);
+        assert_eq!(settings.remote_timeout, Duration::from_millis(750));
+        assert_eq!(settings.device_timeout, Duration::from_millis(1500));
+        assert_eq!(state.pixoo_client.timeout(), Duration::from_millis(1500));
+    }
+
+    #[test]
+    fn env_overrides_win_over_reloaded_file() {
+        let state = state();
+        let next = FileConfigSource::parse("animation_speed_factor = 2.0").unwrap();
+        let env = MockConfig::new().with("PIXOO_ANIMATION_SPEED_FACTOR", "0.5");
+
+        apply_file_change(&FileConfigSource::default(), &next, &env, &state);
+
+        assert!((state.settings.get().animation_speed_factor - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn reloader_picks_up_file_changes() {
+        let path = std::env::temp_dir().join(format!("pixoo-reload-{}.toml", std::process::id()));
+        std::fs::write(&path, "max_image_size = \"1MB\"").unwrap();
+        let initial = FileConfigSource::load(&path).unwrap();
+        let state = Arc::new(state());
+        let handle = spawn_config_reloader(
+            path.clone(),
+            initial,
+            state.clone(),
+            Duration::from_millis(20),
+        );
+
+        std::fs::write(&path, "max_image_size = \"2MB\"").unwrap();
+        let mut reloaded = false;
+        for _ in 0..100 {
+            if state.settings.get().max_image_size == 2 * 1024 * 1024 {
+                reloaded = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        handle.abort();
+        std::fs::remove_file(&path).ok();
+        assert!(reloaded, "settings were not reloaded");
+    }
+}
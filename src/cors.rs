@@ -0,0 +1,103 @@
+//! Cross-origin policy for browser clients.
+//!
+//! Every dimension defaults to "any", which reproduces the historical
+//! `CorsLayer::permissive()` behaviour. Restricting origins, methods, or
+//! headers only narrows that dimension.
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer, ExposeHeaders};
+
+#[derive(Debug, Clone, Default)]
+pub struct CorsConfig {
+    /// Allowed origins; `None` allows any origin.
+    pub origins: Option<Vec<HeaderValue>>,
+    /// Allowed methods; `None` allows any method.
+    pub methods: Option<Vec<Method>>,
+    /// Allowed request headers; `None` allows any header.
+    pub headers: Option<Vec<HeaderName>>,
+}
+
+impl CorsConfig {
+    /// Returns `true` when any origin may call the bridge.
+    pub fn is_permissive(&self) -> bool {
+        self.origins.is_none()
+    }
+
+    pub fn layer(&self) -> CorsLayer {
+        let origins = match &self.origins {
+            Some(origins) => AllowOrigin::list(origins.iter().cloned()),
+            None => AllowOrigin::from(Any),
+        };
+        let methods = match &self.methods {
+            Some(methods) => AllowMethods::list(methods.iter().cloned()),
+            None => AllowMethods::from(Any),
+        };
+        let headers = match &self.headers {
+            Some(headers) => AllowHeaders::list(headers.iter().cloned()),
+            None => AllowHeaders::from(Any),
+        };
+        CorsLayer::new()
+            .allow_origin(origins)
+            .allow_methods(methods)
+            .allow_headers(headers)
+            .expose_headers(ExposeHeaders::from(Any))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request, routing::post, Router};
+    use tower::util::ServiceExt;
+
+    fn app(config: &CorsConfig) -> Router {
+        Router::new()
+            .route("/draw/fill", post(|| async { "ok" }))
+            .layer(config.layer())
+    }
+
+    async fn preflight(config: &CorsConfig, origin: &str) -> axum::response::Response {
+        app(config)
+            .oneshot(
+                Request::builder()
+                    .method(Method::OPTIONS)
+                    .uri("/draw/fill")
+                    .header("Origin", origin)
+                    .header("Access-Control-Request-Method", "POST")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response")
+    }
+
+    #[tokio::test]
+    async fn default_allows_any_origin() {
+        let response = preflight(&CorsConfig::default(), "http://anywhere.test").await;
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+    }
+
+    #[tokio::test]
+    async fn restricted_origins_only_echo_allowed_origin() {
+        let config = CorsConfig {
+            origins: Some(vec![HeaderValue::from_static("http://dashboard.lan")]),
+            methods: Some(vec![Method::GET, Method::POST]),
+            headers: Some(vec![HeaderName::from_static("authorization")]),
+        };
+
+        let response = preflight(&config, "http://dashboard.lan").await;
+        let headers = response.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "http://dashboard.lan"
+        );
+        assert_eq!(headers["access-control-allow-methods"], "GET,POST");
+        assert_eq!(headers["access-control-allow-headers"], "authorization");
+
+        let response = preflight(&config, "http://evil.test").await;
+        assert!(response
+            .headers()
+            .get("access-control-allow-origin")
+            .is_none());
+    }
+}
@@ -0,0 +1,268 @@
+//! In-process event bus for notable bridge events.
+//!
+//! The Pixoo client, the health monitor and the draw and tool routes publish
+//! here; subscribers such as outbound webhooks and the `/events` stream
+//! receive every event through a broadcast channel and apply their own
+//! filtering. Publishing never blocks, and events are dropped when nobody is
+//! subscribed.
+
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// Events buffered per subscriber before the slowest one starts lagging.
+const CHANNEL_CAPACITY: usize = 256;
+
+/// What happened, with event-specific data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "data", rename_all = "kebab-case")]
+pub enum EventKind {
+    /// The device stopped answering (connection failure or timeout).
+    DeviceOffline { reason: String },
+    /// The device answers again after being offline.
+    DeviceOnline,
+    /// A reboot command was accepted by the device.
+    DeviceRebooted,
+    /// The device rejected a command with a non-zero `error_code`.
+    DeviceError { command: String, error_code: i64 },
+    /// A state-changing command was sent to the device. Queries are not
+    /// reported, and the frames of an upload are reported as one
+    /// `Draw/SendHttpGif` command.
+    Command {
+        command: String,
+        /// `X-Request-Id` of the API request that caused the command.
+        request_id: Option<String>,
+        success: bool,
+        error: Option<String>,
+    },
+    /// Every frame of an image or animation was uploaded.
+    DrawCompleted {
+        frames: usize,
+        request_id: Option<String>,
+    },
+    /// A live remote session started polling `link`.
+    LiveRemoteStarted { link: String, interval_seconds: u64 },
+    /// The live remote session was stopped or replaced.
+    LiveRemoteStopped,
+    /// A timer started through the bridge ran out.
+    TimerCompleted {
+        id: String,
+        label: Option<String>,
+        duration_seconds: u64,
+    },
+}
+
+/// Event type names, used for filtering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EventType {
+    DeviceOffline,
+    DeviceOnline,
+    DeviceRebooted,
+    DeviceError,
+    Command,
+    DrawCompleted,
+    LiveRemoteStarted,
+    LiveRemoteStopped,
+    TimerCompleted,
+}
+
+impl EventType {
+    pub const ALL: [EventType; 9] = [
+        EventType::DeviceOffline,
+        EventType::DeviceOnline,
+        EventType::DeviceRebooted,
+        EventType::DeviceError,
+        EventType::Command,
+        EventType::DrawCompleted,
+        EventType::LiveRemoteStarted,
+        EventType::LiveRemoteStopped,
+        EventType::TimerCompleted,
+    ];
+
+    /// Whether webhooks deliver this type when no event list names it.
+    /// `command` fires for every state-changing command, so it has to be
+    /// asked for explicitly.
+    pub fn is_default(self) -> bool {
+        self != EventType::Command
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            EventType::DeviceOffline => "device-offline",
+            EventType::DeviceOnline => "device-online",
+            EventType::DeviceRebooted => "device-rebooted",
+            EventType::DeviceError => "device-error",
+            EventType::Command => "command",
+            EventType::DrawCompleted => "draw-completed",
+            EventType::LiveRemoteStarted => "live-remote-started",
+            EventType::LiveRemoteStopped => "live-remote-stopped",
+            EventType::TimerCompleted => "timer-completed",
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_ascii_lowercase();
+        EventType::ALL
+            .into_iter()
+            .find(|event_type| event_type.as_str() == name)
+            .ok_or_else(|| format!("unknown event type '{}'", s.trim()))
+    }
+}
+
+impl EventKind {
+    pub fn event_type(&self) -> EventType {
+        match self {
+            EventKind::DeviceOffline { .. } => EventType::DeviceOffline,
+            EventKind::DeviceOnline => EventType::DeviceOnline,
+            EventKind::DeviceRebooted => EventType::DeviceRebooted,
+            EventKind::DeviceError { .. } => EventType::DeviceError,
+            EventKind::Command { .. } => EventType::Command,
+            EventKind::DrawCompleted { .. } => EventType::DrawCompleted,
+            EventKind::LiveRemoteStarted { .. } => EventType::LiveRemoteStarted,
+            EventKind::LiveRemoteStopped => EventType::LiveRemoteStopped,
+            EventKind::TimerCompleted { .. } => EventType::TimerCompleted,
+        }
+    }
+}
+
+/// A published event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BridgeEvent {
+    /// Unique id, stable across webhook retries.
+    pub id: String,
+    /// RFC 3339 UTC timestamp.
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub kind: EventKind,
+}
+
+impl BridgeEvent {
+    pub fn new(kind: EventKind) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            kind,
+        }
+    }
+}
+
+/// Cloneable handle to the event bus.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Arc<BridgeEvent>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    /// Publishes `kind` to every current subscriber.
+    pub fn publish(&self, kind: EventKind) {
+        let event = Arc::new(BridgeEvent::new(kind));
+        // An error only means nobody is listening.
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<BridgeEvent>> {
+        self.sender.subscribe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_type_and_data() {
+        let event = BridgeEvent::new(EventKind::DeviceError {
+            command: "Channel/SetBrightness".to_string(),
+            error_code: 1,
+        });
+        let value = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(value["type"], "device-error");
+        assert_eq!(
+            value["data"],
+            json!({ "command": "Channel/SetBrightness", "error_code": 1 })
+        );
+        assert_eq!(value["id"], event.id);
+        assert!(value["timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn event_type_matches_serialized_type() {
+        let kinds = [
+            EventKind::DeviceOnline,
+            EventKind::Command {
+                command: "Channel/OnOffScreen".to_string(),
+                request_id: None,
+                success: true,
+                error: None,
+            },
+            EventKind::DrawCompleted {
+                frames: 3,
+                request_id: Some("id".to_string()),
+            },
+            EventKind::LiveRemoteStarted {
+                link: "https://example.com/a.png".to_string(),
+                interval_seconds: 30,
+            },
+            EventKind::LiveRemoteStopped,
+            EventKind::TimerCompleted {
+                id: "id".to_string(),
+                label: Some("tea".to_string()),
+                duration_seconds: 180,
+            },
+        ];
+        for kind in kinds {
+            let value = serde_json::to_value(BridgeEvent::new(kind.clone())).unwrap();
+            assert_eq!(value["type"], kind.event_type().as_str());
+        }
+    }
+
+    #[test]
+    fn parses_event_types() {
+        assert_eq!(
+            "Device-Offline".parse::<EventType>(),
+            Ok(EventType::DeviceOffline)
+        );
+        assert!("device-exploded".parse::<EventType>().is_err());
+        for event_type in EventType::ALL {
+            assert_eq!(event_type.as_str().parse::<EventType>(), Ok(event_type));
+        }
+    }
+
+    #[tokio::test]
+    async fn delivers_to_subscribers() {
+        let bus = EventBus::new();
+        bus.publish(EventKind::DeviceOnline);
+        let mut receiver = bus.subscribe();
+        bus.publish(EventKind::DeviceRebooted);
+
+        let event = receiver.recv().await.unwrap();
+        assert_eq!(event.kind, EventKind::DeviceRebooted);
+    }
+}
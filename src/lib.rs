@@ -0,0 +1,22 @@
+//! HTTP bridge for Pixoo LED matrix devices.
+//!
+//! The [`pixoo`] module is usable on its own: [`pixoo::PixooClient`] talks to
+//! a device and [`pixoo::protocol`] has a typed request for every command.
+//! [`pixels`] decodes images into device frames, and [`server::run`] starts
+//! the full bridge, as the `pixoo-bridge` binary does.
+
+mod auth;
+mod config;
+mod cors;
+pub mod events;
+mod metrics;
+mod mqtt;
+mod openapi;
+pub mod pixels;
+pub mod pixoo;
+mod remote;
+mod request_tracing;
+mod routes;
+pub mod server;
+mod state;
+mod webhooks;
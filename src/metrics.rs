@@ -0,0 +1,267 @@
+//! Prometheus metrics exposed at `/metrics`.
+//!
+//! Metrics live in a process-wide registry so the Pixoo client, the remote
+//! fetcher, and the HTTP middleware can record without threading a handle
+//! through every constructor. Route labels use the matched route template
+//! (e.g. `/tools/stopwatch/{action}`) to keep cardinality bounded.
+
+use axum::{body::Body, extract::MatchedPath, http::Request, middleware::Next, response::Response};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use crate::pixoo::{PixooCommand, PixooErrorCategory};
+
+/// Route label for requests that did not match any route.
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    pixoo_commands: IntCounterVec,
+    pixoo_command_duration: HistogramVec,
+    pixoo_command_errors: IntCounterVec,
+    pixoo_command_retries: IntCounterVec,
+    remote_fetches: IntCounterVec,
+    remote_fetch_bytes: IntCounter,
+    remote_fetch_duration: HistogramVec,
+    frames_sent: IntCounter,
+    uploads_skipped: IntCounter,
+    webhook_deliveries: IntCounterVec,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Returns the process-wide metrics.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("pixoo_bridge".to_string()), None)
+            .expect("valid registry prefix");
+
+        Self {
+            http_requests: counter_vec(
+                &registry,
+                "http_requests_total",
+                "HTTP requests handled by the bridge",
+                &["method", "route", "status"],
+            ),
+            http_request_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "http_request_duration_seconds",
+                        "HTTP request latency by route",
+                    ),
+                    &["method", "route"],
+                ),
+            ),
+            pixoo_commands: counter_vec(
+                &registry,
+                "pixoo_commands_total",
+                "Commands sent to the Pixoo device",
+                &["command"],
+            ),
+            pixoo_command_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "pixoo_command_duration_seconds",
+                        "Pixoo command latency including retries",
+                    ),
+                    &["command"],
+                ),
+            ),
+            pixoo_command_errors: counter_vec(
+                &registry,
+                "pixoo_command_errors_total",
+                "Pixoo commands that failed after all retries",
+                &["command", "category"],
+            ),
+            pixoo_command_retries: counter_vec(
+                &registry,
+                "pixoo_command_retries_total",
+                "Retry attempts for Pixoo commands",
+                &["command"],
+            ),
+            remote_fetches: counter_vec(
+                &registry,
+                "remote_fetches_total",
+                "Remote image fetches by outcome",
+                &["outcome"],
+            ),
+            remote_fetch_bytes: counter(
+                &registry,
+                "remote_fetch_bytes_total",
+                "Bytes downloaded by remote image fetches",
+            ),
+            remote_fetch_duration: register(
+                &registry,
+                HistogramVec::new(
+                    HistogramOpts::new(
+                        "remote_fetch_duration_seconds",
+                        "Remote image fetch latency",
+                    ),
+                    &["outcome"],
+                ),
+            ),
+            frames_sent: counter(
+                &registry,
+                "frames_sent_total",
+                "Animation frames sent to the Pixoo",
+            ),
+            uploads_skipped: counter(
+                &registry,
+                "uploads_skipped_total",
+                "Draws skipped because the display already showed them",
+            ),
+            webhook_deliveries: counter_vec(
+                &registry,
+                "webhook_deliveries_total",
+                "Webhook delivery attempts by outcome",
+                &["outcome"],
+            ),
+            registry,
+        }
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding never fails");
+        String::from_utf8(buffer).expect("text encoder emits UTF-8")
+    }
+
+    pub fn record_http_request(&self, method: &str, route: &str, status: u16, latency: Duration) {
+        self.http_requests
+            .with_label_values(&[method, route, &status.to_string()])
+            .inc();
+        self.http_request_duration
+            .with_label_values(&[method, route])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn record_pixoo_command(
+        &self,
+        command: &PixooCommand,
+        latency: Duration,
+        error: Option<PixooErrorCategory>,
+    ) {
+        let command = command.as_str();
+        self.pixoo_commands.with_label_values(&[command]).inc();
+        self.pixoo_command_duration
+            .with_label_values(&[command])
+            .observe(latency.as_secs_f64());
+        if let Some(category) = error {
+            self.pixoo_command_errors
+                .with_label_values(&[command, category.as_str()])
+                .inc();
+        }
+    }
+
+    pub fn record_pixoo_retry(&self, command: &PixooCommand) {
+        self.pixoo_command_retries
+            .with_label_values(&[command.as_str()])
+            .inc();
+    }
+
+    /// Records a remote fetch; `outcome` is `downloaded`, `cached`,
+    /// `revalidated`, or `error`.
+    pub fn record_remote_fetch(&self, outcome: &str, bytes: usize, latency: Duration) {
+        self.remote_fetches.with_label_values(&[outcome]).inc();
+        self.remote_fetch_bytes
+            .inc_by(u64::try_from(bytes).unwrap_or(u64::MAX));
+        self.remote_fetch_duration
+            .with_label_values(&[outcome])
+            .observe(latency.as_secs_f64());
+    }
+
+    pub fn record_frame_sent(&self) {
+        self.frames_sent.inc();
+    }
+
+    pub fn record_upload_skipped(&self) {
+        self.uploads_skipped.inc();
+    }
+
+    /// Records a webhook attempt; `outcome` is `delivered`, `retried`, or
+    /// `failed`.
+    pub fn record_webhook_delivery(&self, outcome: &str) {
+        self.webhook_deliveries.with_label_values(&[outcome]).inc();
+    }
+}
+
+/// Registers a freshly created collector and returns it.
+fn counter_vec(registry: &Registry, name: &str, help: &str, labels: &[&str]) -> IntCounterVec {
+    register(registry, IntCounterVec::new(Opts::new(name, help), labels))
+}
+
+fn counter(registry: &Registry, name: &str, help: &str) -> IntCounter {
+    register(registry, IntCounter::new(name, help))
+}
+
+fn register<C>(registry: &Registry, collector: prometheus::Result<C>) -> C
+where
+    C: prometheus::core::Collector + Clone + 'static,
+{
+    let collector = collector.expect("valid metric");
+    registry
+        .register(Box::new(collector.clone()))
+        .expect("unique metric");
+    collector
+}
+
+/// Middleware recording request counts and latencies per matched route.
+pub async fn track_http(req: Request<Body>, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED_ROUTE, MatchedPath::as_str)
+        .to_string();
+    let start = Instant::now();
+    let response = next.run(req).await;
+    metrics().record_http_request(&method, &route, response.status().as_u16(), start.elapsed());
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_recorded_metrics_in_text_format() {
+        let metrics = metrics();
+        metrics.record_pixoo_command(
+            &PixooCommand::ToolsTimer,
+            Duration::from_millis(12),
+            Some(PixooErrorCategory::Timeout),
+        );
+        metrics.record_pixoo_retry(&PixooCommand::ToolsTimer);
+        metrics.record_remote_fetch("downloaded", 2048, Duration::from_millis(30));
+        metrics.record_frame_sent();
+        metrics.record_upload_skipped();
+
+        let output = metrics.render();
+        assert!(output.contains("pixoo_bridge_pixoo_commands_total{command=\"Tools/SetTimer\"}"));
+        assert!(output.contains(
+            "pixoo_bridge_pixoo_command_errors_total{category=\"timeout\",command=\"Tools/SetTimer\"}"
+        ));
+        assert!(
+            output.contains("pixoo_bridge_pixoo_command_retries_total{command=\"Tools/SetTimer\"}")
+        );
+        assert!(output.contains("pixoo_bridge_remote_fetch_bytes_total"));
+        assert!(output
+            .contains("pixoo_bridge_remote_fetch_duration_seconds_bucket{outcome=\"downloaded\""));
+        assert!(output.contains("pixoo_bridge_frames_sent_total"));
+        assert!(output.contains("pixoo_bridge_uploads_skipped_total"));
+    }
+}
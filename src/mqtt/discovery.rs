@@ -0,0 +1,266 @@
+//! Home Assistant MQTT discovery payloads.
+//!
+//! The panel is announced as one device with a light (power and brightness),
+//! selects and a switch for display settings, buttons for one-shot commands,
+//! and a connectivity sensor fed by `/health`. Entity state is read from the
+//! retained `state` topic with value templates, so no extra topics are needed.
+
+use super::topics::Topics;
+use serde_json::{json, Value};
+
+/// Retained discovery message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscoveryMessage {
+    pub topic: String,
+    pub payload: Value,
+}
+
+/// Builds every discovery message for the bridge identified by `node_id`.
+pub fn discovery_messages(
+    discovery_prefix: &str,
+    node_id: &str,
+    topics: &Topics,
+) -> Vec<DiscoveryMessage> {
+    let builder = EntityBuilder::new(discovery_prefix, node_id, topics);
+    let mut messages = setting_entities(&builder);
+    messages.extend(button_entities(&builder));
+    messages
+}
+
+/// Fills in the fields shared by every entity of the device.
+struct EntityBuilder<'a> {
+    discovery_prefix: &'a str,
+    node_id: String,
+    topics: &'a Topics,
+    device: Value,
+}
+
+impl<'a> EntityBuilder<'a> {
+    fn new(discovery_prefix: &'a str, node_id: &str, topics: &'a Topics) -> Self {
+        let node_id = sanitize_node_id(node_id);
+        let device = json!({
+            "identifiers": [node_id],
+            "name": "Pixoo",
+            "manufacturer": "Divoom",
+            "model": "Pixoo 64",
+            "sw_version": env!("CARGO_PKG_VERSION"),
+        });
+        Self {
+            discovery_prefix: discovery_prefix.trim_end_matches('/'),
+            node_id,
+            topics,
+            device,
+        }
+    }
+
+    fn entity(
+        &self,
+        component: &str,
+        object_id: &str,
+        name: &str,
+        mut config: Value,
+    ) -> DiscoveryMessage {
+        let node_id = &self.node_id;
+        let fields = config.as_object_mut().expect("entity config is an object");
+        fields.insert("name".into(), json!(name));
+        fields.insert("unique_id".into(), json!(format!("{node_id}_{object_id}")));
+        fields.insert("object_id".into(), json!(format!("{node_id}_{object_id}")));
+        fields.insert(
+            "availability_topic".into(),
+            json!(self.topics.availability()),
+        );
+        fields.insert("device".into(), self.device.clone());
+        DiscoveryMessage {
+            topic: format!(
+                "{}/{component}/{node_id}/{object_id}/config",
+                self.discovery_prefix
+            ),
+            payload: config,
+        }
+    }
+
+    fn button(&self, object_id: &str, name: &str, path: &str) -> DiscoveryMessage {
+        self.entity(
+            "button",
+            object_id,
+            name,
+            json!({ "command_topic": self.topics.command(path), "payload_press": "" }),
+        )
+    }
+}
+
+/// Entities backed by the retained `state` and `health` topics.
+fn setting_entities(builder: &EntityBuilder) -> Vec<DiscoveryMessage> {
+    let topics = builder.topics;
+    vec![
+        builder.entity(
+            "light",
+            "display",
+            "Display",
+            json!({
+                "command_topic": topics.light_set(),
+                "state_topic": topics.state(),
+                "state_value_template": "{{ 'ON' if value_json.displayOn else 'OFF' }}",
+                "brightness_command_topic": topics.brightness_set(),
+                "brightness_state_topic": topics.state(),
+                "brightness_value_template": "{{ value_json.brightness }}",
+                "brightness_scale": 100,
+            }),
+        ),
+        builder.entity(
+            "switch",
+            "mirror",
+            "Mirror",
+            json!({
+                "command_topic": topics.mirror_set(),
+                "state_topic": topics.state(),
+                "value_template": "{{ 'ON' if value_json.mirrored else 'OFF' }}",
+                "entity_category": "config",
+            }),
+        ),
+        builder.entity(
+            "select",
+            "rotation",
+            "Rotation",
+            json!({
+                "command_topic": topics.rotation_set(),
+                "state_topic": topics.state(),
+                "value_template": "{{ value_json.rotationAngle }}",
+                "options": ["0", "90", "180", "270"],
+                "entity_category": "config",
+            }),
+        ),
+        builder.entity(
+            "select",
+            "time_mode",
+            "Time mode",
+            json!({
+                "command_topic": topics.time_mode_set(),
+                "state_topic": topics.state(),
+                "value_template": "{{ '24h' if value_json.timeMode == 'TWENTY_FOUR' else '12h' }}",
+                "options": ["12h", "24h"],
+                "entity_category": "config",
+            }),
+        ),
+        builder.entity(
+            "select",
+            "temperature_unit",
+            "Temperature unit",
+            json!({
+                "command_topic": topics.temperature_unit_set(),
+                "state_topic": topics.state(),
+                "value_template": "{{ value_json.temperatureUnit | lower }}",
+                "options": ["celsius", "fahrenheit"],
+                "entity_category": "config",
+            }),
+        ),
+        builder.entity(
+            "binary_sensor",
+            "connectivity",
+            "Connectivity",
+            json!({
+                "state_topic": topics.health(),
+                "value_template": "{{ 'ON' if value_json.status == 'ok' else 'OFF' }}",
+                "device_class": "connectivity",
+                "entity_category": "diagnostic",
+            }),
+        ),
+    ]
+}
+
+/// One-shot commands sent to the generic command topics.
+fn button_entities(builder: &EntityBuilder) -> Vec<DiscoveryMessage> {
+    let mut reboot = builder.button("reboot", "Reboot", "reboot");
+    reboot.payload["device_class"] = json!("restart");
+    vec![
+        reboot,
+        builder.button(
+            "stopwatch_start",
+            "Stopwatch start",
+            "tools/stopwatch/start",
+        ),
+        builder.button("stopwatch_stop", "Stopwatch stop", "tools/stopwatch/stop"),
+        builder.button(
+            "stopwatch_reset",
+            "Stopwatch reset",
+            "tools/stopwatch/reset",
+        ),
+        builder.button("timer_stop", "Timer stop", "tools/timer/stop"),
+        builder.button("text_clear", "Clear text", "draw/text/clear"),
+    ]
+}
+
+/// Home Assistant only accepts `[a-zA-Z0-9_-]` in node ids.
+fn sanitize_node_id(raw: &str) -> String {
+    raw.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages() -> Vec<DiscoveryMessage> {
+        discovery_messages("homeassistant", "pixoo bridge", &Topics::new("pixoo"))
+    }
+
+    #[test]
+    fn announces_light_with_state_templates() {
+        let messages = messages();
+        let light = messages
+            .iter()
+            .find(|message| message.topic == "homeassistant/light/pixoo_bridge/display/config")
+            .expect("light config");
+
+        assert_eq!(light.payload["command_topic"], "pixoo/light/set");
+        assert_eq!(
+            light.payload["brightness_command_topic"],
+            "pixoo/light/brightness/set"
+        );
+        assert_eq!(light.payload["state_topic"], "pixoo/state");
+        assert_eq!(light.payload["brightness_scale"], 100);
+        assert_eq!(light.payload["unique_id"], "pixoo_bridge_display");
+        assert_eq!(light.payload["availability_topic"], "pixoo/availability");
+        assert_eq!(light.payload["device"]["identifiers"][0], "pixoo_bridge");
+    }
+
+    #[test]
+    fn buttons_publish_to_generic_command_topics() {
+        let messages = messages();
+        let reboot = messages
+            .iter()
+            .find(|message| message.topic == "homeassistant/button/pixoo_bridge/reboot/config")
+            .expect("reboot button");
+
+        assert_eq!(reboot.payload["command_topic"], "pixoo/command/reboot");
+        assert_eq!(reboot.payload["device_class"], "restart");
+
+        let topics = Topics::new("pixoo");
+        for message in messages
+            .iter()
+            .filter(|message| message.topic.contains("/button/"))
+        {
+            let topic = message.payload["command_topic"].as_str().unwrap();
+            assert!(topics.route(topic, b"").is_ok(), "{topic} is not routable");
+        }
+    }
+
+    #[test]
+    fn unique_ids_are_distinct() {
+        let messages = messages();
+        let mut ids: Vec<&str> = messages
+            .iter()
+            .map(|message| message.payload["unique_id"].as_str().unwrap())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), messages.len());
+    }
+}
@@ -0,0 +1,439 @@
+//! Optional MQTT integration.
+//!
+//! When a broker is configured the bridge publishes the device settings and
+//! health as retained JSON, announces itself through Home Assistant discovery,
+//! and subscribes to command topics. Commands are dispatched through the
+//! bridge's own router (see [`topics`]), so MQTT and HTTP share validation
+//! and device handling. API key authentication does not apply: access control
+//! belongs to the broker.
+
+mod discovery;
+mod topics;
+
+pub use topics::Topics;
+
+use axum::body::{to_bytes, Body};
+use axum::http::{header::CONTENT_TYPE, Method, Request, StatusCode};
+use axum::Router;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, Publish, QoS};
+use serde_json::{json, Value};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use tracing::{debug, info, warn};
+
+/// Delay before reconnecting after the broker connection failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+const CHANNEL_CAPACITY: usize = 64;
+const ONLINE: &str = "online";
+const OFFLINE: &str = "offline";
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct MqttConfig {
+    pub host: String,
+    pub port: u16,
+    pub client_id: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub topic_prefix: String,
+    /// Home Assistant discovery prefix; `None` disables discovery.
+    pub discovery_prefix: Option<String>,
+    /// How often state and health are republished.
+    pub state_interval: Duration,
+}
+
+impl fmt::Debug for MqttConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MqttConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("client_id", &self.client_id)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "***"))
+            .field("topic_prefix", &self.topic_prefix)
+            .field("discovery_prefix", &self.discovery_prefix)
+            .field("state_interval", &self.state_interval)
+            .finish()
+    }
+}
+
+impl MqttConfig {
+    fn options(&self, topics: &Topics) -> MqttOptions {
+        let mut options = MqttOptions::new(&self.client_id, &self.host, self.port);
+        options
+            .set_keep_alive(KEEP_ALIVE)
+            .set_last_will(LastWill::new(
+                topics.availability(),
+                OFFLINE,
+                QoS::AtLeastOnce,
+                true,
+            ));
+        if let Some(username) = &self.username {
+            options.set_credentials(username, self.password.clone().unwrap_or_default());
+        }
+        options
+    }
+}
+
+/// Connects to the broker and serves MQTT until the task is aborted.
+///
+/// `router` should be the bridge router without the authentication layer.
+pub fn spawn_mqtt(config: MqttConfig, router: Router) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(run(config, router))
+}
+
+#[derive(Clone)]
+struct MqttBridge {
+    client: AsyncClient,
+    router: Router,
+    topics: Topics,
+    config: Arc<MqttConfig>,
+}
+
+async fn run(config: MqttConfig, router: Router) {
+    let topics = Topics::new(&config.topic_prefix);
+    let (client, mut eventloop) = AsyncClient::new(config.options(&topics), CHANNEL_CAPACITY);
+    let bridge = MqttBridge {
+        client,
+        router,
+        topics,
+        config: Arc::new(config),
+    };
+    let mut state_timer = tokio::time::interval(bridge.config.state_interval);
+    state_timer.tick().await;
+
+    loop {
+        tokio::select! {
+            event = eventloop.poll() => match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!(
+                        host = %bridge.config.host,
+                        port = bridge.config.port,
+                        "Connected to MQTT broker"
+                    );
+                    let bridge = bridge.clone();
+                    tokio::spawn(async move { bridge.on_connected().await });
+                }
+                Ok(Event::Incoming(Packet::Publish(message))) => {
+                    let bridge = bridge.clone();
+                    tokio::spawn(async move { bridge.on_message(message).await });
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    warn!(error = %err, retry_in = ?RECONNECT_DELAY, "MQTT connection failed");
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                }
+            },
+            _ = state_timer.tick() => {
+                let bridge = bridge.clone();
+                tokio::spawn(async move { bridge.publish_state().await });
+            }
+        }
+    }
+}
+
+impl MqttBridge {
+    async fn on_connected(&self) {
+        for filter in self.topics.subscriptions() {
+            if let Err(err) = self.client.subscribe(&filter, QoS::AtLeastOnce).await {
+                warn!(error = %err, topic = %filter, "MQTT subscribe failed");
+            }
+        }
+        self.publish(self.topics.availability(), ONLINE.as_bytes().to_vec(), true)
+            .await;
+        if let Some(prefix) = &self.config.discovery_prefix {
+            for message in
+                discovery::discovery_messages(prefix, &self.config.client_id, &self.topics)
+            {
+                self.publish(
+                    message.topic,
+                    message.payload.to_string().into_bytes(),
+                    true,
+                )
+                .await;
+            }
+        }
+        self.publish_state().await;
+    }
+
+    async fn on_message(&self, message: Publish) {
+        let route = match self.topics.route(&message.topic, &message.payload) {
+            Ok(route) => route,
+            Err(err) => {
+                warn!(topic = %message.topic, error = %err, "Ignoring MQTT command");
+                let result = json!({ "topic": message.topic, "error": err });
+                self.publish(self.topics.result(), result.to_string().into_bytes(), false)
+                    .await;
+                return;
+            }
+        };
+
+        debug!(topic = %message.topic, path = %route.path, "Dispatching MQTT command");
+        let (status, body) = self
+            .call(Method::POST, &route.path, route.body.unwrap_or_default())
+            .await;
+        if status.is_success() {
+            info!(topic = %message.topic, path = %route.path, "MQTT command applied");
+        } else {
+            warn!(topic = %message.topic, path = %route.path, status = %status, "MQTT command failed");
+        }
+        let result = json!({
+            "topic": message.topic,
+            "path": route.path,
+            "status": status.as_u16(),
+            "body": serde_json::from_slice::<Value>(&body).unwrap_or(Value::Null),
+        });
+        self.publish(self.topics.result(), result.to_string().into_bytes(), false)
+            .await;
+        if status.is_success() {
+            self.publish_state().await;
+        }
+    }
+
+    /// Publishes `/manage/settings` and `/health` as retained messages.
+    async fn publish_state(&self) {
+        let (status, body) = self
+            .call(Method::GET, "/manage/settings", String::new())
+            .await;
+        if status.is_success() {
+            self.publish(self.topics.state(), body, true).await;
+        } else {
+            debug!(status = %status, "Skipping MQTT state update");
+        }
+        let (_, body) = self.call(Method::GET, "/health", String::new()).await;
+        self.publish(self.topics.health(), body, true).await;
+    }
+
+    async fn call(&self, method: Method, path: &str, body: String) -> (StatusCode, Vec<u8>) {
+        let mut request = Request::builder().method(method).uri(path);
+        if !body.is_empty() {
+            request = request.header(CONTENT_TYPE, "application/json");
+        }
+        let request = match request.body(Body::from(body)) {
+            Ok(request) => request,
+            Err(err) => {
+                let body = json!({ "message": err.to_string() }).to_string();
+                return (StatusCode::BAD_REQUEST, body.into_bytes());
+            }
+        };
+        let response = match self.router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(never) => match never {},
+        };
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX)
+            .await
+            .map(|bytes| bytes.to_vec())
+            .unwrap_or_default();
+        (status, body)
+    }
+
+    async fn publish(&self, topic: String, payload: Vec<u8>, retain: bool) {
+        if let Err(err) = self
+            .client
+            .publish(&topic, QoS::AtLeastOnce, retain, payload)
+            .await
+        {
+            warn!(error = %err, topic = %topic, "MQTT publish failed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::build_router;
+    use crate::state::AppState;
+    use bytes::BytesMut;
+    use httpmock::{Method as MockMethod, MockServer};
+    use rumqttc::{ConnAck, ConnectReturnCode, PubAck, SubAck, SubscribeReasonCode};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const MAX_PACKET: usize = 1024 * 1024;
+
+    /// Just enough of an MQTT broker to drive one client connection.
+    struct FakeBroker {
+        stream: TcpStream,
+        buffer: BytesMut,
+        next_pkid: u16,
+    }
+
+    impl FakeBroker {
+        async fn accept(listener: &TcpListener) -> Self {
+            let (stream, _) = listener.accept().await.expect("client connects");
+            Self {
+                stream,
+                buffer: BytesMut::new(),
+                next_pkid: 1,
+            }
+        }
+
+        async fn read(&mut self) -> Packet {
+            loop {
+                match Packet::read(&mut self.buffer, MAX_PACKET) {
+                    Ok(packet) => return packet,
+                    Err(rumqttc::Error::InsufficientBytes(_)) => {
+                        let read = self.stream.read_buf(&mut self.buffer).await.unwrap();
+                        assert!(read > 0, "client closed the connection");
+                    }
+                    Err(err) => panic!("malformed packet: {err}"),
+                }
+            }
+        }
+
+        async fn write(&mut self, packet: Packet) {
+            let mut out = BytesMut::new();
+            packet.write(&mut out, MAX_PACKET).unwrap();
+            self.stream.write_all(&out).await.unwrap();
+        }
+
+        /// Reads packets, acknowledging them, until a publish on `topic`.
+        async fn expect_publish(&mut self, topic: &str, seen: &mut Vec<Publish>) -> Publish {
+            loop {
+                match self.read().await {
+                    Packet::Subscribe(subscribe) => {
+                        let codes = subscribe
+                            .filters
+                            .iter()
+                            .map(|_| SubscribeReasonCode::Success(QoS::AtLeastOnce))
+                            .collect();
+                        self.write(Packet::SubAck(SubAck::new(subscribe.pkid, codes)))
+                            .await;
+                    }
+                    Packet::Publish(publish) => {
+                        if publish.qos == QoS::AtLeastOnce {
+                            self.write(Packet::PubAck(PubAck::new(publish.pkid))).await;
+                        }
+                        seen.push(publish.clone());
+                        if publish.topic == topic {
+                            return publish;
+                        }
+                    }
+                    Packet::PingReq => self.write(Packet::PingResp).await,
+                    _ => {}
+                }
+            }
+        }
+
+        async fn send(&mut self, topic: &str, payload: &str) {
+            let mut publish = Publish::new(topic, QoS::AtLeastOnce, payload);
+            publish.pkid = self.next_pkid;
+            self.next_pkid += 1;
+            self.write(Packet::Publish(publish)).await;
+        }
+    }
+
+    fn config(port: u16) -> MqttConfig {
+        MqttConfig {
+            host: "127.0.0.1".to_string(),
+            port,
+            client_id: "pixoo-bridge".to_string(),
+            username: Some("bridge".to_string()),
+            password: Some("secret".to_string()),
+            topic_prefix: "pixoo".to_string(),
+            discovery_prefix: Some("homeassistant".to_string()),
+            state_interval: Duration::from_hours(1),
+        }
+    }
+
+    fn internal_router(server: &MockServer) -> Router {
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let (router, _) = build_router()
+            .with_state(Arc::new(AppState::with_client(client)))
+            .split_for_parts();
+        router
+    }
+
+    #[test]
+    fn debug_redacts_password() {
+        let rendered = format!("{:?}", config(1883));
+        assert!(rendered.contains("***"));
+        assert!(!rendered.contains("secret"));
+    }
+
+    #[tokio::test]
+    async fn publishes_discovery_state_and_dispatches_commands() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Channel/GetAllConf");
+            then.status(200).body(
+                json!({
+                    "error_code": 0,
+                    "LightSwitch": "1",
+                    "Brightness": 80,
+                    "Time24Flag": "1",
+                    "RotationFlag": "0",
+                    "MirrorFlag": "0",
+                    "TemperatureMode": "0",
+                    "CurClockId": 5,
+                })
+                .to_string(),
+            );
+        });
+        let brightness = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Channel/SetBrightness")
+                .body_includes("\"Brightness\":40");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let handle = spawn_mqtt(config(port), internal_router(&server));
+
+        let scenario = async {
+            let mut broker = FakeBroker::accept(&listener).await;
+            let Packet::Connect(connect) = broker.read().await else {
+                panic!("expected CONNECT");
+            };
+            let will = connect.last_will.expect("last will");
+            assert_eq!(will.topic, "pixoo/availability");
+            assert_eq!(&will.message[..], b"offline");
+            assert_eq!(connect.login.expect("credentials").username, "bridge");
+            broker
+                .write(Packet::ConnAck(ConnAck::new(
+                    ConnectReturnCode::Success,
+                    false,
+                )))
+                .await;
+
+            let mut seen = Vec::new();
+            let state = broker.expect_publish("pixoo/state", &mut seen).await;
+            assert!(state.retain);
+            let state: Value = serde_json::from_slice(&state.payload).unwrap();
+            assert_eq!(state["brightness"], 80);
+            assert!(seen.iter().any(|publish| publish.topic
+                == "homeassistant/light/pixoo-bridge/display/config"
+                && publish.retain));
+            assert!(seen
+                .iter()
+                .any(|publish| publish.topic == "pixoo/availability"
+                    && &publish.payload[..] == b"online"));
+
+            broker.send("pixoo/light/brightness/set", "40").await;
+            let result = broker.expect_publish("pixoo/result", &mut seen).await;
+            let result: Value = serde_json::from_slice(&result.payload).unwrap();
+            assert_eq!(result["path"], "/manage/display/brightness/40");
+            assert_eq!(result["status"], 200);
+
+            broker.send("pixoo/light/brightness/set", "400").await;
+            let result = broker.expect_publish("pixoo/result", &mut seen).await;
+            let result: Value = serde_json::from_slice(&result.payload).unwrap();
+            assert_eq!(result["status"], 400);
+        };
+        tokio::time::timeout(Duration::from_secs(10), scenario)
+            .await
+            .expect("MQTT scenario timed out");
+        handle.abort();
+
+        brightness.assert_calls(1);
+    }
+}
@@ -0,0 +1,219 @@
+//! Topic layout and the mapping from command topics to HTTP routes.
+//!
+//! Commands are not handled separately: each command topic is translated into
+//! the `POST` request the HTTP API would receive, so validation and device
+//! handling stay in the route handlers.
+
+use crate::auth::Scope;
+
+/// Topics below the configured prefix (default `pixoo`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Topics {
+    prefix: String,
+}
+
+impl Topics {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.trim_matches('/').to_string(),
+        }
+    }
+
+    fn topic(&self, suffix: &str) -> String {
+        format!("{}/{suffix}", self.prefix)
+    }
+
+    /// `online`/`offline` bridge availability, backed by the last will.
+    pub fn availability(&self) -> String {
+        self.topic("availability")
+    }
+
+    /// Retained `/manage/settings` payload.
+    pub fn state(&self) -> String {
+        self.topic("state")
+    }
+
+    /// Retained `/health` payload.
+    pub fn health(&self) -> String {
+        self.topic("health")
+    }
+
+    /// Outcome of each command, for debugging automations.
+    pub fn result(&self) -> String {
+        self.topic("result")
+    }
+
+    pub fn light_set(&self) -> String {
+        self.topic("light/set")
+    }
+
+    pub fn brightness_set(&self) -> String {
+        self.topic("light/brightness/set")
+    }
+
+    pub fn rotation_set(&self) -> String {
+        self.topic("rotation/set")
+    }
+
+    pub fn mirror_set(&self) -> String {
+        self.topic("mirror/set")
+    }
+
+    pub fn time_mode_set(&self) -> String {
+        self.topic("time_mode/set")
+    }
+
+    pub fn temperature_unit_set(&self) -> String {
+        self.topic("temperature_unit/set")
+    }
+
+    /// Generic command topic for `path`, e.g. `pixoo/command/tools/timer/stop`.
+    pub fn command(&self, path: &str) -> String {
+        self.topic(&format!("command/{}", path.trim_start_matches('/')))
+    }
+
+    /// Filters the bridge subscribes to.
+    pub fn subscriptions(&self) -> Vec<String> {
+        vec![
+            self.light_set(),
+            self.brightness_set(),
+            self.rotation_set(),
+            self.mirror_set(),
+            self.time_mode_set(),
+            self.temperature_unit_set(),
+            self.topic("command/#"),
+        ]
+    }
+
+    /// Translates a message on a command topic into the matching HTTP call.
+    ///
+    /// # Errors
+    ///
+    /// Returns a description when the topic is unknown or the payload does not
+    /// fit the topic.
+    pub fn route(&self, topic: &str, payload: &[u8]) -> Result<CommandRoute, String> {
+        let text = String::from_utf8_lossy(payload).trim().to_string();
+        let fixed = |path: String| Ok(CommandRoute { path, body: None });
+
+        if topic == self.light_set() {
+            return fixed(format!("/manage/display/{}", on_off(&text)?));
+        }
+        if topic == self.mirror_set() {
+            return fixed(format!("/manage/display/mirror/{}", on_off(&text)?));
+        }
+        if topic == self.brightness_set() {
+            return fixed(format!("/manage/display/brightness/{text}"));
+        }
+        if topic == self.rotation_set() {
+            return fixed(format!("/manage/display/rotation/{text}"));
+        }
+        if topic == self.time_mode_set() {
+            return fixed(format!("/manage/time/mode/{text}"));
+        }
+        if topic == self.temperature_unit_set() {
+            return fixed(format!(
+                "/manage/weather/temperature-unit/{}",
+                text.to_ascii_lowercase()
+            ));
+        }
+
+        let path = topic
+            .strip_prefix(&self.command(""))
+            .filter(|path| !path.is_empty())
+            .map(|path| format!("/{path}"))
+            .ok_or_else(|| format!("unknown command topic '{topic}'"))?;
+        if Scope::for_path(&path).is_none() || matches!(path.as_str(), "/metrics" | "/events") {
+            return Err(format!("'{path}' is not a command route"));
+        }
+        Ok(CommandRoute {
+            path,
+            body: (!text.is_empty()).then_some(text),
+        })
+    }
+}
+
+/// `POST` request equivalent to an MQTT command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandRoute {
+    pub path: String,
+    /// JSON body, when the message carried a payload.
+    pub body: Option<String>,
+}
+
+fn on_off(payload: &str) -> Result<&'static str, String> {
+    match payload.to_ascii_uppercase().as_str() {
+        "ON" => Ok("on"),
+        "OFF" => Ok("off"),
+        other => Err(format!("expected ON or OFF, got '{other}'")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(topic: &str, payload: &str) -> Result<CommandRoute, String> {
+        Topics::new("pixoo").route(topic, payload.as_bytes())
+    }
+
+    #[test]
+    fn maps_entity_topics_to_routes() {
+        assert_eq!(
+            route("pixoo/light/set", "ON").unwrap().path,
+            "/manage/display/on"
+        );
+        assert_eq!(
+            route("pixoo/light/brightness/set", "40").unwrap().path,
+            "/manage/display/brightness/40"
+        );
+        assert_eq!(
+            route("pixoo/mirror/set", "off").unwrap().path,
+            "/manage/display/mirror/off"
+        );
+        assert_eq!(
+            route("pixoo/rotation/set", "90").unwrap().path,
+            "/manage/display/rotation/90"
+        );
+        assert_eq!(
+            route("pixoo/temperature_unit/set", "FAHRENHEIT")
+                .unwrap()
+                .path,
+            "/manage/weather/temperature-unit/fahrenheit"
+        );
+        assert!(route("pixoo/light/set", "toggle").is_err());
+    }
+
+    #[test]
+    fn maps_generic_command_topics_with_json_body() {
+        let command = route(
+            "pixoo/command/draw/fill",
+            r#"{"red":255,"green":0,"blue":0}"#,
+        )
+        .expect("route");
+        assert_eq!(command.path, "/draw/fill");
+        assert_eq!(
+            command.body.as_deref(),
+            Some(r#"{"red":255,"green":0,"blue":0}"#)
+        );
+
+        let command = route("pixoo/command/reboot", "").expect("route");
+        assert_eq!(command.path, "/reboot");
+        assert_eq!(command.body, None);
+    }
+
+    #[test]
+    fn rejects_unknown_and_read_only_topics() {
+        assert!(route("pixoo/command/health", "").is_err());
+        assert!(route("pixoo/command/metrics", "").is_err());
+        assert!(route("pixoo/command/events", "").is_err());
+        assert!(route("pixoo/command/", "").is_err());
+        assert!(route("other/light/set", "ON").is_err());
+    }
+
+    #[test]
+    fn trims_prefix_slashes() {
+        let topics = Topics::new("/home/pixoo/");
+        assert_eq!(topics.state(), "home/pixoo/state");
+        assert_eq!(topics.command("/reboot"), "home/pixoo/command/reboot");
+    }
+}
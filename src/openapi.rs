@@ -4,13 +4,22 @@
 //! (see `crate::routes::build_router`), so this module only defines the
 //! document metadata and the error response schemas that handlers reference.
 
-use utoipa::OpenApi;
+use utoipa::openapi::security::{
+    ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+};
+use utoipa::openapi::{Ref, RefOr};
+use utoipa::{Modify, OpenApi};
 
+use crate::auth::{Scope, API_KEY_HEADER};
 use crate::pixoo::error::{
-    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, InternalErrorResponse,
-    PayloadTooLargeResponse, PixooHttpErrorKind, PixooHttpErrorResponse, ValidationErrorResponse,
+    ConflictResponse, DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse,
+    ForbiddenResponse, InternalErrorResponse, PayloadTooLargeResponse, PixooHttpErrorKind,
+    PixooHttpErrorResponse, UnauthorizedResponse, ValidationErrorResponse,
 };
 
+const BEARER_SCHEME: &str = "bearer";
+const API_KEY_SCHEME: &str = "api_key";
+
 /// Base `OpenAPI` document. Paths and request/response schemas are merged in at
 /// runtime from the `OpenApiRouter`; only metadata lives here.
 #[derive(OpenApi)]
@@ -31,11 +40,75 @@ use crate::pixoo::error::{
         responses(
             ValidationErrorResponse,
             PayloadTooLargeResponse,
+            UnauthorizedResponse,
+            ForbiddenResponse,
+            ConflictResponse,
             InternalErrorResponse,
             DeviceUnreachableResponse,
             DeviceErrorResponse,
             DeviceTimeoutResponse,
         )
-    )
+    ),
+    modifiers(&SecuritySchemes)
 )]
 pub struct ApiDoc;
+
+/// Registers the bearer and `X-API-Key` security schemes.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            BEARER_SCHEME,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "API key sent as `Authorization: Bearer <key>`. Only enforced when keys are configured.",
+                    ))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            API_KEY_SCHEME,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                API_KEY_HEADER,
+                "API key sent in the `X-API-Key` header. Only enforced when keys are configured.",
+            ))),
+        );
+    }
+}
+
+/// Marks every scoped operation with its required scope and the `401`/`403`
+/// responses. Runs on the merged document because paths are only known once
+/// the router has been collected.
+pub fn apply_security(openapi: &mut utoipa::openapi::OpenApi) {
+    for (path, item) in &mut openapi.paths.paths {
+        let Some(scope) = Scope::for_path(path) else {
+            continue;
+        };
+        let operations = [
+            &mut item.get,
+            &mut item.put,
+            &mut item.post,
+            &mut item.delete,
+            &mut item.patch,
+        ];
+        for operation in operations.into_iter().flatten() {
+            operation.security = Some(vec![
+                SecurityRequirement::new(BEARER_SCHEME, [scope.as_str()]),
+                SecurityRequirement::new(API_KEY_SCHEME, [scope.as_str()]),
+            ]);
+            let responses = &mut operation.responses.responses;
+            responses.insert(
+                "401".to_string(),
+                RefOr::Ref(Ref::from_response_name("UnauthorizedResponse")),
+            );
+            responses.insert(
+                "403".to_string(),
+                RefOr::Ref(Ref::from_response_name("ForbiddenResponse")),
+            );
+        }
+    }
+}
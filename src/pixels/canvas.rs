@@ -1,4 +1,4 @@
-use super::{PIXOO_FRAME_LEN, PIXOO_PIXEL_BYTES};
+use super::{PIXOO_FRAME_DIM, PIXOO_FRAME_LEN, PIXOO_PIXEL_BYTES};
 
 /// Creates a uniform pixel buffer where every pixel has the same RGB color.
 pub fn uniform_pixel_buffer(red: u8, green: u8, blue: u8) -> Vec<u8> {
@@ -11,6 +11,22 @@ pub fn uniform_pixel_buffer(red: u8, green: u8, blue: u8) -> Vec<u8> {
     buffer
 }
 
+/// Copies `frame`, a full-screen buffer, into `canvas` scaled down to a
+/// `size`×`size` square whose top-left corner is at (`left`, `top`). Pixels
+/// are picked by nearest neighbour; whatever falls off the screen is dropped.
+pub fn draw_scaled(canvas: &mut [u8], frame: &[u8], size: usize, left: usize, top: usize) {
+    let dim = PIXOO_FRAME_DIM as usize;
+    for row in 0..size.min(dim.saturating_sub(top)) {
+        let source_row = row * dim / size;
+        for column in 0..size.min(dim.saturating_sub(left)) {
+            let source = (source_row * dim + column * dim / size) * PIXOO_PIXEL_BYTES;
+            let target = ((top + row) * dim + left + column) * PIXOO_PIXEL_BYTES;
+            canvas[target..target + PIXOO_PIXEL_BYTES]
+                .copy_from_slice(&frame[source..source + PIXOO_PIXEL_BYTES]);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -23,4 +39,20 @@ mod tests {
         let tail = &buffer[PIXOO_FRAME_LEN - 3..PIXOO_FRAME_LEN];
         assert_eq!(tail, &[255, 0, 128]);
     }
+
+    #[test]
+    fn draw_scaled_shrinks_into_the_square() {
+        let mut canvas = uniform_pixel_buffer(0, 0, 0);
+        let frame = uniform_pixel_buffer(9, 9, 9);
+        draw_scaled(&mut canvas, &frame, 32, 48, 16);
+
+        let pixel = |x: usize, y: usize| {
+            let offset = (y * 64 + x) * PIXOO_PIXEL_BYTES;
+            &canvas[offset..offset + PIXOO_PIXEL_BYTES]
+        };
+        assert_eq!(pixel(48, 16), &[9, 9, 9]);
+        assert_eq!(pixel(63, 47), &[9, 9, 9]);
+        assert_eq!(pixel(47, 16), &[0, 0, 0]);
+        assert_eq!(pixel(48, 48), &[0, 0, 0]);
+    }
 }
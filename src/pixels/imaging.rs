@@ -10,6 +10,7 @@ use super::{PIXOO_FRAME_DIM, PIXOO_FRAME_LEN};
 
 const MAX_ANIMATION_FRAMES: usize = 60;
 
+#[derive(Clone)]
 pub struct DecodedFrame {
     pub rgb_buffer: Vec<u8>,
     pub delay_ms: u32,
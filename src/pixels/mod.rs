@@ -12,6 +12,6 @@ pub const PIXOO_PIXEL_BYTES: usize = 3;
 pub const PIXOO_FRAME_LEN: usize =
     PIXOO_FRAME_DIM as usize * PIXOO_FRAME_DIM as usize * PIXOO_PIXEL_BYTES;
 
-pub use canvas::uniform_pixel_buffer;
+pub use canvas::{draw_scaled, uniform_pixel_buffer};
 pub use encoding::encode_pic_data;
 pub use imaging::{decode_upload, DecodedFrame, ImageError};
@@ -1,26 +1,50 @@
+use crate::events::{EventBus, EventKind};
+use crate::metrics::metrics;
 use crate::pixoo::command::PixooCommand;
-use crate::pixoo::error::PixooError;
+use crate::pixoo::error::{PixooError, PixooErrorCategory};
+use crate::pixoo::protocol::PixooRequest;
+use crate::pixoo::retry::{RetryBudget, RetryConfig, RetryOn, RetryPolicy};
+use crate::request_tracing::RequestId;
 use reqwest::header::CONTENT_TYPE;
 use serde_json::{Map, Value};
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant};
 use tokio::time::sleep;
-use tracing::{debug, error};
+use tracing::{debug, error, info, warn};
 
 pub type PixooResponse = Map<String, Value>;
 
 #[derive(Debug, Clone, Copy)]
 pub struct PixooClientConfig {
+    /// Per-request timeout for device calls.
     pub timeout: Duration,
-    pub retries: usize,
-    pub backoff: Duration,
+    pub retry: RetryConfig,
 }
 
 impl PixooClientConfig {
+    /// Retries every command up to `retries` times, waiting `backoff` before
+    /// the first retry and doubling it for each further one, without jitter.
+    /// Commands that are not idempotent still only retry connection failures.
     pub fn new(timeout: Duration, retries: usize, backoff: Duration) -> Self {
+        let defaults = RetryConfig::default();
+        let idempotent = RetryPolicy {
+            max_retries: retries,
+            base_delay: backoff,
+            max_delay: Duration::MAX,
+            jitter: 0.0,
+            retry_on: RetryOn::Transient,
+        };
         Self {
             timeout,
-            retries,
-            backoff,
+            retry: RetryConfig {
+                idempotent,
+                non_idempotent: RetryPolicy {
+                    retry_on: RetryOn::ConnectFailure,
+                    ..idempotent
+                },
+                budget_ratio: defaults.budget_ratio,
+            },
         }
     }
 }
@@ -29,8 +53,7 @@ impl Default for PixooClientConfig {
     fn default() -> Self {
         Self {
             timeout: Duration::from_secs(10),
-            retries: 2,
-            backoff: Duration::from_millis(200),
+            retry: RetryConfig::default(),
         }
     }
 }
@@ -40,8 +63,13 @@ pub struct PixooClient {
     post_url: String,
     get_url: String,
     http: reqwest::Client,
-    retries: usize,
-    backoff: Duration,
+    timeout: Arc<RwLock<Duration>>,
+    retry: RetryConfig,
+    budget: RetryBudget,
+    events: EventBus,
+    /// Last observed reachability; `None` until the device was contacted.
+    reachable: Arc<Mutex<Option<bool>>>,
+    commands_sent: Arc<AtomicU64>,
 }
 
 impl PixooClient {
@@ -59,25 +87,79 @@ impl PixooClient {
         let post_url = parsed.to_string();
         parsed.set_path("/get");
         let get_url = parsed.to_string();
-        let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+        let http = reqwest::Client::builder().build()?;
 
         Ok(Self {
             post_url,
             get_url,
             http,
-            retries: config.retries,
-            backoff: config.backoff,
+            timeout: Arc::new(RwLock::new(config.timeout)),
+            retry: config.retry,
+            budget: RetryBudget::new(config.retry.budget_ratio),
+            events: EventBus::new(),
+            reachable: Arc::new(Mutex::new(None)),
+            commands_sent: Arc::new(AtomicU64::new(0)),
         })
     }
 
+    /// Publishes device events (reachability changes, reboots, device
+    /// errors) to `events`.
+    #[must_use]
+    pub fn with_events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Current per-request timeout.
+    pub fn timeout(&self) -> Duration {
+        *self.timeout.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Number of state-changing commands sent by this client and its clones,
+    /// whether or not they succeeded. Callers compare two readings to tell
+    /// whether anything may have changed the display in between.
+    pub fn commands_sent(&self) -> u64 {
+        self.commands_sent.load(Ordering::Relaxed)
+    }
+
+    /// Changes the per-request timeout for this client and all its clones.
+    pub fn set_timeout(&self, timeout: Duration) {
+        *self.timeout.write().unwrap_or_else(PoisonError::into_inner) = timeout;
+    }
+
     fn build_payload(command: &PixooCommand, mut args: Map<String, Value>) -> Map<String, Value> {
         args.insert(
-            super::fields::request::COMMAND.to_string(),
+            "Command".to_string(),
             Value::String(command.as_str().to_string()),
         );
         args
     }
 
+    /// Sends a typed request and decodes its reply.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PixooError::InvalidRequest`] if the request does not
+    /// serialize to a JSON object.
+    /// Returns [`PixooError::InvalidResponse`] if the reply does not match
+    /// `R::Response`.
+    /// Otherwise fails like [`send_command`](Self::send_command).
+    pub async fn execute<R: PixooRequest>(&self, request: &R) -> Result<R::Response, PixooError> {
+        let args = match serde_json::to_value(request) {
+            Ok(Value::Object(args)) => args,
+            Ok(Value::Null) => Map::new(),
+            Ok(other) => {
+                return Err(PixooError::InvalidRequest(format!(
+                    "expected an object, got {other}"
+                )))
+            }
+            Err(err) => return Err(PixooError::InvalidRequest(err.to_string())),
+        };
+        let response = self.send_command(&R::COMMAND, args).await?;
+        serde_json::from_value(Value::Object(response))
+            .map_err(|err| PixooError::InvalidResponse(format!("{} reply: {err}", R::COMMAND)))
+    }
+
     /// Sends a command to the Pixoo device.
     ///
     /// # Errors
@@ -94,10 +176,41 @@ impl PixooClient {
     ) -> Result<PixooResponse, PixooError> {
         let payload = Self::build_payload(command, args);
         debug!(command = ?command, payload = ?payload, "sending Pixoo command");
+        if !command.is_query() {
+            self.commands_sent.fetch_add(1, Ordering::Relaxed);
+        }
 
-        let response = self.execute_with_retry(&payload).await;
-        if let Ok(ref body) = response {
-            debug!(command = ?command, response = ?body, "Pixoo command response");
+        let start = Instant::now();
+        let response = self.execute_with_retry(command, &payload).await;
+        metrics().record_pixoo_command(
+            command,
+            start.elapsed(),
+            response.as_ref().err().map(PixooError::category),
+        );
+        self.observe_reachability(response.as_ref().err());
+        match &response {
+            Ok(body) => {
+                debug!(command = ?command, response = ?body, "Pixoo command response");
+                if *command == PixooCommand::SystemReboot {
+                    self.events.publish(EventKind::DeviceRebooted);
+                }
+            }
+            Err(PixooError::DeviceError { code, .. }) => {
+                self.events.publish(EventKind::DeviceError {
+                    command: command.as_str().to_string(),
+                    error_code: *code,
+                });
+            }
+            Err(_) => {}
+        }
+        // Animation frames are reported once per upload by the caller.
+        if !command.is_query() && *command != PixooCommand::DrawSendGif {
+            self.events.publish(EventKind::Command {
+                command: command.as_str().to_string(),
+                request_id: RequestId::current().map(|id| id.to_string()),
+                success: response.is_ok(),
+                error: response.as_ref().err().map(ToString::to_string),
+            });
         }
         response
     }
@@ -109,35 +222,69 @@ impl PixooClient {
     /// Returns [`PixooError::Http`] if the request fails due to network issues.
     /// Returns [`PixooError::HttpStatus`] if the device returns a non-2xx status.
     pub async fn health_check(&self) -> Result<(), PixooError> {
-        self.execute_health_with_retry().await
+        let result = self.execute_health_with_retry().await;
+        self.observe_reachability(result.as_ref().err());
+        result
+    }
+
+    /// Tracks whether the device answers and publishes online/offline
+    /// transitions. Any response, even an error, counts as reachable.
+    fn observe_reachability(&self, error: Option<&PixooError>) {
+        let offline_reason = error
+            .filter(|err| {
+                matches!(
+                    err.category(),
+                    PixooErrorCategory::Unreachable | PixooErrorCategory::Timeout
+                )
+            })
+            .map(ToString::to_string);
+        let reachable = offline_reason.is_none();
+        let previous = self
+            .reachable
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .replace(reachable);
+        match (previous, offline_reason) {
+            (Some(true) | None, Some(reason)) => {
+                warn!(reason = %reason, "Pixoo device went offline");
+                self.events.publish(EventKind::DeviceOffline { reason });
+            }
+            (Some(false), None) => {
+                info!("Pixoo device is back online");
+                self.events.publish(EventKind::DeviceOnline);
+            }
+            _ => {}
+        }
     }
 
     async fn execute_with_retry(
         &self,
+        command: &PixooCommand,
         payload: &Map<String, Value>,
     ) -> Result<PixooResponse, PixooError> {
-        let mut attempt = 0;
+        let policy = self.retry.policy(command);
+        let mut retries = 0;
+        self.budget.deposit();
 
         loop {
             match self.execute_once(payload).await {
                 Ok(response) => {
-                    if attempt > 0 {
+                    if retries > 0 {
                         debug!(
-                            attempts = attempt + 1,
+                            attempts = retries + 1,
                             "Pixoo command succeeded after retries"
                         );
                     }
                     return Ok(response);
                 }
                 Err(err) => {
-                    let retriable = is_retriable(&err);
-                    if attempt >= self.retries || !retriable {
-                        log_pixoo_error("sending Pixoo command", &err, retriable);
+                    let Some(delay) = self.retry_delay(policy, retries, &err) else {
+                        log_pixoo_error("sending Pixoo command", &err, policy.should_retry(&err));
                         return Err(err);
-                    }
+                    };
 
-                    attempt += 1;
-                    let delay = self.backoff * u32::try_from(attempt).unwrap_or(u32::MAX);
+                    retries += 1;
+                    metrics().record_pixoo_retry(command);
                     sleep(delay).await;
                 }
             }
@@ -145,34 +292,52 @@ impl PixooClient {
     }
 
     async fn execute_health_with_retry(&self) -> Result<(), PixooError> {
-        let mut attempt = 0;
+        let policy = &self.retry.idempotent;
+        let mut retries = 0;
+        self.budget.deposit();
 
         loop {
             match self.execute_health_once().await {
                 Ok(()) => {
-                    if attempt > 0 {
+                    if retries > 0 {
                         debug!(
-                            attempts = attempt + 1,
+                            attempts = retries + 1,
                             "Pixoo health check succeeded after retries"
                         );
                     }
                     return Ok(());
                 }
                 Err(err) => {
-                    let retriable = is_retriable(&err);
-                    if attempt >= self.retries || !retriable {
-                        log_pixoo_error("Pixoo health check", &err, retriable);
+                    let Some(delay) = self.retry_delay(policy, retries, &err) else {
+                        log_pixoo_error("Pixoo health check", &err, policy.should_retry(&err));
                         return Err(err);
-                    }
+                    };
 
-                    attempt += 1;
-                    let delay = self.backoff * u32::try_from(attempt).unwrap_or(u32::MAX);
+                    retries += 1;
                     sleep(delay).await;
                 }
             }
         }
     }
 
+    /// Wait before the next attempt after `retries` retries failed with
+    /// `err`, or `None` if the error should be returned.
+    fn retry_delay(
+        &self,
+        policy: &RetryPolicy,
+        retries: usize,
+        err: &PixooError,
+    ) -> Option<Duration> {
+        if retries >= policy.max_retries || !policy.should_retry(err) {
+            return None;
+        }
+        if !self.budget.withdraw() {
+            warn!(error = %err, "Pixoo retry budget exhausted; not retrying");
+            return None;
+        }
+        Some(policy.delay(retries + 1, rand::random()))
+    }
+
     async fn execute_once(
         &self,
         payload: &Map<String, Value>,
@@ -180,6 +345,7 @@ impl PixooClient {
         let response = self
             .http
             .post(&self.post_url)
+            .timeout(self.timeout())
             .header(CONTENT_TYPE, "application/json")
             .json(payload)
             .send()
@@ -196,7 +362,12 @@ impl PixooClient {
     }
 
     async fn execute_health_once(&self) -> Result<(), PixooError> {
-        let response = self.http.get(&self.get_url).send().await?;
+        let response = self
+            .http
+            .get(&self.get_url)
+            .timeout(self.timeout())
+            .send()
+            .await?;
 
         let status = response.status();
 
@@ -208,14 +379,6 @@ impl PixooClient {
     }
 }
 
-fn is_retriable(err: &PixooError) -> bool {
-    match err {
-        PixooError::Http(_) => true,
-        PixooError::HttpStatus(status) => *status >= 500,
-        _ => false,
-    }
-}
-
 fn parse_response(body: &str) -> Result<PixooResponse, PixooError> {
     let value: Value =
         serde_json::from_str(body).map_err(|err| PixooError::InvalidResponse(err.to_string()))?;
@@ -267,7 +430,7 @@ fn log_pixoo_error(context: &str, err: &PixooError, retriable: bool) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::pixoo::fields::{request as req, response as resp};
+    use crate::pixoo::protocol::{GetAllConf, SetBrightness};
     use axum::extract::State;
     use axum::http::StatusCode;
     use axum::routing::post;
@@ -289,8 +452,15 @@ mod tests {
             PixooClientConfig::new(Duration::from_millis(250), 7, Duration::from_millis(90));
         let client = PixooClient::new("http://127.0.0.1", config).expect("client");
 
-        assert_eq!(client.retries, 7);
-        assert_eq!(client.backoff, Duration::from_millis(90));
+        assert_eq!(client.retry.idempotent.max_retries, 7);
+        assert_eq!(
+            client.retry.idempotent.base_delay,
+            Duration::from_millis(90)
+        );
+        assert_eq!(
+            client.retry.non_idempotent.retry_on,
+            RetryOn::ConnectFailure
+        );
     }
 
     #[derive(Clone)]
@@ -330,19 +500,69 @@ mod tests {
     #[test]
     fn builds_payload_with_command_and_args() {
         let mut args = Map::new();
-        args.insert(req::MINUTE.to_string(), Value::Number(1.into()));
-        args.insert(req::SECOND.to_string(), Value::Number(0.into()));
-        args.insert(req::STATUS.to_string(), Value::Number(1.into()));
+        args.insert("Minute".to_string(), Value::Number(1.into()));
+        args.insert("Second".to_string(), Value::Number(0.into()));
+        args.insert("Status".to_string(), Value::Number(1.into()));
 
         let payload = PixooClient::build_payload(&PixooCommand::SystemReboot, args);
 
         assert_eq!(
-            payload.get(req::COMMAND),
+            payload.get("Command"),
             Some(&Value::String("Device/SysReboot".to_string()))
         );
-        assert_eq!(payload.get(req::MINUTE), Some(&Value::Number(1.into())));
-        assert_eq!(payload.get(req::SECOND), Some(&Value::Number(0.into())));
-        assert_eq!(payload.get(req::STATUS), Some(&Value::Number(1.into())));
+        assert_eq!(payload.get("Minute"), Some(&Value::Number(1.into())));
+        assert_eq!(payload.get("Second"), Some(&Value::Number(0.into())));
+        assert_eq!(payload.get("Status"), Some(&Value::Number(1.into())));
+    }
+
+    #[tokio::test]
+    async fn executes_typed_requests() {
+        let server = MockServer::start_async().await;
+        let set = server.mock(|when, then| {
+            when.method(POST)
+                .path("/post")
+                .json_body(json!({ "Command": "Channel/SetBrightness", "Brightness": 40 }));
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let get = server.mock(|when, then| {
+            when.method(POST)
+                .path("/post")
+                .json_body(json!({ "Command": "Channel/GetAllConf" }));
+            then.status(200).body(
+                json!({
+                    "error_code": 0, "LightSwitch": 1, "Brightness": "40", "Time24Flag": 0,
+                    "RotationFlag": 2, "MirrorFlag": 0, "TemperatureMode": 0, "CurClockId": 7
+                })
+                .to_string(),
+            );
+        });
+
+        let client = PixooClient::new(server.base_url(), default_config()).expect("client");
+        client
+            .execute(&SetBrightness { brightness: 40 })
+            .await
+            .expect("ack");
+        let settings = client.execute(&GetAllConf).await.expect("settings");
+
+        set.assert();
+        get.assert();
+        assert_eq!(settings.brightness, 40);
+        assert_eq!(settings.rotation_flag, 2);
+    }
+
+    #[tokio::test]
+    async fn rejects_replies_that_do_not_match_the_response_type() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(POST).path("/post");
+            then.status(200)
+                .body(r#"{"error_code":0,"Brightness":"bright"}"#);
+        });
+
+        let client = PixooClient::new(server.base_url(), default_config()).expect("client");
+        let err = client.execute(&GetAllConf).await.expect_err("decode error");
+
+        assert!(matches!(err, PixooError::InvalidResponse(_)));
     }
 
     #[test]
@@ -358,14 +578,8 @@ mod tests {
         .expect("response should parse");
 
         assert!(response.get("error_code").is_none());
-        assert_eq!(
-            response.get(resp::BRIGHTNESS),
-            Some(&Value::Number(100.into()))
-        );
-        assert_eq!(
-            response.get(resp::ROTATION_FLAG),
-            Some(&Value::Number(1.into()))
-        );
+        assert_eq!(response.get("Brightness"), Some(&Value::Number(100.into())));
+        assert_eq!(response.get("RotationFlag"), Some(&Value::Number(1.into())));
     }
 
     #[test]
@@ -433,7 +647,7 @@ mod tests {
 
         let client = PixooClient::new(base_url, default_config()).expect("client");
         let response = client
-            .send_command(&PixooCommand::SystemReboot, Map::new())
+            .send_command(&PixooCommand::ManageDisplayBrightness, Map::new())
             .await
             .expect("request should succeed");
 
@@ -441,6 +655,40 @@ mod tests {
         assert_eq!(counter.load(Ordering::SeqCst), 3);
     }
 
+    #[tokio::test]
+    async fn retries_non_idempotent_commands_only_when_unsent() {
+        let (base_url, counter) =
+            start_sequence_server(vec![StatusCode::INTERNAL_SERVER_ERROR, StatusCode::OK]).await;
+
+        let client = PixooClient::new(base_url, default_config()).expect("client");
+        let err = client
+            .send_command(&PixooCommand::DrawSendGif, Map::new())
+            .await
+            .expect_err("expected http status error");
+
+        assert!(matches!(err, PixooError::HttpStatus(500)));
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn stops_retrying_when_the_budget_is_spent() {
+        let (base_url, counter) = start_sequence_server(Vec::new()).await;
+        let mut config = PixooClientConfig::new(Duration::from_secs(10), 3, Duration::ZERO);
+        config.retry.budget_ratio = 0.0;
+        let client = PixooClient::new(base_url, config).expect("client");
+
+        for _ in 0..4 {
+            client
+                .send_command(&PixooCommand::ManageDisplayBrightness, Map::new())
+                .await
+                .expect_err("expected http status error");
+        }
+
+        // Three retries for each of the first three calls, one left for the
+        // fourth, which is then sent without further retries.
+        assert_eq!(counter.load(Ordering::SeqCst), 4 + 10);
+    }
+
     #[tokio::test]
     async fn does_not_retry_on_client_errors() {
         let (base_url, counter) = start_sequence_server(vec![StatusCode::BAD_REQUEST]).await;
@@ -506,7 +754,7 @@ mod tests {
         let client = PixooClient::new(base_url, config).expect("client");
 
         let response = client
-            .send_command(&PixooCommand::SystemReboot, Map::new())
+            .send_command(&PixooCommand::ManageDisplayBrightness, Map::new())
             .await
             .expect("request should succeed");
 
@@ -557,4 +805,125 @@ mod tests {
         assert!(response.is_empty());
         mock.assert();
     }
+
+    #[tokio::test]
+    async fn publishes_reboot_and_device_error_events() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/post")
+                .body_includes("Device/SysReboot");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        server.mock(|when, then| {
+            when.method(POST)
+                .path("/post")
+                .body_includes("Channel/SetBrightness");
+            then.status(200).body(r#"{"error_code":7}"#);
+        });
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let client = PixooClient::new(server.base_url(), default_config())
+            .expect("client")
+            .with_events(events);
+
+        client
+            .send_command(&PixooCommand::SystemReboot, Map::new())
+            .await
+            .expect("reboot accepted");
+        let _ = client
+            .send_command(&PixooCommand::ManageDisplayBrightness, Map::new())
+            .await;
+
+        assert_eq!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::DeviceRebooted
+        );
+        assert!(matches!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::Command { success: true, .. }
+        ));
+        assert_eq!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::DeviceError {
+                command: "Channel/SetBrightness".to_string(),
+                error_code: 7,
+            }
+        );
+        assert!(matches!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::Command { success: false, .. }
+        ));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn publishes_commands_but_not_queries_or_frames() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let client = PixooClient::new(server.base_url(), default_config())
+            .expect("client")
+            .with_events(events);
+
+        client
+            .send_command(&PixooCommand::ManageGetSettings, Map::new())
+            .await
+            .expect("query");
+        client
+            .send_command(&PixooCommand::DrawSendGif, Map::new())
+            .await
+            .expect("frame");
+        client
+            .send_command(&PixooCommand::ManageDisplayPower, Map::new())
+            .await
+            .expect("command");
+
+        assert_eq!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::Command {
+                command: "Channel/OnOffScreen".to_string(),
+                request_id: None,
+                success: true,
+                error: None,
+            }
+        );
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn publishes_reachability_transitions() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let config = PixooClientConfig::new(Duration::from_millis(500), 0, Duration::ZERO);
+        let client = PixooClient::new(format!("http://{addr}"), config)
+            .expect("client")
+            .with_events(events);
+
+        assert!(client.health_check().await.is_err());
+        assert!(client.health_check().await.is_err());
+        assert!(matches!(
+            receiver.recv().await.unwrap().kind,
+            EventKind::DeviceOffline { .. }
+        ));
+        assert!(
+            receiver.try_recv().is_err(),
+            "offline is only reported once"
+        );
+
+        let app = Router::new().route("/get", axum::routing::get(|| async { "ok" }));
+        let listener = TcpListener::bind(addr).await.unwrap();
+        let server = tokio::spawn(async move { axum::serve(listener, app).await });
+
+        client.health_check().await.expect("device reachable");
+        assert_eq!(receiver.recv().await.unwrap().kind, EventKind::DeviceOnline);
+        server.abort();
+    }
 }
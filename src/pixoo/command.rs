@@ -7,6 +7,7 @@ pub enum PixooCommand {
     ToolsStopwatch,
     ToolsScoreboard,
     ToolsSoundMeter,
+    ToolsBuzzer,
     ManageGetSettings,
     ManageGetTime,
     ManageGetWeather,
@@ -21,12 +22,19 @@ pub enum PixooCommand {
     ManageDisplayMirror,
     ManageDisplayOverclock,
     ManageDisplayWhiteBalance,
+    ManageChannelSet,
+    ManageChannelGet,
+    ManageChannelClock,
+    ManageChannelGetClock,
+    ManageChannelCustomPage,
+    ManageChannelVisualizer,
+    ManageChannelCloud,
     DrawGetGifId,
     DrawSendGif,
-    #[allow(dead_code)] // Required by spec but not yet used
     DrawResetGifId,
     DrawSendText,
     DrawClearText,
+    DrawSendItemList,
 }
 
 impl PixooCommand {
@@ -37,6 +45,7 @@ impl PixooCommand {
             PixooCommand::ToolsStopwatch => "Tools/SetStopWatch",
             PixooCommand::ToolsScoreboard => "Tools/SetScoreBoard",
             PixooCommand::ToolsSoundMeter => "Tools/SetNoiseStatus",
+            PixooCommand::ToolsBuzzer => "Device/PlayBuzzer",
             PixooCommand::ManageGetSettings => "Channel/GetAllConf",
             PixooCommand::ManageGetTime => "Device/GetDeviceTime",
             PixooCommand::ManageGetWeather => "Device/GetWeatherInfo",
@@ -51,13 +60,48 @@ impl PixooCommand {
             PixooCommand::ManageDisplayMirror => "Device/SetMirrorMode",
             PixooCommand::ManageDisplayOverclock => "Device/SetHighLightMode",
             PixooCommand::ManageDisplayWhiteBalance => "Device/SetWhiteBalance",
+            PixooCommand::ManageChannelSet => "Channel/SetIndex",
+            PixooCommand::ManageChannelGet => "Channel/GetIndex",
+            PixooCommand::ManageChannelClock => "Channel/SetClockSelectId",
+            PixooCommand::ManageChannelGetClock => "Channel/GetClockInfo",
+            PixooCommand::ManageChannelCustomPage => "Channel/SetCustomPageIndex",
+            PixooCommand::ManageChannelVisualizer => "Channel/SetEqPosition",
+            PixooCommand::ManageChannelCloud => "Channel/CloudIndex",
             PixooCommand::DrawGetGifId => "Draw/GetHttpGifId",
             PixooCommand::DrawSendGif => "Draw/SendHttpGif",
             PixooCommand::DrawResetGifId => "Draw/ResetHttpGifId",
             PixooCommand::DrawSendText => "Draw/SendHttpText",
             PixooCommand::DrawClearText => "Draw/ClearHttpText",
+            PixooCommand::DrawSendItemList => "Draw/SendHttpItemList",
         }
     }
+
+    /// Whether the command only reads device state.
+    pub fn is_query(&self) -> bool {
+        matches!(
+            self,
+            PixooCommand::ManageGetSettings
+                | PixooCommand::ManageGetTime
+                | PixooCommand::ManageGetWeather
+                | PixooCommand::ManageChannelGet
+                | PixooCommand::ManageChannelGetClock
+                | PixooCommand::DrawGetGifId
+        )
+    }
+
+    /// Whether sending the command twice has the same effect as sending it
+    /// once. Reserving an animation id and uploading frames advance device
+    /// counters, a repeated reboot restarts the device again, and a repeated
+    /// buzzer command beeps twice.
+    pub fn is_idempotent(&self) -> bool {
+        !matches!(
+            self,
+            PixooCommand::SystemReboot
+                | PixooCommand::ToolsBuzzer
+                | PixooCommand::DrawGetGifId
+                | PixooCommand::DrawSendGif
+        )
+    }
 }
 
 impl fmt::Display for PixooCommand {
@@ -83,6 +127,7 @@ mod tests {
             PixooCommand::ToolsSoundMeter.as_str(),
             "Tools/SetNoiseStatus"
         );
+        assert_eq!(PixooCommand::ToolsBuzzer.as_str(), "Device/PlayBuzzer");
         assert_eq!(
             PixooCommand::ManageGetSettings.as_str(),
             "Channel/GetAllConf"
@@ -127,10 +172,36 @@ mod tests {
             PixooCommand::ManageDisplayWhiteBalance.as_str(),
             "Device/SetWhiteBalance"
         );
+        assert_eq!(PixooCommand::ManageChannelSet.as_str(), "Channel/SetIndex");
+        assert_eq!(PixooCommand::ManageChannelGet.as_str(), "Channel/GetIndex");
+        assert_eq!(
+            PixooCommand::ManageChannelClock.as_str(),
+            "Channel/SetClockSelectId"
+        );
+        assert_eq!(
+            PixooCommand::ManageChannelGetClock.as_str(),
+            "Channel/GetClockInfo"
+        );
+        assert_eq!(
+            PixooCommand::ManageChannelCustomPage.as_str(),
+            "Channel/SetCustomPageIndex"
+        );
+        assert_eq!(
+            PixooCommand::ManageChannelVisualizer.as_str(),
+            "Channel/SetEqPosition"
+        );
+        assert_eq!(
+            PixooCommand::ManageChannelCloud.as_str(),
+            "Channel/CloudIndex"
+        );
         assert_eq!(PixooCommand::DrawGetGifId.as_str(), "Draw/GetHttpGifId");
         assert_eq!(PixooCommand::DrawSendGif.as_str(), "Draw/SendHttpGif");
         assert_eq!(PixooCommand::DrawResetGifId.as_str(), "Draw/ResetHttpGifId");
         assert_eq!(PixooCommand::DrawSendText.as_str(), "Draw/SendHttpText");
         assert_eq!(PixooCommand::DrawClearText.as_str(), "Draw/ClearHttpText");
+        assert_eq!(
+            PixooCommand::DrawSendItemList.as_str(),
+            "Draw/SendHttpItemList"
+        );
     }
 }
@@ -15,6 +15,9 @@ pub enum PixooError {
     #[error("invalid base url: {0}")]
     InvalidBaseUrl(String),
 
+    #[error("invalid request: {0}")]
+    InvalidRequest(String),
+
     #[error("invalid response: {0}")]
     InvalidResponse(String),
 
@@ -66,7 +69,9 @@ impl PixooError {
             | PixooError::InvalidResponse(_)
             | PixooError::MissingErrorCode
             | PixooError::InvalidErrorCode(_) => PixooErrorCategory::DeviceError,
-            PixooError::InvalidBaseUrl(_) => PixooErrorCategory::Unknown,
+            PixooError::InvalidBaseUrl(_) | PixooError::InvalidRequest(_) => {
+                PixooErrorCategory::Unknown
+            }
         }
     }
 }
@@ -79,10 +84,21 @@ pub enum PixooErrorCategory {
     Unknown,
 }
 
+impl PixooErrorCategory {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PixooErrorCategory::Unreachable => "unreachable",
+            PixooErrorCategory::Timeout => "timeout",
+            PixooErrorCategory::DeviceError => "device-error",
+            PixooErrorCategory::Unknown => "unknown",
+        }
+    }
+}
+
 /// Discriminator for every error envelope.
 ///
-/// `validation`, `not-found`, and `payload-too-large` describe request-side
-/// failures; `unreachable`, `timeout`, and `device-error` originate from the
+/// `validation`, `not-found`, `conflict`, and `payload-too-large` describe
+/// request-side failures; `unauthorized` and `forbidden` are API key rejections; `unreachable`, `timeout`, and `device-error` originate from the
 /// Pixoo device; `remote-fetch` covers failed remote image downloads; and
 /// `internal` covers unexpected bridge-side failures (e.g. encoding or response
 /// parsing).
@@ -91,7 +107,10 @@ pub enum PixooErrorCategory {
 pub enum PixooHttpErrorKind {
     Validation,
     NotFound,
+    Conflict,
     PayloadTooLarge,
+    Unauthorized,
+    Forbidden,
     Unreachable,
     Timeout,
     DeviceError,
@@ -106,7 +125,7 @@ pub enum PixooHttpErrorKind {
 /// and any kind-specific data lives in the optional `details` object. `details`
 /// is omitted entirely when there is no extra data; its per-kind contents are:
 /// validation → a field/action error map; payload-too-large → `{ limit, actual }`;
-/// device errors → `{ error_code }` when the device provided one.
+/// forbidden → `{ required_scope }`; device errors → `{ error_code }` when the device provided one.
 #[derive(Debug, Serialize, ToSchema)]
 #[schema(examples(
     json!({
@@ -221,6 +240,46 @@ pub struct ValidationErrorResponse(PixooHttpErrorResponse);
 )]
 pub struct PayloadTooLargeResponse(PixooHttpErrorResponse);
 
+/// Reusable `401 Unauthorized` response component.
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(
+    description = "API key missing or invalid",
+    example = json!({
+        "error_status": 401,
+        "error_kind": "unauthorized",
+        "message": "missing API key"
+    })
+)]
+pub struct UnauthorizedResponse(PixooHttpErrorResponse);
+
+/// Reusable `403 Forbidden` response component.
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(
+    description = "API key lacks the scope required by this route",
+    example = json!({
+        "error_status": 403,
+        "error_kind": "forbidden",
+        "message": "API key lacks the 'system' scope",
+        "details": { "required_scope": "system" }
+    })
+)]
+pub struct ForbiddenResponse(PixooHttpErrorResponse);
+
+/// Reusable `409 Conflict` response component.
+#[allow(dead_code)]
+#[derive(ToResponse)]
+#[response(
+    description = "Another draw is uploading frames, or a newer draw took over",
+    example = json!({
+        "error_status": 409,
+        "error_kind": "conflict",
+        "message": "another draw is still uploading"
+    })
+)]
+pub struct ConflictResponse(PixooHttpErrorResponse);
+
 /// Reusable `500 Internal Error` response component.
 #[allow(dead_code)]
 #[derive(ToResponse)]
@@ -290,7 +349,10 @@ pub fn map_pixoo_error(
         // `kind` is always a device variant here; all remaining kinds map to 503.
         PixooHttpErrorKind::Validation
         | PixooHttpErrorKind::NotFound
+        | PixooHttpErrorKind::Conflict
         | PixooHttpErrorKind::PayloadTooLarge
+        | PixooHttpErrorKind::Unauthorized
+        | PixooHttpErrorKind::Forbidden
         | PixooHttpErrorKind::DeviceError
         | PixooHttpErrorKind::RemoteFetch
         | PixooHttpErrorKind::Internal => StatusCode::SERVICE_UNAVAILABLE,
@@ -0,0 +1,163 @@
+//! Named buzzer melodies.
+//!
+//! The buzzer plays one on/off pattern per command, so a melody is a list of
+//! [`PlayBuzzer`] steps sent one after another, each once the previous one
+//! finished plus a short pause.
+
+use crate::pixoo::error::PixooError;
+use crate::pixoo::protocol::PlayBuzzer;
+use crate::pixoo::PixooClient;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use tokio::time::sleep;
+
+/// One buzzer command followed by silence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub buzz: PlayBuzzer,
+    /// Silence after the buzz, in milliseconds.
+    pub pause: u32,
+}
+
+const fn step(active: u32, off: u32, total: u32, pause: u32) -> Step {
+    Step {
+        buzz: PlayBuzzer {
+            active_time_in_cycle: active,
+            off_time_in_cycle: off,
+            play_total_time: total,
+        },
+        pause,
+    }
+}
+
+const BEEP: &[Step] = &[step(150, 0, 150, 0)];
+const DOUBLE: &[Step] = &[step(100, 100, 300, 0)];
+const CHIME: &[Step] = &[step(80, 80, 240, 150), step(400, 0, 400, 0)];
+const ALARM: &[Step] = &[
+    step(150, 100, 750, 400),
+    step(150, 100, 750, 400),
+    step(150, 100, 750, 0),
+];
+const SUCCESS: &[Step] = &[step(100, 0, 100, 100), step(300, 0, 300, 0)];
+const FAILURE: &[Step] = &[step(500, 0, 500, 200), step(500, 0, 500, 0)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Melody {
+    Beep,
+    Double,
+    Chime,
+    Alarm,
+    Success,
+    Failure,
+}
+
+impl Melody {
+    /// Names accepted by [`FromStr`], in declaration order.
+    pub const NAMES: &'static [&'static str] =
+        &["beep", "double", "chime", "alarm", "success", "failure"];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Melody::Beep => "beep",
+            Melody::Double => "double",
+            Melody::Chime => "chime",
+            Melody::Alarm => "alarm",
+            Melody::Success => "success",
+            Melody::Failure => "failure",
+        }
+    }
+
+    pub fn steps(self) -> &'static [Step] {
+        match self {
+            Melody::Beep => BEEP,
+            Melody::Double => DOUBLE,
+            Melody::Chime => CHIME,
+            Melody::Alarm => ALARM,
+            Melody::Success => SUCCESS,
+            Melody::Failure => FAILURE,
+        }
+    }
+}
+
+impl FromStr for Melody {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "beep" => Ok(Melody::Beep),
+            "double" => Ok(Melody::Double),
+            "chime" => Ok(Melody::Chime),
+            "alarm" => Ok(Melody::Alarm),
+            "success" => Ok(Melody::Success),
+            "failure" => Ok(Melody::Failure),
+            other => Err(format!(
+                "unknown melody '{other}' (expected one of {})",
+                Melody::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Melody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Plays `melody` and returns once its last buzz was sent.
+///
+/// # Errors
+///
+/// Returns the error of the first buzzer command that failed; the rest of
+/// the melody is not played.
+pub async fn play(client: &PixooClient, melody: Melody) -> Result<(), PixooError> {
+    let steps = melody.steps();
+    for (index, step) in steps.iter().enumerate() {
+        client.execute(&step.buzz).await?;
+        if index + 1 < steps.len() {
+            let wait = step.buzz.play_total_time.saturating_add(step.pause);
+            sleep(Duration::from_millis(u64::from(wait))).await;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixoo::PixooClientConfig;
+    use httpmock::{Method as MockMethod, MockServer};
+
+    #[test]
+    fn names_round_trip() {
+        for name in Melody::NAMES {
+            let melody: Melody = name.parse().expect("known melody");
+            assert_eq!(melody.as_str(), *name);
+        }
+        assert!("fanfare".parse::<Melody>().is_err());
+    }
+
+    #[tokio::test]
+    async fn plays_every_step() {
+        let server = MockServer::start_async().await;
+        let short = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"PlayTotalTime\":240");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let long = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"PlayTotalTime\":400");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let client = PixooClient::new(server.base_url(), PixooClientConfig::default()).unwrap();
+
+        play(&client, Melody::Chime).await.expect("melody played");
+
+        short.assert();
+        long.assert();
+    }
+}
@@ -1,8 +1,14 @@
 pub mod client;
 pub mod command;
 pub mod error;
-pub mod fields;
+pub mod melody;
+mod monitor;
+pub mod protocol;
+pub mod retry;
 
 pub use client::{PixooClient, PixooClientConfig};
 pub use command::PixooCommand;
-pub use error::map_pixoo_error;
+pub use error::{map_pixoo_error, PixooErrorCategory};
+pub use melody::Melody;
+pub use monitor::spawn_health_monitor;
+pub use retry::{RetryConfig, RetryOn, RetryPolicy};
@@ -0,0 +1,50 @@
+//! Background health polling.
+//!
+//! Reachability changes are otherwise only noticed when a request reaches the
+//! device, so an idle bridge would never report the device going offline.
+//! The monitor periodically runs a health check; the client publishes the
+//! resulting online/offline transitions.
+
+use super::PixooClient;
+use std::time::Duration;
+
+/// Runs [`PixooClient::health_check`] every `interval` until aborted.
+pub fn spawn_health_monitor(
+    client: PixooClient,
+    interval: Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            // Failures are logged and published by the client.
+            let _ = client.health_check().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBus, EventKind};
+    use crate::pixoo::PixooClientConfig;
+
+    #[tokio::test]
+    async fn reports_offline_device() {
+        let events = EventBus::new();
+        let mut receiver = events.subscribe();
+        let config = PixooClientConfig::new(Duration::from_millis(200), 0, Duration::ZERO);
+        let client = PixooClient::new("http://127.0.0.1:9", config)
+            .expect("client")
+            .with_events(events);
+
+        let handle = spawn_health_monitor(client, Duration::from_millis(20));
+        let event = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("event published")
+            .unwrap();
+        handle.abort();
+
+        assert!(matches!(event.kind, EventKind::DeviceOffline { .. }));
+    }
+}
@@ -0,0 +1,210 @@
+use super::{flex, Ack, PixooRequest};
+use crate::pixoo::PixooCommand;
+use serde::{Deserialize, Serialize};
+
+/// Reserves the next animation id for [`SendGif`].
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetGifId;
+
+impl PixooRequest for GetGifId {
+    const COMMAND: PixooCommand = PixooCommand::DrawGetGifId;
+    type Response = GifId;
+}
+
+/// Reply of [`GetGifId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct GifId {
+    #[serde(rename = "PicId", deserialize_with = "flex::int")]
+    pub pic_id: i64,
+}
+
+/// Uploads one frame of an animation. The device shows the animation once
+/// all `pic_num` frames with the same `pic_id` have arrived.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SendGif {
+    pub pic_id: i64,
+    pub pic_num: u32,
+    /// Zero-based frame index.
+    pub pic_offset: u32,
+    /// Frame edge in pixels (16, 32, or 64).
+    pub pic_width: u32,
+    /// Frame duration in milliseconds.
+    pub pic_speed: u32,
+    /// Base64 of the RGB bytes, row by row.
+    pub pic_data: String,
+}
+
+impl PixooRequest for SendGif {
+    const COMMAND: PixooCommand = PixooCommand::DrawSendGif;
+    type Response = Ack;
+}
+
+/// Resets the animation id counter.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ResetGifId;
+
+impl PixooRequest for ResetGifId {
+    const COMMAND: PixooCommand = PixooCommand::DrawResetGifId;
+    type Response = Ack;
+}
+
+/// Scroll direction of a text item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum TextDirection {
+    Left,
+    Right,
+}
+
+impl From<TextDirection> for u8 {
+    fn from(direction: TextDirection) -> Self {
+        match direction {
+            TextDirection::Left => 0,
+            TextDirection::Right => 1,
+        }
+    }
+}
+
+/// Horizontal alignment of a text item inside its width.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum TextAlign {
+    Left,
+    Middle,
+    Right,
+}
+
+impl From<TextAlign> for u8 {
+    fn from(align: TextAlign) -> Self {
+        match align {
+            TextAlign::Left => 1,
+            TextAlign::Middle => 2,
+            TextAlign::Right => 3,
+        }
+    }
+}
+
+/// Draws a text item over the current animation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SendText {
+    #[serde(rename = "LcdId")]
+    pub lcd_id: u8,
+    /// Item slot; sending the same id replaces the item.
+    #[serde(rename = "TextId")]
+    pub text_id: u16,
+    pub x: u16,
+    pub y: u16,
+    pub dir: TextDirection,
+    pub font: u16,
+    #[serde(rename = "TextWidth")]
+    pub text_width: u16,
+    /// Scroll step duration in milliseconds.
+    pub speed: u16,
+    #[serde(rename = "TextString")]
+    pub text_string: String,
+    /// `#RRGGBB`.
+    pub color: String,
+    pub align: TextAlign,
+}
+
+impl PixooRequest for SendText {
+    const COMMAND: PixooCommand = PixooCommand::DrawSendText;
+    type Response = Ack;
+}
+
+/// Removes every text item.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ClearText;
+
+impl PixooRequest for ClearText {
+    const COMMAND: PixooCommand = PixooCommand::DrawClearText;
+    type Response = Ack;
+}
+
+/// What an entry of [`SendItemList`] shows. Every kind but [`ItemType::Text`]
+/// is filled in and kept current by the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum ItemType {
+    Second,
+    Minute,
+    Hour,
+    /// `AM` or `PM`.
+    Meridiem,
+    HourMinute,
+    HourMinuteSecond,
+    Year,
+    Day,
+    Month,
+    MonthYear,
+    MonthDayYear,
+    Temperature,
+    TodayHigh,
+    TodayLow,
+    /// Weather description, such as `Sunny`.
+    Weather,
+    /// The item's `TextString`.
+    Text,
+}
+
+impl From<ItemType> for u8 {
+    fn from(item_type: ItemType) -> Self {
+        match item_type {
+            ItemType::Second => 1,
+            ItemType::Minute => 2,
+            ItemType::Hour => 3,
+            ItemType::Meridiem => 4,
+            ItemType::HourMinute => 5,
+            ItemType::HourMinuteSecond => 6,
+            ItemType::Year => 7,
+            ItemType::Day => 8,
+            ItemType::Month => 9,
+            ItemType::MonthYear => 10,
+            ItemType::MonthDayYear => 11,
+            ItemType::Temperature => 14,
+            ItemType::TodayHigh => 15,
+            ItemType::TodayLow => 16,
+            ItemType::Weather => 17,
+            ItemType::Text => 22,
+        }
+    }
+}
+
+/// One entry of [`SendItemList`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ListItem {
+    /// Item slot; sending the same id replaces the item.
+    #[serde(rename = "TextId")]
+    pub text_id: u16,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    pub x: u16,
+    pub y: u16,
+    pub dir: TextDirection,
+    pub font: u16,
+    #[serde(rename = "TextWidth")]
+    pub text_width: u16,
+    #[serde(rename = "Textheight")]
+    pub text_height: u16,
+    /// Only sent for [`ItemType::Text`].
+    #[serde(rename = "TextString", skip_serializing_if = "Option::is_none")]
+    pub text_string: Option<String>,
+    /// Scroll step duration in milliseconds.
+    pub speed: u16,
+    /// `#RRGGBB`.
+    pub color: String,
+    pub align: TextAlign,
+}
+
+/// Draws several text and live data items over the current animation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SendItemList {
+    #[serde(rename = "ItemList")]
+    pub items: Vec<ListItem>,
+}
+
+impl PixooRequest for SendItemList {
+    const COMMAND: PixooCommand = PixooCommand::DrawSendItemList;
+    type Response = Ack;
+}
@@ -0,0 +1,418 @@
+use super::{flex, Ack, PixooRequest};
+use crate::pixoo::PixooCommand;
+use serde::{Deserialize, Serialize};
+
+/// Reads the display and clock configuration.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetAllConf;
+
+impl PixooRequest for GetAllConf {
+    const COMMAND: PixooCommand = PixooCommand::ManageGetSettings;
+    type Response = DeviceSettings;
+}
+
+/// Reply of [`GetAllConf`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+#[allow(clippy::struct_excessive_bools)] // one per device flag
+pub struct DeviceSettings {
+    #[serde(deserialize_with = "flex::flag")]
+    pub light_switch: bool,
+    #[serde(deserialize_with = "flex::int")]
+    pub brightness: i64,
+    #[serde(rename = "Time24Flag", deserialize_with = "flex::flag")]
+    pub time_24_flag: bool,
+    /// Quarter turns clockwise.
+    #[serde(deserialize_with = "flex::int")]
+    pub rotation_flag: i64,
+    #[serde(deserialize_with = "flex::flag")]
+    pub mirror_flag: bool,
+    /// Set when temperatures are shown in Fahrenheit.
+    #[serde(deserialize_with = "flex::flag")]
+    pub temperature_mode: bool,
+    #[serde(deserialize_with = "flex::int")]
+    pub cur_clock_id: i64,
+}
+
+/// Reads the device clock.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetDeviceTime;
+
+impl PixooRequest for GetDeviceTime {
+    const COMMAND: PixooCommand = PixooCommand::ManageGetTime;
+    type Response = DeviceTime;
+}
+
+/// Reply of [`GetDeviceTime`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct DeviceTime {
+    /// Unix timestamp.
+    #[serde(rename = "UTCTime", deserialize_with = "flex::int")]
+    pub utc_time: i64,
+    /// `YYYY-MM-DD HH:MM:SS` in the device timezone.
+    #[serde(rename = "LocalTime", deserialize_with = "flex::string")]
+    pub local_time: String,
+}
+
+/// Reads the weather the device fetched for its location.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetWeatherInfo;
+
+impl PixooRequest for GetWeatherInfo {
+    const COMMAND: PixooCommand = PixooCommand::ManageGetWeather;
+    type Response = WeatherInfo;
+}
+
+/// Reply of [`GetWeatherInfo`], in the configured temperature unit.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct WeatherInfo {
+    #[serde(deserialize_with = "flex::string")]
+    pub weather: String,
+    #[serde(deserialize_with = "flex::float")]
+    pub cur_temp: f64,
+    #[serde(deserialize_with = "flex::float")]
+    pub min_temp: f64,
+    #[serde(deserialize_with = "flex::float")]
+    pub max_temp: f64,
+    #[serde(deserialize_with = "flex::int")]
+    pub pressure: i64,
+    #[serde(deserialize_with = "flex::int")]
+    pub humidity: i64,
+    #[serde(deserialize_with = "flex::float")]
+    pub wind_speed: f64,
+}
+
+/// Sets the location used for weather. Coordinates are sent as strings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetLocation {
+    pub longitude: String,
+    pub latitude: String,
+}
+
+impl PixooRequest for SetLocation {
+    const COMMAND: PixooCommand = PixooCommand::ManageSetLocation;
+    type Response = Ack;
+}
+
+/// Sets the timezone as a POSIX-style `GMT±N` value.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetTimeZone {
+    pub time_zone_value: String,
+}
+
+impl SetTimeZone {
+    /// Builds the value for `offset` hours east of UTC. The device expects
+    /// the opposite sign from what humans call the offset, so `+3` is sent as
+    /// `GMT-3`.
+    pub fn from_utc_offset(offset: i8) -> Self {
+        let time_zone_value = match offset {
+            0 => "GMT+0".to_string(),
+            value if value > 0 => format!("GMT-{value}"),
+            value => format!("GMT+{}", value.unsigned_abs()),
+        };
+        Self { time_zone_value }
+    }
+}
+
+impl PixooRequest for SetTimeZone {
+    const COMMAND: PixooCommand = PixooCommand::ManageSetTimezone;
+    type Response = Ack;
+}
+
+/// Sets the device clock to a Unix timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetUtc {
+    pub utc: i64,
+}
+
+impl PixooRequest for SetUtc {
+    const COMMAND: PixooCommand = PixooCommand::ManageSetUtc;
+    type Response = Ack;
+}
+
+/// Switches between 12-hour (`false`) and 24-hour (`true`) clocks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetTime24Flag {
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub mode: bool,
+}
+
+impl PixooRequest for SetTime24Flag {
+    const COMMAND: PixooCommand = PixooCommand::ManageSetTimeMode;
+    type Response = Ack;
+}
+
+/// Switches between Celsius (`false`) and Fahrenheit (`true`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetTemperatureMode {
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub mode: bool,
+}
+
+impl PixooRequest for SetTemperatureMode {
+    const COMMAND: PixooCommand = PixooCommand::ManageSetTemperatureUnit;
+    type Response = Ack;
+}
+
+/// Turns the screen on or off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SwitchScreen {
+    #[serde(rename = "OnOff", serialize_with = "flex::serialize_flag")]
+    pub on: bool,
+}
+
+impl PixooRequest for SwitchScreen {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayPower;
+    type Response = Ack;
+}
+
+/// Sets brightness (0–100).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetBrightness {
+    pub brightness: u8,
+}
+
+impl PixooRequest for SetBrightness {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayBrightness;
+    type Response = Ack;
+}
+
+/// Screen rotation, sent as quarter turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum Rotation {
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    /// Maps 0, 90, 180, or 270 degrees.
+    pub fn from_degrees(degrees: i32) -> Option<Self> {
+        match degrees {
+            0 => Some(Rotation::Deg0),
+            90 => Some(Rotation::Deg90),
+            180 => Some(Rotation::Deg180),
+            270 => Some(Rotation::Deg270),
+            _ => None,
+        }
+    }
+}
+
+impl From<Rotation> for u8 {
+    fn from(rotation: Rotation) -> Self {
+        match rotation {
+            Rotation::Deg0 => 0,
+            Rotation::Deg90 => 1,
+            Rotation::Deg180 => 2,
+            Rotation::Deg270 => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetRotation {
+    pub mode: Rotation,
+}
+
+impl PixooRequest for SetRotation {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayRotation;
+    type Response = Ack;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetMirrorMode {
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub mode: bool,
+}
+
+impl PixooRequest for SetMirrorMode {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayMirror;
+    type Response = Ack;
+}
+
+/// Toggles the high-brightness (overclock) mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetHighLightMode {
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub mode: bool,
+}
+
+impl PixooRequest for SetHighLightMode {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayOverclock;
+    type Response = Ack;
+}
+
+/// Per-channel white balance (0–100 each).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SetWhiteBalance {
+    #[serde(rename = "RValue")]
+    pub red: u8,
+    #[serde(rename = "GValue")]
+    pub green: u8,
+    #[serde(rename = "BValue")]
+    pub blue: u8,
+}
+
+impl PixooRequest for SetWhiteBalance {
+    const COMMAND: PixooCommand = PixooCommand::ManageDisplayWhiteBalance;
+    type Response = Ack;
+}
+
+/// What the device shows when nothing is drawn over it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum Channel {
+    Faces,
+    Cloud,
+    Visualizer,
+    Custom,
+}
+
+impl Channel {
+    /// Maps a `SelectIndex` reply.
+    pub fn from_index(index: i64) -> Option<Self> {
+        match index {
+            0 => Some(Channel::Faces),
+            1 => Some(Channel::Cloud),
+            2 => Some(Channel::Visualizer),
+            3 => Some(Channel::Custom),
+            _ => None,
+        }
+    }
+}
+
+impl From<Channel> for u8 {
+    fn from(channel: Channel) -> Self {
+        match channel {
+            Channel::Faces => 0,
+            Channel::Cloud => 1,
+            Channel::Visualizer => 2,
+            Channel::Custom => 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SetChannel {
+    #[serde(rename = "SelectIndex")]
+    pub channel: Channel,
+}
+
+impl PixooRequest for SetChannel {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelSet;
+    type Response = Ack;
+}
+
+/// Reads the selected channel.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetChannel;
+
+impl PixooRequest for GetChannel {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelGet;
+    type Response = ChannelIndex;
+}
+
+/// Reply of [`GetChannel`]; see [`Channel::from_index`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ChannelIndex {
+    #[serde(deserialize_with = "flex::int")]
+    pub select_index: i64,
+}
+
+/// Shows the clock face with `clock_id` and switches to the faces channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetClockSelectId {
+    pub clock_id: u32,
+}
+
+impl PixooRequest for SetClockSelectId {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelClock;
+    type Response = Ack;
+}
+
+/// Reads the selected clock face.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct GetClockInfo;
+
+impl PixooRequest for GetClockInfo {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelGetClock;
+    type Response = ClockInfo;
+}
+
+/// Reply of [`GetClockInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClockInfo {
+    #[serde(deserialize_with = "flex::int")]
+    pub clock_id: i64,
+    #[serde(deserialize_with = "flex::int")]
+    pub brightness: i64,
+}
+
+/// Shows one of the three custom pages (0–2) set up in the Divoom app.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetCustomPageIndex {
+    pub custom_page_index: u8,
+}
+
+impl PixooRequest for SetCustomPageIndex {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelCustomPage;
+    type Response = Ack;
+}
+
+/// Shows the visualizer at `eq_position`, counted from 0 in the app's list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetEqPosition {
+    pub eq_position: u32,
+}
+
+impl PixooRequest for SetEqPosition {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelVisualizer;
+    type Response = Ack;
+}
+
+/// Gallery the cloud channel plays from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum CloudSource {
+    Recommended,
+    Favourites,
+    Artists,
+}
+
+impl From<CloudSource> for u8 {
+    fn from(source: CloudSource) -> Self {
+        match source {
+            CloudSource::Recommended => 0,
+            CloudSource::Favourites => 1,
+            CloudSource::Artists => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetCloudIndex {
+    pub index: CloudSource,
+}
+
+impl PixooRequest for SetCloudIndex {
+    const COMMAND: PixooCommand = PixooCommand::ManageChannelCloud;
+    type Response = Ack;
+}
@@ -0,0 +1,218 @@
+//! Typed requests and responses for every [`PixooCommand`].
+//!
+//! Each request struct serializes to the argument object the device expects
+//! (field names match the Pixoo protocol) and names its response type, so
+//! [`PixooClient::execute`](super::PixooClient::execute) can send it and
+//! decode the reply without callers handling raw JSON.
+//!
+//! ```no_run
+//! # async fn demo() -> Result<(), pixoo_bridge::pixoo::error::PixooError> {
+//! use pixoo_bridge::pixoo::protocol::{GetAllConf, SetBrightness};
+//! use pixoo_bridge::pixoo::{PixooClient, PixooClientConfig};
+//!
+//! let client = PixooClient::new("http://192.168.1.50", PixooClientConfig::default())?;
+//! client.execute(&SetBrightness { brightness: 40 }).await?;
+//! let settings = client.execute(&GetAllConf).await?;
+//! assert_eq!(settings.brightness, 40);
+//! # Ok(())
+//! # }
+//! ```
+
+mod draw;
+mod manage;
+mod system;
+mod tools;
+
+pub use draw::{
+    ClearText, GetGifId, GifId, ItemType, ListItem, ResetGifId, SendGif, SendItemList, SendText,
+    TextAlign, TextDirection,
+};
+pub use manage::{
+    Channel, ChannelIndex, ClockInfo, CloudSource, DeviceSettings, DeviceTime, GetAllConf,
+    GetChannel, GetClockInfo, GetDeviceTime, GetWeatherInfo, Rotation, SetBrightness, SetChannel,
+    SetClockSelectId, SetCloudIndex, SetCustomPageIndex, SetEqPosition, SetHighLightMode,
+    SetLocation, SetMirrorMode, SetRotation, SetTemperatureMode, SetTime24Flag, SetTimeZone,
+    SetUtc, SetWhiteBalance, SwitchScreen, WeatherInfo,
+};
+pub use system::Reboot;
+pub use tools::{
+    PlayBuzzer, SetNoiseStatus, SetScoreboard, SetStopwatch, SetTimer, StopwatchStatus,
+};
+
+use super::PixooCommand;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A device command with typed arguments and reply.
+pub trait PixooRequest: Serialize {
+    /// Command name sent as `Command`.
+    const COMMAND: PixooCommand;
+
+    /// Reply fields next to `error_code`.
+    type Response: DeserializeOwned;
+}
+
+/// Reply of commands that only report success.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct Ack {}
+
+/// Serde helpers for the device's loose typing: numbers may arrive as
+/// strings, and flags are `0`/`1`.
+mod flex {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde_json::Value;
+
+    fn text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::String(text) => Ok(text),
+            Value::Number(number) => Ok(number.to_string()),
+            other => Err(D::Error::custom(format!(
+                "expected string or number, got {other}"
+            ))),
+        }
+    }
+
+    pub fn string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        text(deserializer)
+    }
+
+    pub fn int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        let value = text(deserializer)?;
+        value
+            .trim()
+            .parse()
+            .map_err(|err| D::Error::custom(format!("'{value}' is not an integer: {err}")))
+    }
+
+    pub fn float<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        let value = text(deserializer)?;
+        value
+            .trim()
+            .parse()
+            .map_err(|err| D::Error::custom(format!("'{value}' is not a number: {err}")))
+    }
+
+    /// `"1"` or `1` is set; anything else is clear.
+    pub fn flag<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+        Ok(text(deserializer)?.trim() == "1")
+    }
+
+    #[allow(clippy::trivially_copy_pass_by_ref)] // signature required by serde
+    pub fn serialize_flag<S: Serializer>(value: &bool, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(u8::from(*value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn args<R: PixooRequest>(request: &R) -> Value {
+        serde_json::to_value(request).unwrap()
+    }
+
+    #[test]
+    fn serializes_protocol_field_names() {
+        assert_eq!(args(&SwitchScreen { on: false }), json!({ "OnOff": 0 }));
+        assert_eq!(
+            args(&SetRotation {
+                mode: Rotation::Deg270
+            }),
+            json!({ "Mode": 3 })
+        );
+        assert_eq!(
+            args(&SetTimeZone::from_utc_offset(3)),
+            json!({ "TimeZoneValue": "GMT-3" })
+        );
+        assert_eq!(args(&SetTimer::stop()), json!({ "Status": 0 }));
+        assert_eq!(
+            args(&SetStopwatch {
+                status: StopwatchStatus::Reset
+            }),
+            json!({ "Status": 2 })
+        );
+        assert_eq!(args(&GetAllConf), json!(null));
+        assert_eq!(
+            args(&PlayBuzzer {
+                active_time_in_cycle: 100,
+                off_time_in_cycle: 50,
+                play_total_time: 300
+            }),
+            json!({ "ActiveTimeInCycle": 100, "OffTimeInCycle": 50, "PlayTotalTime": 300 })
+        );
+        assert_eq!(
+            args(&SetChannel {
+                channel: Channel::Visualizer
+            }),
+            json!({ "SelectIndex": 2 })
+        );
+        assert_eq!(
+            args(&SetCloudIndex {
+                index: CloudSource::Artists
+            }),
+            json!({ "Index": 2 })
+        );
+        assert_eq!(
+            args(&SetCustomPageIndex {
+                custom_page_index: 1
+            }),
+            json!({ "CustomPageIndex": 1 })
+        );
+        assert_eq!(
+            args(&SendItemList {
+                items: vec![ListItem {
+                    text_id: 3,
+                    item_type: ItemType::HourMinute,
+                    x: 0,
+                    y: 8,
+                    dir: TextDirection::Left,
+                    font: 2,
+                    text_width: 64,
+                    text_height: 16,
+                    text_string: None,
+                    speed: 100,
+                    color: "#FFFFFF".to_string(),
+                    align: TextAlign::Middle,
+                }]
+            }),
+            json!({ "ItemList": [{
+                "TextId": 3, "type": 5, "x": 0, "y": 8, "dir": 0, "font": 2, "TextWidth": 64,
+                "Textheight": 16, "speed": 100, "color": "#FFFFFF", "align": 2
+            }] })
+        );
+    }
+
+    #[test]
+    fn decodes_loosely_typed_replies() {
+        let settings: DeviceSettings = serde_json::from_value(json!({
+            "LightSwitch": "1",
+            "Brightness": "80",
+            "Time24Flag": 1,
+            "RotationFlag": "3",
+            "MirrorFlag": "0",
+            "TemperatureMode": "1",
+            "CurClockId": 5,
+            "Unknown": true,
+        }))
+        .unwrap();
+
+        assert!(settings.light_switch);
+        assert_eq!(settings.brightness, 80);
+        assert!(settings.time_24_flag);
+        assert_eq!(settings.rotation_flag, 3);
+        assert!(!settings.mirror_flag);
+        assert!(settings.temperature_mode);
+        assert_eq!(settings.cur_clock_id, 5);
+
+        let clock: ClockInfo =
+            serde_json::from_value(json!({ "ClockId": "12", "Brightness": 90 })).unwrap();
+        assert_eq!(clock.clock_id, 12);
+        assert_eq!(clock.brightness, 90);
+
+        let ack: Ack = serde_json::from_value(json!({ "extra": 1 })).unwrap();
+        assert_eq!(ack, Ack {});
+        assert!(serde_json::from_value::<GifId>(json!({ "PicId": "x" })).is_err());
+    }
+}
@@ -0,0 +1,12 @@
+use super::{Ack, PixooRequest};
+use crate::pixoo::PixooCommand;
+use serde::Serialize;
+
+/// Restarts the device.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Reboot;
+
+impl PixooRequest for Reboot {
+    const COMMAND: PixooCommand = PixooCommand::SystemReboot;
+    type Response = Ack;
+}
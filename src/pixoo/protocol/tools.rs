@@ -0,0 +1,108 @@
+use super::{flex, Ack, PixooRequest};
+use crate::pixoo::PixooCommand;
+use serde::Serialize;
+
+/// Starts a countdown, or stops it when `minute`/`second` are absent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetTimer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minute: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub second: Option<u32>,
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub status: bool,
+}
+
+impl SetTimer {
+    pub fn start(minute: u32, second: u32) -> Self {
+        Self {
+            minute: Some(minute),
+            second: Some(second),
+            status: true,
+        }
+    }
+
+    pub fn stop() -> Self {
+        Self {
+            minute: None,
+            second: None,
+            status: false,
+        }
+    }
+}
+
+impl PixooRequest for SetTimer {
+    const COMMAND: PixooCommand = PixooCommand::ToolsTimer;
+    type Response = Ack;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(into = "u8")]
+pub enum StopwatchStatus {
+    Stop,
+    Start,
+    Reset,
+}
+
+impl From<StopwatchStatus> for u8 {
+    fn from(status: StopwatchStatus) -> Self {
+        match status {
+            StopwatchStatus::Stop => 0,
+            StopwatchStatus::Start => 1,
+            StopwatchStatus::Reset => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetStopwatch {
+    pub status: StopwatchStatus,
+}
+
+impl PixooRequest for SetStopwatch {
+    const COMMAND: PixooCommand = PixooCommand::ToolsStopwatch;
+    type Response = Ack;
+}
+
+/// Shows the scoreboard with both scores (0–999).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetScoreboard {
+    pub blue_score: u16,
+    pub red_score: u16,
+}
+
+impl PixooRequest for SetScoreboard {
+    const COMMAND: PixooCommand = PixooCommand::ToolsScoreboard;
+    type Response = Ack;
+}
+
+/// Starts or stops the sound meter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SetNoiseStatus {
+    #[serde(serialize_with = "flex::serialize_flag")]
+    pub noise_status: bool,
+}
+
+impl PixooRequest for SetNoiseStatus {
+    const COMMAND: PixooCommand = PixooCommand::ToolsSoundMeter;
+    type Response = Ack;
+}
+
+/// Beeps for `active_time_in_cycle` ms, stays silent for
+/// `off_time_in_cycle` ms, and repeats until `play_total_time` ms passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PlayBuzzer {
+    pub active_time_in_cycle: u32,
+    pub off_time_in_cycle: u32,
+    pub play_total_time: u32,
+}
+
+impl PixooRequest for PlayBuzzer {
+    const COMMAND: PixooCommand = PixooCommand::ToolsBuzzer;
+    type Response = Ack;
+}
@@ -0,0 +1,211 @@
+//! Retry policies for device commands.
+//!
+//! Commands that can safely be repeated are retried on any transient failure.
+//! Commands the device acts on cumulatively (a reboot, reserving an animation
+//! id, uploading a frame) are only retried when the connection could not be
+//! established, because only then is it certain the device never saw them.
+
+use crate::pixoo::command::PixooCommand;
+use crate::pixoo::error::PixooError;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// Most retries the budget can bank while the device is healthy.
+const BUDGET_CAPACITY: f64 = 10.0;
+
+/// Which failures a [`RetryPolicy`] retries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryOn {
+    /// Connection failures, timeouts, and 5xx replies.
+    Transient,
+    /// Only failures to connect, where the request never reached the device.
+    ConnectFailure,
+}
+
+/// How often and how patiently a command is retried.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: usize,
+    /// Delay before the first retry; doubled for every further one.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Fraction (0–1) of each delay that is randomised, so clients that
+    /// failed together do not retry together.
+    pub jitter: f64,
+    pub retry_on: RetryOn,
+}
+
+impl RetryPolicy {
+    /// Whether `err` is worth another attempt under this policy.
+    pub fn should_retry(&self, err: &PixooError) -> bool {
+        match (self.retry_on, err) {
+            (RetryOn::Transient, PixooError::Http(_)) => true,
+            (RetryOn::Transient, PixooError::HttpStatus(status)) => *status >= 500,
+            (RetryOn::ConnectFailure, PixooError::Http(err)) => err.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// Delay before retry number `retry` (starting at 1). `random` is a
+    /// sample from `[0, 1)` that picks the point inside the jitter range.
+    pub fn delay(&self, retry: usize, random: f64) -> Duration {
+        let doublings = u32::try_from(retry.saturating_sub(1))
+            .unwrap_or(u32::MAX)
+            .min(31);
+        let delay = self
+            .base_delay
+            .saturating_mul(1 << doublings)
+            .min(self.max_delay);
+        delay.mul_f64(1.0 - self.jitter.clamp(0.0, 1.0) * random.clamp(0.0, 1.0))
+    }
+}
+
+/// Retry policies for all device commands.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryConfig {
+    pub idempotent: RetryPolicy,
+    pub non_idempotent: RetryPolicy,
+    /// Retries earned per request; a handful can be banked for bursts.
+    pub budget_ratio: f64,
+}
+
+impl RetryConfig {
+    /// Policy for `command`.
+    pub fn policy(&self, command: &PixooCommand) -> &RetryPolicy {
+        if command.is_idempotent() {
+            &self.idempotent
+        } else {
+            &self.non_idempotent
+        }
+    }
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        let idempotent = RetryPolicy {
+            max_retries: 2,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(2),
+            jitter: 0.2,
+            retry_on: RetryOn::Transient,
+        };
+        Self {
+            idempotent,
+            non_idempotent: RetryPolicy {
+                max_retries: 1,
+                retry_on: RetryOn::ConnectFailure,
+                ..idempotent
+            },
+            budget_ratio: 0.2,
+        }
+    }
+}
+
+/// Limits retries to a share of recent requests so an unhealthy device is
+/// not flooded with retries from every caller at once. Every request earns
+/// `ratio` of a retry, up to a small reserve; every retry spends one.
+#[derive(Debug, Clone)]
+pub(crate) struct RetryBudget {
+    ratio: f64,
+    tokens: Arc<Mutex<f64>>,
+}
+
+impl RetryBudget {
+    pub(crate) fn new(ratio: f64) -> Self {
+        Self {
+            ratio,
+            tokens: Arc::new(Mutex::new(BUDGET_CAPACITY)),
+        }
+    }
+
+    /// Records a first attempt.
+    pub(crate) fn deposit(&self) {
+        let mut tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        *tokens = (*tokens + self.ratio).min(BUDGET_CAPACITY);
+    }
+
+    /// Spends one retry; `false` when the budget is exhausted.
+    pub(crate) fn withdraw(&self) -> bool {
+        let mut tokens = self.tokens.lock().unwrap_or_else(PoisonError::into_inner);
+        if *tokens < 1.0 {
+            return false;
+        }
+        *tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: f64) -> RetryPolicy {
+        RetryPolicy {
+            jitter,
+            ..RetryConfig::default().idempotent
+        }
+    }
+
+    #[test]
+    fn delays_double_up_to_the_cap() {
+        let policy = policy(0.0);
+
+        assert_eq!(policy.delay(1, 0.5), Duration::from_millis(200));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(400));
+        assert_eq!(policy.delay(4, 0.5), Duration::from_millis(1600));
+        assert_eq!(policy.delay(5, 0.5), Duration::from_secs(2));
+        assert_eq!(policy.delay(500, 0.5), Duration::from_secs(2));
+    }
+
+    #[test]
+    fn jitter_shortens_delays_within_its_fraction() {
+        let policy = policy(0.25);
+
+        assert_eq!(policy.delay(2, 0.0), Duration::from_millis(400));
+        assert_eq!(policy.delay(2, 0.5), Duration::from_millis(350));
+        assert!(policy.delay(2, 0.999_999) > Duration::from_millis(300));
+    }
+
+    #[test]
+    fn picks_policies_by_command() {
+        let config = RetryConfig::default();
+
+        assert_eq!(
+            config
+                .policy(&PixooCommand::ManageDisplayBrightness)
+                .retry_on,
+            RetryOn::Transient
+        );
+        assert_eq!(
+            config.policy(&PixooCommand::SystemReboot).retry_on,
+            RetryOn::ConnectFailure
+        );
+        assert_eq!(
+            config.policy(&PixooCommand::DrawSendGif).retry_on,
+            RetryOn::ConnectFailure
+        );
+    }
+
+    #[test]
+    fn non_idempotent_policy_ignores_server_errors() {
+        let config = RetryConfig::default();
+        let err = PixooError::HttpStatus(500);
+
+        assert!(config.idempotent.should_retry(&err));
+        assert!(!config.non_idempotent.should_retry(&err));
+    }
+
+    #[test]
+    fn budget_refills_from_requests() {
+        let budget = RetryBudget::new(0.5);
+        for _ in 0..10 {
+            assert!(budget.withdraw());
+        }
+        assert!(!budget.withdraw());
+
+        budget.deposit();
+        assert!(!budget.withdraw());
+        budget.deposit();
+        assert!(budget.withdraw());
+    }
+}
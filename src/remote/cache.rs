@@ -0,0 +1,707 @@
+//! HTTP cache for remote image downloads.
+//!
+//! Entries are keyed by URL and honour `Cache-Control` (`no-store`, `no-cache`,
+//! `max-age`), `ETag`, and `Last-Modified`. Fresh entries are served without
+//! touching the network; stale entries with validators are revalidated through
+//! a conditional request. Decoded frames are attached to an entry so repeated
+//! draws of the same content skip decoding as well.
+//!
+//! When a cache directory is configured, response bodies and their metadata are
+//! also persisted on disk so the cache survives restarts. The directory is held
+//! to the same capacity as the memory cache: evicted entries lose their files
+//! and leftovers beyond capacity are pruned on startup. Decoded frames only
+//! live in memory.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex as AsyncMutex;
+use tracing::{debug, warn};
+
+use super::RemoteAsset;
+use crate::pixels::DecodedFrame;
+
+/// Parsed subset of a `Cache-Control` response header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheControl {
+    pub no_store: bool,
+    pub no_cache: bool,
+    pub max_age: Option<u64>,
+}
+
+impl CacheControl {
+    pub fn parse(header: Option<&str>) -> Self {
+        let mut parsed = Self::default();
+        let Some(header) = header else {
+            return parsed;
+        };
+
+        for directive in header.split(',') {
+            let directive = directive.trim();
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive, None),
+            };
+            match name.to_ascii_lowercase().as_str() {
+                "no-store" => parsed.no_store = true,
+                "no-cache" => parsed.no_cache = true,
+                "max-age" => parsed.max_age = value.and_then(|v| v.parse().ok()),
+                _ => {}
+            }
+        }
+        parsed
+    }
+
+    fn fresh_until(self, now: SystemTime) -> Option<SystemTime> {
+        if self.no_cache {
+            return None;
+        }
+        self.max_age
+            .and_then(|secs| now.checked_add(Duration::from_secs(secs)))
+    }
+}
+
+/// Validators sent along with a conditional request.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Result of looking up a URL in the cache.
+pub enum Lookup {
+    /// The entry is fresh and can be used without contacting the server.
+    Fresh(RemoteAsset),
+    /// The entry exists but must be revalidated with these validators.
+    Stale(Validators),
+    /// Nothing usable is cached.
+    Miss,
+}
+
+/// A response received from the remote server, ready to be cached.
+pub struct StoredResponse {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+    pub validators: Validators,
+    pub cache_control: CacheControl,
+}
+
+struct CacheEntry {
+    id: u64,
+    bytes: Vec<u8>,
+    content_type: Option<String>,
+    validators: Validators,
+    fresh_until: Option<SystemTime>,
+    last_used: SystemTime,
+    frames: Option<Arc<Vec<DecodedFrame>>>,
+}
+
+impl CacheEntry {
+    fn asset(&self) -> RemoteAsset {
+        RemoteAsset {
+            bytes: self.bytes.clone(),
+            content_type: self.content_type.clone(),
+            frames: self.frames.clone(),
+            cache_id: Some(self.id),
+        }
+    }
+}
+
+/// On-disk metadata stored next to each cached body.
+#[derive(Serialize, Deserialize)]
+struct DiskMetadata {
+    url: String,
+    content_type: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fresh_until: Option<u64>,
+}
+
+pub struct RemoteCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+    capacity: usize,
+    dir: Option<PathBuf>,
+    /// Serialises disk writes so a body and its metadata always match.
+    disk_lock: AsyncMutex<()>,
+    next_id: AtomicU64,
+}
+
+impl RemoteCache {
+    pub fn new(capacity: usize, dir: Option<PathBuf>) -> Self {
+        if let Some(dir) = &dir {
+            if let Err(err) = prune_disk(dir, capacity) {
+                warn!(dir = %dir.display(), error = %err, "failed to prune remote cache directory");
+            }
+        }
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            capacity,
+            dir,
+            disk_lock: AsyncMutex::new(()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub async fn lookup(&self, url: &str) -> Lookup {
+        if !self.is_enabled() {
+            return Lookup::Miss;
+        }
+        if let Some(lookup) = self.lookup_memory(url) {
+            return lookup;
+        }
+        if let Some(entry) = self.load_from_disk(url).await {
+            let evicted = self.insert(url, entry);
+            self.remove_from_disk(&evicted).await;
+            if let Some(lookup) = self.lookup_memory(url) {
+                return lookup;
+            }
+        }
+        Lookup::Miss
+    }
+
+    fn lookup_memory(&self, url: &str) -> Option<Lookup> {
+        let now = SystemTime::now();
+        let mut entries = self.entries.lock().unwrap();
+        let entry = entries.get_mut(url)?;
+        entry.last_used = now;
+
+        if entry.fresh_until.is_some_and(|until| until > now) {
+            debug!(url = %url, "serving remote asset from cache");
+            return Some(Lookup::Fresh(entry.asset()));
+        }
+        if entry.validators.is_empty() {
+            return Some(Lookup::Miss);
+        }
+        Some(Lookup::Stale(entry.validators.clone()))
+    }
+
+    /// Marks a stale entry as revalidated after a `304 Not Modified`.
+    pub async fn revalidated(&self, url: &str, cache_control: CacheControl) -> Option<RemoteAsset> {
+        let now = SystemTime::now();
+        let asset = {
+            let mut entries = self.entries.lock().unwrap();
+            let entry = entries.get_mut(url)?;
+            entry.fresh_until = cache_control.fresh_until(now);
+            entry.last_used = now;
+            entry.asset()
+        };
+        debug!(url = %url, "remote asset revalidated");
+        self.persist(url).await;
+        Some(asset)
+    }
+
+    /// Stores a full `200` response and returns the asset to hand out.
+    pub async fn store(&self, url: &str, response: StoredResponse) -> RemoteAsset {
+        if !self.is_enabled() || response.cache_control.no_store {
+            self.remove(url).await;
+            return RemoteAsset {
+                bytes: response.bytes,
+                content_type: response.content_type,
+                frames: None,
+                cache_id: None,
+            };
+        }
+
+        let now = SystemTime::now();
+        let (asset, evicted) = {
+            let mut entries = self.entries.lock().unwrap();
+            let fresh_until = response.cache_control.fresh_until(now);
+            match entries.get_mut(url) {
+                // Same body as before: keep the id so decoded frames stay valid.
+                Some(entry) if entry.bytes == response.bytes => {
+                    entry.content_type = response.content_type;
+                    entry.validators = response.validators;
+                    entry.fresh_until = fresh_until;
+                    entry.last_used = now;
+                    (entry.asset(), Vec::new())
+                }
+                _ => {
+                    let entry = CacheEntry {
+                        id: self.next_id.fetch_add(1, Ordering::Relaxed),
+                        bytes: response.bytes,
+                        content_type: response.content_type,
+                        validators: response.validators,
+                        fresh_until,
+                        last_used: now,
+                        frames: None,
+                    };
+                    let asset = entry.asset();
+                    let evicted = Self::insert_locked(&mut entries, self.capacity, url, entry);
+                    (asset, evicted)
+                }
+            }
+        };
+        self.remove_from_disk(&evicted).await;
+        self.persist(url).await;
+        asset
+    }
+
+    /// Attaches decoded frames to the entry the asset was served from.
+    ///
+    /// Ignored when the entry has been replaced in the meantime so frames can
+    /// never be paired with different content.
+    pub fn remember_frames(&self, url: &str, asset: &RemoteAsset, frames: Arc<Vec<DecodedFrame>>) {
+        let Some(id) = asset.cache_id else {
+            return;
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if let Some(entry) = entries.get_mut(url).filter(|entry| entry.id == id) {
+            entry.frames = Some(frames);
+        }
+    }
+
+    fn insert(&self, url: &str, entry: CacheEntry) -> Vec<String> {
+        let mut entries = self.entries.lock().unwrap();
+        Self::insert_locked(&mut entries, self.capacity, url, entry)
+    }
+
+    /// Inserts `entry` and returns the URLs evicted to stay within `capacity`.
+    fn insert_locked(
+        entries: &mut HashMap<String, CacheEntry>,
+        capacity: usize,
+        url: &str,
+        entry: CacheEntry,
+    ) -> Vec<String> {
+        entries.insert(url.to_string(), entry);
+        let mut evicted = Vec::new();
+        while entries.len() > capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(key) => {
+                    entries.remove(&key);
+                    evicted.push(key);
+                }
+                None => break,
+            }
+        }
+        evicted
+    }
+
+    async fn remove(&self, url: &str) {
+        self.entries.lock().unwrap().remove(url);
+        self.remove_from_disk(&[url.to_string()]).await;
+    }
+
+    async fn remove_from_disk(&self, urls: &[String]) {
+        if self.dir.is_none() || urls.is_empty() {
+            return;
+        }
+        let _guard = self.disk_lock.lock().await;
+        for url in urls {
+            // Skip URLs stored again since they were evicted.
+            if self.entries.lock().unwrap().contains_key(url) {
+                continue;
+            }
+            if let Some((meta_path, body_path)) = self.disk_paths(url) {
+                let _ = tokio::fs::remove_file(meta_path).await;
+                let _ = tokio::fs::remove_file(body_path).await;
+            }
+        }
+    }
+
+    fn disk_paths(&self, url: &str) -> Option<(PathBuf, PathBuf)> {
+        let dir = self.dir.as_ref()?;
+        let mut hasher = DefaultHasher::new();
+        url.hash(&mut hasher);
+        let name = format!("{:016x}", hasher.finish());
+        Some((
+            dir.join(format!("{name}.json")),
+            dir.join(format!("{name}.bin")),
+        ))
+    }
+
+    async fn persist(&self, url: &str) {
+        let Some((meta_path, body_path)) = self.disk_paths(url) else {
+            return;
+        };
+        // Snapshot under the disk lock so the last write carries the latest entry.
+        let _guard = self.disk_lock.lock().await;
+        let snapshot = {
+            let entries = self.entries.lock().unwrap();
+            entries.get(url).map(|entry| {
+                let metadata = DiskMetadata {
+                    url: url.to_string(),
+                    content_type: entry.content_type.clone(),
+                    etag: entry.validators.etag.clone(),
+                    last_modified: entry.validators.last_modified.clone(),
+                    fresh_until: entry.fresh_until.and_then(unix_secs),
+                };
+                (metadata, entry.bytes.clone())
+            })
+        };
+        let Some((metadata, bytes)) = snapshot else {
+            return;
+        };
+
+        if let Err(err) = write_disk_entry(&meta_path, &body_path, &metadata, &bytes).await {
+            warn!(url = %url, error = %err, "failed to persist remote cache entry");
+        }
+    }
+
+    async fn load_from_disk(&self, url: &str) -> Option<CacheEntry> {
+        let (meta_path, body_path) = self.disk_paths(url)?;
+        let metadata = tokio::fs::read(&meta_path).await.ok()?;
+        let metadata: DiskMetadata = serde_json::from_slice(&metadata).ok()?;
+        // Hash collisions are possible in theory; the stored URL settles it.
+        if metadata.url != url {
+            return None;
+        }
+        let bytes = tokio::fs::read(&body_path).await.ok()?;
+        let now = SystemTime::now();
+
+        Some(CacheEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            bytes,
+            content_type: metadata.content_type,
+            validators: Validators {
+                etag: metadata.etag,
+                last_modified: metadata.last_modified,
+            },
+            fresh_until: metadata
+                .fresh_until
+                .map(|secs| UNIX_EPOCH + Duration::from_secs(secs)),
+            last_used: now,
+            frames: None,
+        })
+    }
+}
+
+async fn write_disk_entry(
+    meta_path: &Path,
+    body_path: &Path,
+    metadata: &DiskMetadata,
+    bytes: &[u8],
+) -> io::Result<()> {
+    if let Some(parent) = meta_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let metadata = serde_json::to_vec(metadata).map_err(io::Error::other)?;
+    // Metadata goes last: it is what makes an entry loadable.
+    write_atomically(body_path, bytes).await?;
+    write_atomically(meta_path, &metadata).await
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it into
+/// place, so readers never observe a partially written file.
+async fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+    let tmp_path = path.with_file_name(tmp_name);
+    if let Err(err) = tokio::fs::write(&tmp_path, contents).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(err);
+    }
+    if let Err(err) = tokio::fs::rename(&tmp_path, path).await {
+        let _ = tokio::fs::remove_file(&tmp_path).await;
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Removes leftover temporary files and bodies without metadata, then keeps
+/// only the `capacity` most recently written entries in `dir`.
+///
+/// Only files named like cache entries are touched.
+fn prune_disk(dir: &Path, capacity: usize) -> io::Result<()> {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+
+    let mut metadata_files = Vec::new();
+    let mut body_files = Vec::new();
+    for dir_entry in read_dir {
+        let dir_entry = dir_entry?;
+        let file_name = dir_entry.file_name();
+        let Some(file_name) = file_name.to_str() else {
+            continue;
+        };
+        let Some((key, rest)) = file_name.split_once('.') else {
+            continue;
+        };
+        if key.len() != 16 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            continue;
+        }
+        let path = dir_entry.path();
+        if rest.rsplit('.').next() == Some("tmp") {
+            let _ = std::fs::remove_file(&path);
+        } else if rest == "json" {
+            let modified = dir_entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(UNIX_EPOCH);
+            metadata_files.push((modified, path));
+        } else if rest == "bin" {
+            body_files.push(path);
+        }
+    }
+
+    metadata_files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    for (_, path) in metadata_files.drain(capacity.min(metadata_files.len())..) {
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("bin"));
+    }
+    for path in body_files {
+        if !path.with_extension("json").exists() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    Ok(())
+}
+
+fn unix_secs(time: SystemTime) -> Option<u64> {
+    time.duration_since(UNIX_EPOCH).ok().map(|d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(bytes: &[u8], etag: Option<&str>, cache_control: &str) -> StoredResponse {
+        StoredResponse {
+            bytes: bytes.to_vec(),
+            content_type: Some("image/png".to_string()),
+            validators: Validators {
+                etag: etag.map(str::to_string),
+                last_modified: None,
+            },
+            cache_control: CacheControl::parse(Some(cache_control)),
+        }
+    }
+
+    #[test]
+    fn parses_cache_control_directives() {
+        let parsed = CacheControl::parse(Some("public, max-age=60, no-cache"));
+        assert_eq!(
+            parsed,
+            CacheControl {
+                no_store: false,
+                no_cache: true,
+                max_age: Some(60),
+            }
+        );
+        assert!(CacheControl::parse(Some("No-Store")).no_store);
+        assert_eq!(CacheControl::parse(Some("max-age=abc")).max_age, None);
+        assert_eq!(CacheControl::parse(None), CacheControl::default());
+    }
+
+    #[tokio::test]
+    async fn fresh_entries_are_served_without_revalidation() {
+        let cache = RemoteCache::new(4, None);
+        cache
+            .store("http://a/x.png", response(b"abc", None, "max-age=60"))
+            .await;
+
+        match cache.lookup("http://a/x.png").await {
+            Lookup::Fresh(asset) => assert_eq!(asset.bytes, b"abc"),
+            _ => panic!("expected fresh entry"),
+        }
+    }
+
+    #[tokio::test]
+    async fn stale_entries_with_validators_require_revalidation() {
+        let cache = RemoteCache::new(4, None);
+        cache
+            .store(
+                "http://a/x.png",
+                response(b"abc", Some("\"v1\""), "no-cache"),
+            )
+            .await;
+
+        match cache.lookup("http://a/x.png").await {
+            Lookup::Stale(validators) => assert_eq!(validators.etag.as_deref(), Some("\"v1\"")),
+            _ => panic!("expected stale entry"),
+        }
+    }
+
+    #[tokio::test]
+    async fn no_store_responses_are_not_cached() {
+        let cache = RemoteCache::new(4, None);
+        let asset = cache
+            .store(
+                "http://a/x.png",
+                response(b"abc", Some("\"v1\""), "no-store"),
+            )
+            .await;
+
+        assert!(asset.cache_id.is_none());
+        assert!(matches!(cache.lookup("http://a/x.png").await, Lookup::Miss));
+    }
+
+    #[tokio::test]
+    async fn frames_survive_identical_refetch_but_not_changed_content() {
+        let cache = RemoteCache::new(4, None);
+        let url = "http://a/x.png";
+        let asset = cache.store(url, response(b"abc", None, "")).await;
+        cache.remember_frames(url, &asset, Arc::new(Vec::new()));
+
+        let same = cache.store(url, response(b"abc", None, "")).await;
+        assert!(same.frames.is_some());
+
+        let changed = cache.store(url, response(b"xyz", None, "")).await;
+        assert!(changed.frames.is_none());
+        // Frames decoded from the old asset must not attach to the new entry.
+        cache.remember_frames(url, &asset, Arc::new(Vec::new()));
+        let again = cache.store(url, response(b"xyz", None, "")).await;
+        assert!(again.frames.is_none());
+    }
+
+    #[tokio::test]
+    async fn evicts_least_recently_used_entry() {
+        let cache = RemoteCache::new(2, None);
+        cache
+            .store("http://a/1", response(b"1", None, "max-age=60"))
+            .await;
+        cache
+            .store("http://a/2", response(b"2", None, "max-age=60"))
+            .await;
+        let _ = cache.lookup("http://a/1").await;
+        cache
+            .store("http://a/3", response(b"3", None, "max-age=60"))
+            .await;
+
+        assert!(matches!(cache.lookup("http://a/1").await, Lookup::Fresh(_)));
+        assert!(matches!(cache.lookup("http://a/2").await, Lookup::Miss));
+        assert!(matches!(cache.lookup("http://a/3").await, Lookup::Fresh(_)));
+    }
+
+    fn temp_cache_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("pixoo-cache-{}", uuid::Uuid::new_v4()))
+    }
+
+    fn disk_files(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[tokio::test]
+    async fn evicted_entries_are_removed_from_disk() {
+        let dir = temp_cache_dir();
+        let cache = RemoteCache::new(2, Some(dir.clone()));
+        for i in 0..5 {
+            cache
+                .store(
+                    &format!("http://a/{i}"),
+                    response(b"abc", None, "max-age=60"),
+                )
+                .await;
+        }
+
+        assert_eq!(disk_files(&dir).len(), 4);
+        let reloaded = RemoteCache::new(2, Some(dir.clone()));
+        assert!(matches!(
+            reloaded.lookup("http://a/3").await,
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(
+            reloaded.lookup("http://a/4").await,
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(reloaded.lookup("http://a/0").await, Lookup::Miss));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn startup_prunes_the_directory_to_capacity() {
+        let dir = temp_cache_dir();
+        let cache = RemoteCache::new(4, Some(dir.clone()));
+        for i in 0..4 {
+            cache
+                .store(
+                    &format!("http://a/{i}"),
+                    response(b"abc", None, "max-age=60"),
+                )
+                .await;
+            // Distinct modification times decide which entries survive.
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        std::fs::write(dir.join("0123456789abcdef.bin.1.tmp"), b"partial").unwrap();
+        std::fs::write(dir.join("fedcba9876543210.bin"), b"orphan").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"keep").unwrap();
+
+        let pruned = RemoteCache::new(2, Some(dir.clone()));
+
+        assert_eq!(disk_files(&dir).len(), 5);
+        assert!(dir.join("notes.txt").exists());
+        assert!(matches!(
+            pruned.lookup("http://a/3").await,
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(
+            pruned.lookup("http://a/2").await,
+            Lookup::Fresh(_)
+        ));
+        assert!(matches!(pruned.lookup("http://a/0").await, Lookup::Miss));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn concurrent_stores_leave_matching_body_and_metadata() {
+        let dir = temp_cache_dir();
+        let cache = Arc::new(RemoteCache::new(4, Some(dir.clone())));
+        let url = "http://a/x.png";
+        let stores = (0..8).map(|i| {
+            let cache = cache.clone();
+            tokio::spawn(async move {
+                let body = format!("body-{i}");
+                let etag = format!("\"{i}\"");
+                cache
+                    .store(url, response(body.as_bytes(), Some(&etag), "no-cache"))
+                    .await;
+            })
+        });
+        for store in stores.collect::<Vec<_>>() {
+            store.await.unwrap();
+        }
+
+        assert_eq!(disk_files(&dir).len(), 2);
+        let reloaded = RemoteCache::new(4, Some(dir.clone()));
+        let entry = reloaded.load_from_disk(url).await.expect("entry on disk");
+        let etag = entry.validators.etag.expect("etag");
+        assert_eq!(
+            String::from_utf8(entry.bytes).unwrap(),
+            format!("body-{}", etag.trim_matches('"'))
+        );
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn disk_entries_are_reloaded_by_a_new_cache() {
+        let dir = temp_cache_dir();
+        let url = "http://a/x.png";
+        RemoteCache::new(4, Some(dir.clone()))
+            .store(url, response(b"abc", Some("\"v1\""), "no-cache"))
+            .await;
+
+        let reloaded = RemoteCache::new(4, Some(dir.clone()));
+        match reloaded.lookup(url).await {
+            Lookup::Stale(validators) => assert_eq!(validators.etag.as_deref(), Some("\"v1\"")),
+            _ => panic!("expected entry loaded from disk"),
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
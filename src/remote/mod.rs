@@ -0,0 +1,512 @@
+mod cache;
+mod policy;
+
+pub use policy::{HostPattern, RemotePolicy};
+
+use crate::metrics::metrics;
+use crate::pixels::DecodedFrame;
+use cache::{CacheControl, Lookup, RemoteCache, StoredResponse, Validators};
+use policy::{GuardedResolver, PolicyViolation};
+use reqwest::header::{
+    HeaderMap, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{redirect, Client, StatusCode, Url};
+use std::error::Error as _;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+#[derive(Debug, Clone)]
+pub struct RemoteFetchConfig {
+    pub timeout: Duration,
+    pub max_image_size: usize,
+    /// Maximum number of cached URLs; `0` disables caching.
+    pub cache_entries: usize,
+    /// Optional directory where cached bodies are persisted.
+    pub cache_dir: Option<PathBuf>,
+    /// Outbound access rules applied to every request and redirect hop.
+    pub policy: RemotePolicy,
+}
+
+impl RemoteFetchConfig {
+    pub fn new(timeout: Duration, max_image_size: usize) -> Self {
+        Self {
+            timeout,
+            max_image_size,
+            cache_entries: 0,
+            cache_dir: None,
+            policy: RemotePolicy::default(),
+        }
+    }
+
+    /// Enables the response cache with the given capacity and optional
+    /// on-disk directory.
+    #[must_use]
+    pub fn with_cache(mut self, entries: usize, dir: Option<PathBuf>) -> Self {
+        self.cache_entries = entries;
+        self.cache_dir = dir;
+        self
+    }
+
+    #[must_use]
+    pub fn with_policy(mut self, policy: RemotePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+}
+
+#[derive(Clone)]
+pub struct RemoteAsset {
+    pub bytes: Vec<u8>,
+    pub content_type: Option<String>,
+    /// Frames previously decoded from these exact bytes, when cached.
+    pub frames: Option<Arc<Vec<DecodedFrame>>>,
+    cache_id: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum RemoteFetchError {
+    RequestFailed(reqwest::Error),
+    Status(StatusCode),
+    TooLarge {
+        limit: usize,
+        actual: usize,
+    },
+    /// The link, a redirect target, or its resolved address is not allowed.
+    Blocked(String),
+}
+
+impl fmt::Display for RemoteFetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteFetchError::RequestFailed(err) => write!(f, "{err}"),
+            RemoteFetchError::Status(status) => {
+                write!(f, "remote server responded with status {status}")
+            }
+            RemoteFetchError::TooLarge { limit, actual } => {
+                write!(f, "remote payload exceeds limit {limit} (actual {actual})")
+            }
+            RemoteFetchError::Blocked(reason) => write!(f, "remote link blocked: {reason}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FetchLimits {
+    timeout: Duration,
+    max_image_size: usize,
+}
+
+/// Where a successful fetch got its bytes from.
+enum FetchSource {
+    Cache,
+    Revalidated,
+    Network,
+}
+
+#[derive(Clone)]
+pub struct RemoteFetcher {
+    client: Client,
+    limits: Arc<RwLock<FetchLimits>>,
+    cache: Arc<RemoteCache>,
+    policy: Arc<RemotePolicy>,
+}
+
+impl RemoteFetcher {
+    pub fn new(config: RemoteFetchConfig) -> Result<Self, reqwest::Error> {
+        let policy = Arc::new(config.policy);
+        let redirect_policy = {
+            let policy = policy.clone();
+            redirect::Policy::custom(move |attempt| {
+                if attempt.previous().len() > policy.max_redirects {
+                    return attempt.error(PolicyViolation::new(format!(
+                        "too many redirects (limit {})",
+                        policy.max_redirects
+                    )));
+                }
+                match policy.check_url(attempt.url()) {
+                    Ok(()) => attempt.follow(),
+                    Err(violation) => attempt.error(violation),
+                }
+            })
+        };
+        // A proxy would resolve hosts itself and bypass `GuardedResolver`.
+        let client = Client::builder()
+            .no_proxy()
+            .redirect(redirect_policy)
+            .dns_resolver(Arc::new(GuardedResolver::new(policy.clone())))
+            .build()?;
+        Ok(Self {
+            client,
+            limits: Arc::new(RwLock::new(FetchLimits {
+                timeout: config.timeout,
+                max_image_size: config.max_image_size,
+            })),
+            cache: Arc::new(RemoteCache::new(config.cache_entries, config.cache_dir)),
+            policy,
+        })
+    }
+
+    /// Downloads `link`, serving it from the cache when the cached copy is
+    /// still fresh or the server confirms it with `304 Not Modified`.
+    pub async fn fetch(&self, link: &str) -> Result<RemoteAsset, RemoteFetchError> {
+        let start = Instant::now();
+        let result = self.fetch_uninstrumented(link).await;
+        let (outcome, bytes) = match &result {
+            Ok((_, FetchSource::Cache)) => ("cached", 0),
+            Ok((_, FetchSource::Revalidated)) => ("revalidated", 0),
+            Ok((asset, FetchSource::Network)) => ("downloaded", asset.bytes.len()),
+            Err(_) => ("error", 0),
+        };
+        metrics().record_remote_fetch(outcome, bytes, start.elapsed());
+        result.map(|(asset, _)| asset)
+    }
+
+    async fn fetch_uninstrumented(
+        &self,
+        link: &str,
+    ) -> Result<(RemoteAsset, FetchSource), RemoteFetchError> {
+        let limits = *self.limits.read().unwrap_or_else(PoisonError::into_inner);
+        let url = Url::parse(link).map_err(|err| RemoteFetchError::Blocked(err.to_string()))?;
+        self.policy
+            .check_url(&url)
+            .map_err(|violation| RemoteFetchError::Blocked(violation.to_string()))?;
+
+        let mut validators = match self.cache.lookup(link).await {
+            Lookup::Fresh(asset) => return Ok((asset, FetchSource::Cache)),
+            Lookup::Stale(validators) => Some(validators),
+            Lookup::Miss => None,
+        };
+
+        let response = loop {
+            let mut request = self.client.get(link).timeout(limits.timeout);
+            if let Some(validators) = &validators {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let response = request.send().await.map_err(request_error)?;
+            if response.status() != StatusCode::NOT_MODIFIED || validators.is_none() {
+                break response;
+            }
+            let cache_control = CacheControl::parse(header_str(response.headers(), &CACHE_CONTROL));
+            if let Some(asset) = self.cache.revalidated(link, cache_control).await {
+                return Ok((asset, FetchSource::Revalidated));
+            }
+            // The entry was evicted while the request was in flight; ask again
+            // without validators, this time for the full body.
+            debug!(link, "cached copy vanished during revalidation; refetching");
+            validators = None;
+        };
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RemoteFetchError::Status(status));
+        }
+
+        if let Some(length) = response.content_length() {
+            let length = usize::try_from(length).unwrap_or(usize::MAX);
+            if length > limits.max_image_size {
+                return Err(RemoteFetchError::TooLarge {
+                    limit: limits.max_image_size,
+                    actual: length,
+                });
+            }
+        }
+
+        let headers = response.headers();
+        let content_type = header_str(headers, &CONTENT_TYPE)
+            .map(|value| value.split(';').next().unwrap_or(value).trim().to_string());
+        let validators = Validators {
+            etag: header_str(headers, &ETAG).map(str::to_string),
+            last_modified: header_str(headers, &LAST_MODIFIED).map(str::to_string),
+        };
+        let cache_control = CacheControl::parse(header_str(headers, &CACHE_CONTROL));
+
+        let mut body: Vec<u8> = Vec::new();
+        let mut response = response;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(RemoteFetchError::RequestFailed)?
+        {
+            if body.len() + chunk.len() > limits.max_image_size {
+                return Err(RemoteFetchError::TooLarge {
+                    limit: limits.max_image_size,
+                    actual: body.len() + chunk.len(),
+                });
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        let asset = self
+            .cache
+            .store(
+                link,
+                StoredResponse {
+                    bytes: body,
+                    content_type,
+                    validators,
+                    cache_control,
+                },
+            )
+            .await;
+        Ok((asset, FetchSource::Network))
+    }
+
+    /// Changes the timeout and size limit for this fetcher and all its clones.
+    pub fn set_limits(&self, timeout: Duration, max_image_size: usize) {
+        *self.limits.write().unwrap_or_else(PoisonError::into_inner) = FetchLimits {
+            timeout,
+            max_image_size,
+        };
+    }
+
+    /// Remembers the frames decoded from `asset` so the next fetch of the same
+    /// content can skip decoding.
+    pub fn remember_frames(&self, link: &str, asset: &RemoteAsset, frames: Arc<Vec<DecodedFrame>>) {
+        self.cache.remember_frames(link, asset, frames);
+    }
+}
+
+/// Surfaces policy violations raised inside reqwest (DNS resolution or
+/// redirects) as [`RemoteFetchError::Blocked`].
+fn request_error(err: reqwest::Error) -> RemoteFetchError {
+    let mut source = err.source();
+    while let Some(cause) = source {
+        if let Some(violation) = cause.downcast_ref::<PolicyViolation>() {
+            return RemoteFetchError::Blocked(violation.to_string());
+        }
+        source = cause.source();
+    }
+    RemoteFetchError::RequestFailed(err)
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &reqwest::header::HeaderName) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use httpmock::{Method::GET, MockServer};
+
+    fn cached_fetcher() -> RemoteFetcher {
+        RemoteFetcher::new(RemoteFetchConfig::new(Duration::from_secs(5), 1024).with_cache(4, None))
+            .expect("fetcher")
+    }
+
+    #[tokio::test]
+    async fn fresh_responses_are_served_from_cache() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/a.png");
+            then.status(200)
+                .header("cache-control", "max-age=300")
+                .body("image");
+        });
+
+        let fetcher = cached_fetcher();
+        let link = server.url("/a.png");
+        fetcher.fetch(&link).await.expect("first fetch");
+        let second = fetcher.fetch(&link).await.expect("second fetch");
+
+        assert_eq!(second.bytes, b"image");
+        mock.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn stale_responses_are_revalidated_with_etag() {
+        let server = MockServer::start_async().await;
+        let revalidation = server.mock(|when, then| {
+            when.method(GET)
+                .path("/a.png")
+                .header("if-none-match", "\"v1\"");
+            then.status(304);
+        });
+        let full = server.mock(|when, then| {
+            when.method(GET).path("/a.png");
+            then.status(200)
+                .header("etag", "\"v1\"")
+                .header("cache-control", "no-cache")
+                .body("image");
+        });
+
+        let fetcher = cached_fetcher();
+        let link = server.url("/a.png");
+        fetcher.fetch(&link).await.expect("first fetch");
+        let second = fetcher.fetch(&link).await.expect("second fetch");
+
+        assert_eq!(second.bytes, b"image");
+        full.assert_calls(1);
+        revalidation.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn refetches_when_entry_is_evicted_during_revalidation() {
+        let server = MockServer::start_async().await;
+        let revalidation = server.mock(|when, then| {
+            when.method(GET)
+                .path("/a.png")
+                .header("if-none-match", "\"v1\"");
+            then.status(304).delay(Duration::from_millis(300));
+        });
+        let full = server.mock(|when, then| {
+            when.method(GET)
+                .path("/a.png")
+                .header_missing("if-none-match");
+            then.status(200)
+                .header("etag", "\"v1\"")
+                .header("cache-control", "no-cache")
+                .body("image");
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/b.png");
+            then.status(200).body("other");
+        });
+
+        // A single entry, so caching /b.png evicts /a.png.
+        let fetcher = RemoteFetcher::new(
+            RemoteFetchConfig::new(Duration::from_secs(5), 1024).with_cache(1, None),
+        )
+        .expect("fetcher");
+        let link = server.url("/a.png");
+        fetcher.fetch(&link).await.expect("first fetch");
+
+        let revalidating = fetcher.fetch(&link);
+        let evicting = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            fetcher.fetch(&server.url("/b.png")).await
+        };
+        let (second, other) = tokio::join!(revalidating, evicting);
+
+        assert_eq!(other.expect("evicting fetch").bytes, b"other");
+        assert_eq!(second.expect("second fetch").bytes, b"image");
+        revalidation.assert_calls(1);
+        full.assert_calls(2);
+    }
+
+    #[tokio::test]
+    async fn revalidates_with_last_modified() {
+        let server = MockServer::start_async().await;
+        let revalidation = server.mock(|when, then| {
+            when.method(GET)
+                .path("/a.png")
+                .header("if-modified-since", "Wed, 21 Oct 2015 07:28:00 GMT");
+            then.status(304);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/a.png");
+            then.status(200)
+                .header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+                .body("image");
+        });
+
+        let fetcher = cached_fetcher();
+        let link = server.url("/a.png");
+        fetcher.fetch(&link).await.expect("first fetch");
+        fetcher.fetch(&link).await.expect("second fetch");
+
+        revalidation.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn caching_disabled_by_default() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/a.png");
+            then.status(200)
+                .header("cache-control", "max-age=300")
+                .body("image");
+        });
+
+        let fetcher = RemoteFetcher::new(RemoteFetchConfig::new(Duration::from_secs(5), 1024))
+            .expect("fetcher");
+        let link = server.url("/a.png");
+        fetcher.fetch(&link).await.expect("first fetch");
+        fetcher.fetch(&link).await.expect("second fetch");
+
+        mock.assert_calls(2);
+    }
+
+    fn policy_fetcher(policy: RemotePolicy) -> RemoteFetcher {
+        RemoteFetcher::new(RemoteFetchConfig::new(Duration::from_secs(5), 1024).with_policy(policy))
+            .expect("fetcher")
+    }
+
+    #[tokio::test]
+    async fn blocks_private_addresses_when_configured() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(GET).path("/a.png");
+            then.status(200).body("image");
+        });
+
+        let fetcher = policy_fetcher(RemotePolicy {
+            block_private: true,
+            ..RemotePolicy::default()
+        });
+        let err = fetcher
+            .fetch(&server.url("/a.png"))
+            .await
+            .err()
+            .expect("blocked");
+        assert!(matches!(err, RemoteFetchError::Blocked(_)));
+
+        let localhost = format!("http://localhost:{}/a.png", server.port());
+        let err = fetcher.fetch(&localhost).await.err().expect("blocked");
+        assert!(matches!(err, RemoteFetchError::Blocked(_)), "{err}");
+
+        mock.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn redirects_are_revalidated_against_policy() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(GET).path("/start");
+            then.status(302)
+                .header("location", "http://denied.example/secret");
+        });
+
+        let fetcher = policy_fetcher(RemotePolicy {
+            denied_hosts: vec![HostPattern::new("denied.example")],
+            ..RemotePolicy::default()
+        });
+        let err = fetcher
+            .fetch(&server.url("/start"))
+            .await
+            .err()
+            .expect("blocked");
+        assert!(matches!(err, RemoteFetchError::Blocked(ref reason) if reason.contains("denied")));
+    }
+
+    #[tokio::test]
+    async fn enforces_redirect_limit() {
+        let server = MockServer::start_async().await;
+        let target = server.url("/loop");
+        server.mock(|when, then| {
+            when.method(GET).path("/loop");
+            then.status(302).header("location", target.as_str());
+        });
+
+        let fetcher = policy_fetcher(RemotePolicy {
+            max_redirects: 2,
+            ..RemotePolicy::default()
+        });
+        let err = fetcher
+            .fetch(&server.url("/loop"))
+            .await
+            .err()
+            .expect("too many redirects");
+        assert!(
+            matches!(err, RemoteFetchError::Blocked(ref reason) if reason.contains("too many redirects"))
+        );
+    }
+}
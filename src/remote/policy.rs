@@ -0,0 +1,376 @@
+//! Outbound access rules for remote fetches (SSRF protection).
+//!
+//! Every URL the fetcher touches — the requested link and each redirect hop —
+//! is checked against the host deny/allow lists. Hostnames are additionally
+//! checked after DNS resolution by [`GuardedResolver`], so a public name that
+//! resolves to a loopback, private, or link-local address is refused as well.
+//! Hosts on the allowlist are trusted explicitly and may resolve to private
+//! addresses (e.g. a webcam on the LAN).
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::Url;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+
+/// A host rule: either an exact hostname or a `*.suffix` wildcard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostPattern(String);
+
+impl HostPattern {
+    pub fn new(pattern: &str) -> Self {
+        Self(pattern.trim().trim_end_matches('.').to_ascii_lowercase())
+    }
+
+    fn matches(&self, host: &str) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        match self.0.strip_prefix("*.") {
+            Some(suffix) => host.len() > suffix.len() && host.ends_with(&format!(".{suffix}")),
+            None => host == self.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RemotePolicy {
+    /// When non-empty, only these hosts may be fetched.
+    pub allowed_hosts: Vec<HostPattern>,
+    /// Hosts that are always refused, even when allowlisted.
+    pub denied_hosts: Vec<HostPattern>,
+    /// Refuse loopback, private, link-local and other non-public addresses.
+    pub block_private: bool,
+    pub max_redirects: usize,
+}
+
+impl Default for RemotePolicy {
+    /// Unrestricted policy; production configuration comes from `AppConfig`.
+    fn default() -> Self {
+        Self {
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            block_private: false,
+            max_redirects: 5,
+        }
+    }
+}
+
+/// Reason a URL or address was refused by the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyViolation(String);
+
+impl PolicyViolation {
+    pub(crate) fn new(reason: String) -> Self {
+        Self(reason)
+    }
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PolicyViolation {}
+
+impl RemotePolicy {
+    /// Checks a URL before it is requested (initial link or redirect target).
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`PolicyViolation`] describing why the URL is refused.
+    pub fn check_url(&self, url: &Url) -> Result<(), PolicyViolation> {
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(PolicyViolation(format!(
+                "scheme {} is not allowed",
+                url.scheme()
+            )));
+        }
+        let Some(host) = url.host_str() else {
+            return Err(PolicyViolation("url has no host".to_string()));
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        self.check_host(host)?;
+
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            if self.block_private && !self.is_allowlisted(host) && is_non_public(ip) {
+                return Err(PolicyViolation(format!("address {ip} is not allowed")));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_host(&self, host: &str) -> Result<(), PolicyViolation> {
+        if self
+            .denied_hosts
+            .iter()
+            .any(|pattern| pattern.matches(host))
+        {
+            return Err(PolicyViolation(format!("host {host} is denied")));
+        }
+        if !self.allowed_hosts.is_empty() && !self.is_allowlisted(host) {
+            return Err(PolicyViolation(format!("host {host} is not allowlisted")));
+        }
+        Ok(())
+    }
+
+    fn is_allowlisted(&self, host: &str) -> bool {
+        self.allowed_hosts
+            .iter()
+            .any(|pattern| pattern.matches(host))
+    }
+}
+
+/// DNS resolver that drops addresses the policy refuses.
+pub struct GuardedResolver {
+    policy: Arc<RemotePolicy>,
+}
+
+impl GuardedResolver {
+    pub fn new(policy: Arc<RemotePolicy>) -> Self {
+        Self { policy }
+    }
+}
+
+impl Resolve for GuardedResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let policy = self.policy.clone();
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let resolved: Vec<SocketAddr> =
+                tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+
+            if !policy.block_private || policy.is_allowlisted(&host) {
+                let addrs: Addrs = Box::new(resolved.into_iter());
+                return Ok(addrs);
+            }
+
+            let allowed: Vec<SocketAddr> = resolved
+                .iter()
+                .copied()
+                .filter(|addr| !is_non_public(addr.ip()))
+                .collect();
+            if allowed.is_empty() {
+                return Err(Box::new(PolicyViolation(format!(
+                    "host {host} resolves to a non-public address"
+                ))) as _);
+            }
+            let addrs: Addrs = Box::new(allowed.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Returns `true` for addresses that must not be reachable from remote fetches:
+/// loopback, private, link-local, shared (CGNAT), unspecified, broadcast,
+/// multicast, documentation, and unique-local ranges. IPv6 addresses that
+/// carry an IPv4 address are judged by that address too.
+pub fn is_non_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_non_public_v4(ip),
+        IpAddr::V6(ip) => is_non_public_v6(ip) || embedded_v4(ip).is_some_and(is_non_public_v4),
+    }
+}
+
+/// The IPv4 address inside an IPv4-mapped (`::ffff:0:0/96`), IPv4-compatible
+/// (`::/96`), NAT64 (`64:ff9b::/96`), or 6to4 (`2002::/16`) address.
+fn embedded_v4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    if let Some(mapped) = ip.to_ipv4_mapped() {
+        return Some(mapped);
+    }
+    let segments = ip.segments();
+    let octets = ip.octets();
+    let low = Ipv4Addr::new(octets[12], octets[13], octets[14], octets[15]);
+    match segments {
+        [0, 0, 0, 0, 0, 0, ..] | [0x64, 0xff9b, 0, 0, 0, 0, ..] => Some(low),
+        [0x2002, ..] => Some(Ipv4Addr::new(octets[2], octets[3], octets[4], octets[5])),
+        _ => None,
+    }
+}
+
+fn is_non_public_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local()
+        || ip.is_unspecified()
+        || ip.is_broadcast()
+        || ip.is_multicast()
+        || ip.is_documentation()
+        // 100.64.0.0/10 shared address space (carrier-grade NAT)
+        || (a == 100 && (64..128).contains(&b))
+        // 0.0.0.0/8 "this network"
+        || a == 0
+        // 192.0.0.0/24 IETF protocol assignments
+        || (a == 192 && b == 0 && c == 0)
+        // 198.18.0.0/15 benchmarking
+        || (a == 198 && (b == 18 || b == 19))
+        // 240.0.0.0/4 reserved
+        || a >= 240
+}
+
+fn is_non_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    ip.is_loopback()
+        || ip.is_unspecified()
+        || ip.is_multicast()
+        // fc00::/7 unique local
+        || (first & 0xfe00) == 0xfc00
+        // fe80::/10 link-local
+        || (first & 0xffc0) == 0xfe80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> Url {
+        Url::parse(value).unwrap()
+    }
+
+    fn blocking_policy() -> RemotePolicy {
+        RemotePolicy {
+            block_private: true,
+            ..RemotePolicy::default()
+        }
+    }
+
+    #[test]
+    fn classifies_non_public_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.10",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+            "198.18.0.1",
+            "198.19.255.254",
+            "::ffff:198.18.0.1",
+            "192.0.0.8",
+            "::ffff:192.0.0.8",
+            "240.0.0.1",
+            "::ffff:240.0.0.1",
+        ] {
+            assert!(is_non_public(ip.parse().unwrap()), "{ip} should be blocked");
+        }
+        for ip in [
+            "93.184.216.34",
+            "1.1.1.1",
+            "198.20.0.1",
+            "192.0.1.1",
+            "2606:4700:4700::1111",
+        ] {
+            assert!(!is_non_public(ip.parse().unwrap()), "{ip} should be public");
+        }
+    }
+
+    #[test]
+    fn classifies_ipv6_forms_by_embedded_ipv4() {
+        // NAT64
+        assert!(is_non_public("64:ff9b::a9fe:a9fe".parse().unwrap()));
+        assert!(is_non_public("64:ff9b::10.0.0.1".parse().unwrap()));
+        assert!(!is_non_public("64:ff9b::1.1.1.1".parse().unwrap()));
+        // 6to4
+        assert!(is_non_public("2002:c0a8:0101::1".parse().unwrap()));
+        assert!(is_non_public("2002:7f00:1::".parse().unwrap()));
+        assert!(!is_non_public("2002:0101:0101::1".parse().unwrap()));
+        // IPv4-compatible
+        assert!(is_non_public("::169.254.169.254".parse().unwrap()));
+        assert!(is_non_public("::192.168.0.1".parse().unwrap()));
+        assert!(!is_non_public("::1.1.1.1".parse().unwrap()));
+    }
+
+    #[test]
+    fn blocks_ipv6_literals_embedding_private_ipv4() {
+        let policy = blocking_policy();
+        for link in [
+            "http://[64:ff9b::a9fe:a9fe]/latest/meta-data",
+            "http://[2002:a9fe:a9fe::1]/",
+            "http://[::127.0.0.1]/",
+        ] {
+            assert!(policy.check_url(&url(link)).is_err(), "{link}");
+        }
+    }
+
+    #[test]
+    fn blocks_private_ip_literals() {
+        let policy = blocking_policy();
+        assert!(policy
+            .check_url(&url("http://169.254.169.254/latest/meta-data"))
+            .is_err());
+        assert!(policy.check_url(&url("http://[::1]:8080/")).is_err());
+        assert!(policy
+            .check_url(&url("https://93.184.216.34/a.png"))
+            .is_ok());
+    }
+
+    #[test]
+    fn private_literals_pass_when_blocking_disabled() {
+        let policy = RemotePolicy::default();
+        assert!(policy.check_url(&url("http://127.0.0.1/a.png")).is_ok());
+    }
+
+    #[test]
+    fn allowlist_restricts_hosts_and_trusts_them() {
+        let policy = RemotePolicy {
+            allowed_hosts: vec![
+                HostPattern::new("*.example.com"),
+                HostPattern::new("192.168.1.50"),
+            ],
+            ..blocking_policy()
+        };
+        assert!(policy
+            .check_url(&url("https://cdn.example.com/a.png"))
+            .is_ok());
+        assert!(policy.check_url(&url("https://example.com/a.png")).is_err());
+        assert!(policy.check_url(&url("https://other.org/a.png")).is_err());
+        assert!(policy
+            .check_url(&url("http://192.168.1.50/snap.jpg"))
+            .is_ok());
+    }
+
+    #[test]
+    fn deny_list_wins_over_allowlist() {
+        let policy = RemotePolicy {
+            allowed_hosts: vec![HostPattern::new("*.example.com")],
+            denied_hosts: vec![HostPattern::new("admin.example.com")],
+            ..RemotePolicy::default()
+        };
+        let err = policy
+            .check_url(&url("https://admin.example.com/"))
+            .expect_err("denied");
+        assert!(err.to_string().contains("denied"));
+    }
+
+    #[test]
+    fn rejects_non_http_schemes() {
+        assert!(RemotePolicy::default()
+            .check_url(&url("file:///etc/passwd"))
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn resolver_refuses_names_resolving_to_loopback() {
+        let resolver = GuardedResolver::new(Arc::new(blocking_policy()));
+        let name: Name = "localhost".parse().unwrap();
+        let result = resolver.resolve(name).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn resolver_allows_allowlisted_private_names() {
+        let policy = RemotePolicy {
+            allowed_hosts: vec![HostPattern::new("localhost")],
+            ..blocking_policy()
+        };
+        let resolver = GuardedResolver::new(Arc::new(policy));
+        let name: Name = "localhost".parse().unwrap();
+        let addrs: Vec<SocketAddr> = resolver.resolve(name).await.expect("resolved").collect();
+        assert!(!addrs.is_empty());
+    }
+}
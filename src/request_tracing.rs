@@ -4,12 +4,16 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use std::{fmt, str::FromStr};
+use std::{fmt, future::Future, str::FromStr};
 use uuid::Uuid;
 
 /// HTTP header name for request correlation.
 const HEADER_NAME: &str = "X-Request-Id";
 
+tokio::task_local! {
+    static CURRENT: RequestId;
+}
+
 /// A UUID-based identifier for correlating logs and traces to a single request.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RequestId(String);
@@ -51,6 +55,19 @@ impl RequestId {
             .and_then(|value| value.parse::<RequestId>().ok())
     }
 
+    /// Returns the ID of the request being handled by the current task, if any.
+    ///
+    /// Only set inside [`propagate`]; background tasks have no request ID.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Clone::clone).ok()
+    }
+
+    /// Runs `future` with this ID as the current request ID, so background
+    /// work started by a request stays correlated with it.
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT.scope(self, future).await
+    }
+
     /// Records this request ID on the current tracing span.
     pub fn record(&self) {
         tracing::Span::current().record("request_id", tracing::field::display(self));
@@ -78,7 +95,8 @@ impl FromStr for RequestId {
 /// 1. Extracts an existing `X-Request-Id` header from the request, or generates a new UUID
 /// 2. Stores the request ID in request extensions (accessible via `Extension<RequestId>`)
 /// 3. Records the request ID on the current tracing span
-/// 4. Adds the `X-Request-Id` header to the response
+/// 4. Makes the request ID available to the handler via [`RequestId::current`]
+/// 5. Adds the `X-Request-Id` header to the response
 ///
 /// # Panics
 ///
@@ -93,7 +111,7 @@ pub async fn propagate(mut req: Request<Body>, next: Next) -> Response {
     req.extensions_mut().insert(request_id.clone());
     request_id.record();
 
-    let mut response = next.run(req).await;
+    let mut response = CURRENT.scope(request_id.clone(), next.run(req)).await;
     let header_name: HeaderName = HEADER_NAME.parse().expect("valid header name");
     response
         .headers_mut()
@@ -128,6 +146,34 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[tokio::test]
+    async fn current_is_scoped_to_the_request() {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|| async { RequestId::current().expect("request id").to_string() }),
+            )
+            .layer(from_fn(propagate));
+        let id = RequestId::new();
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/")
+                    .header(HEADER_NAME, id.header_value())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        assert_eq!(body, id.as_str());
+        assert_eq!(RequestId::current(), None);
+    }
+
     #[tokio::test]
     async fn propagate_inserts_header() {
         let app = Router::new()
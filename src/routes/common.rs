@@ -1,5 +1,5 @@
-use crate::pixoo::client::PixooResponse;
-use crate::pixoo::error::{PixooHttpErrorKind, PixooHttpErrorResponse};
+use crate::pixoo::error::{PixooError, PixooHttpErrorKind, PixooHttpErrorResponse};
+use crate::pixoo::protocol::PixooRequest;
 use crate::pixoo::{map_pixoo_error, PixooCommand};
 use crate::state::AppState;
 use axum::body::Body;
@@ -11,7 +11,7 @@ use serde::de::DeserializeOwned;
 use serde_json::{json, Map, Value};
 use std::str::FromStr;
 use tracing::error;
-use validator::{Validate, ValidationError, ValidationErrors};
+use validator::{Validate, ValidationError, ValidationErrors, ValidationErrorsKind};
 
 /// A JSON extractor that deserializes and validates the request body,
 /// returning consistent validation error responses on failure.
@@ -90,45 +90,39 @@ pub fn action_validation_error(action: &str, allowed: &[&str]) -> Response {
     }))
 }
 
-/// Sends a Pixoo command and returns the device response.
+/// Sends a Pixoo request and returns the decoded device response.
 ///
 /// Used by GET-style handlers that need to read the response body.
 /// On failure, returns an error `Response` ready to send to the client.
-pub async fn dispatch_pixoo_query(
+pub async fn dispatch_pixoo_query<R: PixooRequest>(
     state: &AppState,
-    command: PixooCommand,
-) -> Result<PixooResponse, Response> {
-    let client = &state.pixoo_client;
-    match client.send_command(&command, Map::new()).await {
-        Ok(response) => Ok(response),
-        Err(err) => {
-            let (status, body) = map_pixoo_error(&err, &format!("Pixoo {command} command"));
-            error!(command = %command, error = ?err, status = %status, "Pixoo command failed");
-            Err((status, body).into_response())
-        }
-    }
+    request: &R,
+) -> Result<R::Response, Response> {
+    state
+        .pixoo_client
+        .execute(request)
+        .await
+        .map_err(|err| pixoo_error_response(&R::COMMAND, &err))
 }
 
-/// Sends a Pixoo command and discards the response.
+/// Sends a Pixoo request and discards the response.
 ///
 /// Used by POST-style handlers that only need success/failure.
 /// Returns `200 OK` on success or the appropriate error response.
-pub async fn dispatch_pixoo_command(
-    state: &AppState,
-    command: PixooCommand,
-    args: Map<String, Value>,
-) -> Response {
-    let client = &state.pixoo_client;
-    match client.send_command(&command, args).await {
+pub async fn dispatch_pixoo_command<R: PixooRequest>(state: &AppState, request: &R) -> Response {
+    match state.pixoo_client.execute(request).await {
         Ok(_) => StatusCode::OK.into_response(),
-        Err(err) => {
-            let (status, body) = map_pixoo_error(&err, &format!("Pixoo {command} command"));
-            error!(command = %command, error = ?err, status = %status, "Pixoo command failed");
-            (status, body).into_response()
-        }
+        Err(err) => pixoo_error_response(&R::COMMAND, &err),
     }
 }
 
+/// Maps a failed device call to its error response and logs it.
+pub fn pixoo_error_response(command: &PixooCommand, err: &PixooError) -> Response {
+    let (status, body) = map_pixoo_error(err, &format!("Pixoo {command} command"));
+    error!(command = %command, error = ?err, status = %status, "Pixoo command failed");
+    (status, body).into_response()
+}
+
 pub fn service_unavailable() -> Response {
     PixooHttpErrorResponse::new(
         StatusCode::SERVICE_UNAVAILABLE,
@@ -157,16 +151,41 @@ fn validation_error_message(error: &ValidationError) -> String {
 
 fn validation_errors_response(errors: &ValidationErrors) -> Response {
     let mut details = Map::new();
+    collect_validation_details("", errors, &mut details);
+    validation_response(Value::Object(details))
+}
 
-    for (field, field_errors) in errors.field_errors() {
-        let messages: Vec<Value> = field_errors
-            .iter()
-            .map(|error| Value::String(validation_error_message(error)))
-            .collect();
-        details.insert(field.to_string(), Value::Array(messages));
+/// Flattens `errors` into `details`, keying nested structs as `field.inner`
+/// and list entries as `field[index].inner`.
+fn collect_validation_details(
+    prefix: &str,
+    errors: &ValidationErrors,
+    details: &mut Map<String, Value>,
+) {
+    for (field, kind) in errors.errors() {
+        let key = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{prefix}.{field}")
+        };
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                let messages: Vec<Value> = field_errors
+                    .iter()
+                    .map(|error| Value::String(validation_error_message(error)))
+                    .collect();
+                details.insert(key, Value::Array(messages));
+            }
+            ValidationErrorsKind::Struct(inner) => {
+                collect_validation_details(&key, inner, details);
+            }
+            ValidationErrorsKind::List(entries) => {
+                for (index, inner) in entries {
+                    collect_validation_details(&format!("{key}[{index}]"), inner, details);
+                }
+            }
+        }
     }
-
-    validation_response(Value::Object(details))
 }
 
 #[cfg(test)]
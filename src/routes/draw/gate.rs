@@ -0,0 +1,270 @@
+//! Mutual exclusion of frame uploads.
+//!
+//! The device assembles an animation from every frame sent under one id, and
+//! a new id discards the animation being built. Two uploads running at once
+//! therefore leave the display showing whichever happened to send last, or a
+//! half-sent animation. Every upload holds the gate while it talks to the
+//! device; what a newer upload does when the gate is taken depends on the
+//! [`DrawConflictPolicy`].
+//!
+//! The gate also remembers what the last complete upload put on the display,
+//! so an identical draw can be skipped.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Mutex as AsyncMutex, Notify, OwnedMutexGuard};
+
+/// What a draw does when another one is still uploading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DrawConflictPolicy {
+    /// Stop the running upload and start right away. Of several waiting
+    /// draws only the newest is sent.
+    Cancel,
+    /// Wait until earlier draws finished, in arrival order.
+    #[default]
+    Queue,
+    /// Fail with `409 Conflict`.
+    Reject,
+}
+
+impl DrawConflictPolicy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DrawConflictPolicy::Cancel => "cancel",
+            DrawConflictPolicy::Queue => "queue",
+            DrawConflictPolicy::Reject => "reject",
+        }
+    }
+}
+
+impl FromStr for DrawConflictPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "cancel" => Ok(DrawConflictPolicy::Cancel),
+            "queue" => Ok(DrawConflictPolicy::Queue),
+            "reject" => Ok(DrawConflictPolicy::Reject),
+            other => Err(format!(
+                "unknown draw conflict policy '{other}' (expected cancel, queue, or reject)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for DrawConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Why a draw did not get to upload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawRejected {
+    /// Another draw holds the gate and the policy is [`DrawConflictPolicy::Reject`].
+    Busy,
+    /// A newer draw took over, either while this one was waiting or while it
+    /// was uploading.
+    Preempted,
+}
+
+/// Animation the last complete upload left on the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Displayed {
+    /// Hash of the encoded frames and their speeds.
+    pub hash: u64,
+    /// [`PixooClient::commands_sent`](crate::pixoo::PixooClient::commands_sent)
+    /// right after the upload; any later command may have replaced it.
+    pub commands_sent: u64,
+}
+
+#[derive(Default)]
+struct GateState {
+    /// Ticket of the newest draw that asked for the gate.
+    latest: u64,
+    /// Preemption signal of the draw holding the gate.
+    holder: Option<Arc<Notify>>,
+    displayed: Option<Displayed>,
+}
+
+/// Serialises frame uploads to the device.
+#[derive(Clone)]
+pub struct DrawGate {
+    policy: DrawConflictPolicy,
+    lock: Arc<AsyncMutex<()>>,
+    state: Arc<Mutex<GateState>>,
+}
+
+impl Default for DrawGate {
+    fn default() -> Self {
+        Self::new(DrawConflictPolicy::default())
+    }
+}
+
+impl DrawGate {
+    pub fn new(policy: DrawConflictPolicy) -> Self {
+        Self {
+            policy,
+            lock: Arc::new(AsyncMutex::new(())),
+            state: Arc::new(Mutex::new(GateState::default())),
+        }
+    }
+
+    /// Waits for the gate as the policy prescribes.
+    pub async fn acquire(&self) -> Result<DrawPermit, DrawRejected> {
+        let ticket = {
+            let mut state = self.state.lock().unwrap();
+            state.latest += 1;
+            if self.policy == DrawConflictPolicy::Cancel {
+                if let Some(holder) = &state.holder {
+                    holder.notify_one();
+                }
+            }
+            state.latest
+        };
+
+        let guard = match self.policy {
+            DrawConflictPolicy::Reject => self
+                .lock
+                .clone()
+                .try_lock_owned()
+                .map_err(|_| DrawRejected::Busy)?,
+            DrawConflictPolicy::Queue | DrawConflictPolicy::Cancel => {
+                self.lock.clone().lock_owned().await
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        if self.policy == DrawConflictPolicy::Cancel && state.latest != ticket {
+            return Err(DrawRejected::Preempted);
+        }
+        let preempted = Arc::new(Notify::new());
+        state.holder = Some(preempted.clone());
+        Ok(DrawPermit {
+            preempted,
+            state: self.state.clone(),
+            _guard: guard,
+        })
+    }
+}
+
+/// Right to upload frames; released on drop.
+pub struct DrawPermit {
+    preempted: Arc<Notify>,
+    state: Arc<Mutex<GateState>>,
+    _guard: OwnedMutexGuard<()>,
+}
+
+impl DrawPermit {
+    /// Resolves once a newer draw wants the gate under
+    /// [`DrawConflictPolicy::Cancel`]; never resolves otherwise.
+    pub async fn preempted(&self) {
+        self.preempted.notified().await;
+    }
+
+    /// What the last complete upload left on the display.
+    pub fn displayed(&self) -> Option<Displayed> {
+        self.state.lock().unwrap().displayed
+    }
+
+    pub fn set_displayed(&self, displayed: Option<Displayed>) {
+        self.state.lock().unwrap().displayed = displayed;
+    }
+}
+
+impl Drop for DrawPermit {
+    fn drop(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        if state
+            .holder
+            .as_ref()
+            .is_some_and(|holder| Arc::ptr_eq(holder, &self.preempted))
+        {
+            state.holder = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::time::timeout;
+
+    const SHORT: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn parses_policies() {
+        assert_eq!(
+            "Cancel".parse::<DrawConflictPolicy>(),
+            Ok(DrawConflictPolicy::Cancel)
+        );
+        assert_eq!(
+            " reject ".parse::<DrawConflictPolicy>(),
+            Ok(DrawConflictPolicy::Reject)
+        );
+        assert!("newest".parse::<DrawConflictPolicy>().is_err());
+    }
+
+    #[tokio::test]
+    async fn reject_fails_while_a_draw_is_running() {
+        let gate = DrawGate::new(DrawConflictPolicy::Reject);
+        let permit = gate.acquire().await.expect("free gate");
+
+        assert!(matches!(gate.acquire().await, Err(DrawRejected::Busy)));
+        drop(permit);
+        assert!(gate.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn queue_waits_for_the_running_draw() {
+        let gate = DrawGate::new(DrawConflictPolicy::Queue);
+        let permit = gate.acquire().await.expect("free gate");
+
+        assert!(timeout(SHORT, gate.acquire()).await.is_err());
+        assert!(timeout(SHORT, permit.preempted()).await.is_err());
+        drop(permit);
+        assert!(timeout(SHORT, gate.acquire()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn remembers_the_displayed_animation_across_permits() {
+        let gate = DrawGate::default();
+        let displayed = Displayed {
+            hash: 7,
+            commands_sent: 3,
+        };
+        let permit = gate.acquire().await.expect("free gate");
+        assert_eq!(permit.displayed(), None);
+        permit.set_displayed(Some(displayed));
+        drop(permit);
+
+        let permit = gate.acquire().await.expect("free gate");
+        assert_eq!(permit.displayed(), Some(displayed));
+    }
+
+    #[tokio::test]
+    async fn cancel_preempts_the_running_draw_and_skips_stale_waiters() {
+        let gate = DrawGate::new(DrawConflictPolicy::Cancel);
+        let permit = gate.acquire().await.expect("free gate");
+
+        let older = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.acquire().await.map(drop) }
+        });
+        timeout(SHORT, permit.preempted())
+            .await
+            .expect("running draw preempted");
+        tokio::time::sleep(SHORT).await;
+        let newer = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.acquire().await.map(drop) }
+        });
+        tokio::time::sleep(SHORT).await;
+        drop(permit);
+
+        assert_eq!(older.await.unwrap(), Err(DrawRejected::Preempted));
+        assert_eq!(newer.await.unwrap(), Ok(()));
+    }
+}
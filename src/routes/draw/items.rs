@@ -0,0 +1,323 @@
+//! Text and live data items sent together with `Draw/SendHttpItemList`.
+//!
+//! Unlike `/draw/text`, most items are filled in by the device itself: it
+//! keeps time, date, temperature, and weather items current on its own.
+
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
+};
+use crate::pixoo::protocol::{ItemType, ListItem, SendItemList};
+use crate::state::AppState;
+use axum::extract::State;
+use axum::response::Response;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use validator::{Validate, ValidationError};
+
+use super::text::{
+    rgb_to_hex, validate_rgb_color, RgbColor, ScrollDirection, TextAlignment, TextPosition,
+};
+use crate::routes::common::{dispatch_pixoo_command, ValidatedJson};
+
+/// Number of item slots the device offers (ids `0..=39`).
+const ITEM_SLOTS: u64 = 40;
+
+pub(super) fn items_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new().routes(routes!(draw_items))
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+struct DrawItemsRequest {
+    #[validate(length(min = 1, max = ITEM_SLOTS), nested)]
+    items: Vec<DisplayItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct DisplayItem {
+    #[validate(range(min = 0, max = 39))]
+    id: u16,
+    #[serde(flatten)]
+    #[validate(custom(function = "validate_item_content"))]
+    content: ItemContent,
+    position: TextPosition,
+    scroll_direction: ScrollDirection,
+    #[validate(range(min = 0, max = 7))]
+    font: u16,
+    #[validate(range(min = 16, max = 64))]
+    width: u16,
+    #[validate(range(min = 8, max = 64))]
+    height: u16,
+    #[validate(range(min = 0, max = 100))]
+    scroll_speed: u16,
+    #[validate(custom(function = "validate_rgb_color"))]
+    color: RgbColor,
+    text_alignment: TextAlignment,
+}
+
+impl DisplayItem {
+    fn into_list_item(self) -> ListItem {
+        let (item_type, text_string) = self.content.into_parts();
+        ListItem {
+            text_id: self.id,
+            item_type,
+            x: self.position.x,
+            y: self.position.y,
+            dir: self.scroll_direction.direction(),
+            font: self.font,
+            text_width: self.width,
+            text_height: self.height,
+            text_string,
+            speed: self.scroll_speed,
+            color: rgb_to_hex(&self.color),
+            align: self.text_alignment.align(),
+        }
+    }
+}
+
+/// What an item shows, selected by `type`.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+enum ItemContent {
+    /// Fixed text.
+    Text { text: String },
+    /// Current time.
+    Time {
+        #[serde(default)]
+        format: TimeFormat,
+    },
+    /// Current date.
+    Date {
+        #[serde(default)]
+        format: DateFormat,
+    },
+    /// Outdoor temperature in the device's temperature unit.
+    Temperature {
+        #[serde(default)]
+        reading: TemperatureReading,
+    },
+    /// Weather description, such as `Sunny`.
+    Weather,
+}
+
+impl ItemContent {
+    fn into_parts(self) -> (ItemType, Option<String>) {
+        match self {
+            Self::Text { text } => (ItemType::Text, Some(text)),
+            Self::Time { format } => (format.item_type(), None),
+            Self::Date { format } => (format.item_type(), None),
+            Self::Temperature { reading } => (reading.item_type(), None),
+            Self::Weather => (ItemType::Weather, None),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TimeFormat {
+    #[default]
+    HourMinute,
+    HourMinuteSecond,
+    Hour,
+    Minute,
+    Second,
+    /// `AM` or `PM`.
+    Meridiem,
+}
+
+impl TimeFormat {
+    fn item_type(&self) -> ItemType {
+        match self {
+            Self::HourMinute => ItemType::HourMinute,
+            Self::HourMinuteSecond => ItemType::HourMinuteSecond,
+            Self::Hour => ItemType::Hour,
+            Self::Minute => ItemType::Minute,
+            Self::Second => ItemType::Second,
+            Self::Meridiem => ItemType::Meridiem,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum DateFormat {
+    #[default]
+    MonthDayYear,
+    MonthYear,
+    Year,
+    Month,
+    Day,
+}
+
+impl DateFormat {
+    fn item_type(&self) -> ItemType {
+        match self {
+            Self::MonthDayYear => ItemType::MonthDayYear,
+            Self::MonthYear => ItemType::MonthYear,
+            Self::Year => ItemType::Year,
+            Self::Month => ItemType::Month,
+            Self::Day => ItemType::Day,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum TemperatureReading {
+    #[default]
+    Current,
+    /// Today's forecast maximum.
+    High,
+    /// Today's forecast minimum.
+    Low,
+}
+
+impl TemperatureReading {
+    fn item_type(&self) -> ItemType {
+        match self {
+            Self::Current => ItemType::Temperature,
+            Self::High => ItemType::TodayHigh,
+            Self::Low => ItemType::TodayLow,
+        }
+    }
+}
+
+fn validate_item_content(content: &ItemContent) -> Result<(), ValidationError> {
+    if let ItemContent::Text { text } = content {
+        if text.chars().count() > 512 {
+            let mut error = ValidationError::new("length");
+            error.message = Some("text must be at most 512 characters".into());
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/items",
+    tag = "draw",
+    request_body = DrawItemsRequest,
+    responses(
+        (status = 200, description = "Items sent to the display"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn draw_items(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<DrawItemsRequest>,
+) -> Response {
+    let request = SendItemList {
+        items: payload
+            .items
+            .into_iter()
+            .map(DisplayItem::into_list_item)
+            .collect(),
+    };
+    dispatch_pixoo_command(&state, &request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{build_draw_app, start_pixoo_mock};
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::common::testing::send_json_request;
+    use crate::state::AppState;
+    use axum::http::{Method, StatusCode};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn item(id: u16, content: &Value) -> Value {
+        let mut item = json!({
+            "id": id,
+            "position": { "x": 0, "y": 8 },
+            "scrollDirection": "LEFT",
+            "font": 2,
+            "width": 64,
+            "height": 16,
+            "scrollSpeed": 100,
+            "color": { "red": 255, "green": 255, "blue": 255 },
+            "textAlignment": "MIDDLE"
+        });
+        for (key, value) in content.as_object().unwrap() {
+            item[key] = value.clone();
+        }
+        item
+    }
+
+    #[tokio::test]
+    async fn items_request_sends_one_item_list() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/items",
+            Some(json!({ "items": [
+                item(0, &json!({ "type": "TEXT", "text": "Kitchen" })),
+                item(1, &json!({ "type": "TIME" })),
+                item(2, &json!({ "type": "DATE", "format": "DAY" })),
+                item(3, &json!({ "type": "TEMPERATURE", "reading": "HIGH" })),
+                item(4, &json!({ "type": "WEATHER" }))
+            ] })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let captured = requests.lock().unwrap();
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0]["Command"], "Draw/SendHttpItemList");
+        let items = captured[0]["ItemList"].as_array().unwrap();
+        let types: Vec<_> = items.iter().map(|item| item["type"].clone()).collect();
+        assert_eq!(types, [22, 5, 8, 15, 17]);
+        assert_eq!(items[0]["TextString"], "Kitchen");
+        assert!(items[1].get("TextString").is_none());
+        assert_eq!(items[0]["TextWidth"], 64);
+        assert_eq!(items[0]["Textheight"], 16);
+        assert_eq!(items[0]["color"], "#FFFFFF");
+        assert_eq!(items[0]["align"], 2);
+    }
+
+    #[tokio::test]
+    async fn items_request_with_invalid_payload() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let mut bad = item(45, &json!({ "type": "TEXT", "text": "a".repeat(513) }));
+        bad["height"] = json!(2);
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/items",
+            Some(json!({ "items": [item(0, &json!({ "type": "WEATHER" })), bad] })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "validation");
+        assert!(json_body["details"]["items[1].id"].is_array());
+        assert!(json_body["details"]["items[1].height"].is_array());
+        assert!(json_body["details"]["items[1].content"].is_array());
+
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/items",
+            Some(json!({ "items": [item(0, &json!({ "type": "CLOCK" }))] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,446 @@
+//! Background draw jobs.
+//!
+//! With `?async=true` the frame-uploading draw routes answer `202 Accepted`
+//! right away and do the work in a job. The job status tracks the current
+//! stage, how many frames were encoded and sent, which frames failed, and how
+//! the job ended; a failed job carries the same error envelope the synchronous
+//! route would have returned. Finished jobs are kept so clients can poll the
+//! outcome, and the oldest are dropped once more than [`MAX_RETAINED_JOBS`]
+//! exist.
+
+use axum::body::to_bytes;
+use axum::extract::{Path, State};
+use axum::http::header::LOCATION;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::task::AbortHandle;
+use tracing::{info, info_span, Instrument};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use uuid::Uuid;
+
+use crate::pixoo::error::{PixooHttpErrorKind, PixooHttpErrorResponse};
+use crate::request_tracing::RequestId;
+use crate::state::AppState;
+
+/// Jobs kept in memory, finished ones are dropped oldest first.
+const MAX_RETAINED_JOBS: usize = 64;
+
+pub fn jobs_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new().routes(routes!(draw_job_status, draw_job_cancel))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+/// What a running job is doing; `DONE` once every frame was sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobStage {
+    Fetching,
+    Decoding,
+    Encoding,
+    /// Waiting for an earlier draw to finish uploading.
+    Waiting,
+    Sending,
+    Done,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FrameFailure {
+    /// Zero-based frame index.
+    frame: usize,
+    message: String,
+}
+
+/// Progress and outcome of a background draw.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DrawJobStatus {
+    id: String,
+    state: JobState,
+    stage: JobStage,
+    /// Number of frames, known once the image was decoded.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frames_total: Option<usize>,
+    frames_encoded: usize,
+    frames_sent: usize,
+    frame_failures: Vec<FrameFailure>,
+    /// Error envelope of a failed job.
+    #[schema(value_type = Object)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+    /// RFC 3339 timestamp of the request.
+    created_at: String,
+    /// RFC 3339 timestamp of success, failure, or cancellation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<String>,
+}
+
+impl DrawJobStatus {
+    fn new(stage: JobStage) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            state: JobState::Running,
+            stage,
+            frames_total: None,
+            frames_encoded: 0,
+            frames_sent: 0,
+            frame_failures: Vec::new(),
+            error: None,
+            created_at: now(),
+            finished_at: None,
+        }
+    }
+
+    /// Moves a running job to `state`; finished jobs stay as they are.
+    fn finish(&mut self, state: JobState) -> bool {
+        if self.state != JobState::Running {
+            return false;
+        }
+        self.state = state;
+        self.finished_at = Some(now());
+        true
+    }
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Receives progress from the draw helpers. The default handle discards
+/// updates, which is what synchronous draws use.
+#[derive(Clone, Default)]
+pub struct JobProgress(Option<Arc<Mutex<DrawJobStatus>>>);
+
+impl JobProgress {
+    fn update(&self, apply: impl FnOnce(&mut DrawJobStatus)) {
+        if let Some(status) = &self.0 {
+            apply(&mut status.lock().unwrap());
+        }
+    }
+
+    pub fn stage(&self, stage: JobStage) {
+        self.update(|status| status.stage = stage);
+    }
+
+    /// Records the decoded frame count and starts encoding.
+    pub fn frames(&self, total: usize) {
+        self.update(|status| {
+            status.frames_total = Some(total);
+            status.stage = JobStage::Encoding;
+        });
+    }
+
+    pub fn frame_encoded(&self) {
+        self.update(|status| status.frames_encoded += 1);
+    }
+
+    pub fn frame_sent(&self) {
+        self.update(|status| status.frames_sent += 1);
+    }
+
+    pub fn frame_failed(&self, frame: usize, message: impl Into<String>) {
+        let message = message.into();
+        self.update(|status| status.frame_failures.push(FrameFailure { frame, message }));
+    }
+}
+
+struct DrawJob {
+    status: Arc<Mutex<DrawJobStatus>>,
+    task: AbortHandle,
+}
+
+impl DrawJob {
+    fn snapshot(&self) -> DrawJobStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+/// Background draw jobs, oldest first.
+#[derive(Clone, Default)]
+pub struct DrawJobs {
+    jobs: Arc<Mutex<VecDeque<DrawJob>>>,
+}
+
+impl DrawJobs {
+    /// Runs the draw built by `work` in the background, starting in `stage`,
+    /// and returns the new job's status. The job keeps the request id and
+    /// tracing span of the caller so its events and logs can be correlated
+    /// with the request.
+    pub fn spawn<F, Fut>(&self, stage: JobStage, work: F) -> DrawJobStatus
+    where
+        F: FnOnce(JobProgress) -> Fut,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        let status = Arc::new(Mutex::new(DrawJobStatus::new(stage)));
+        let draw = work(JobProgress(Some(status.clone())));
+        let request_id = RequestId::current();
+        let span = info_span!(
+            "draw_job",
+            job_id = %status.lock().unwrap().id,
+            request_id = request_id.as_ref().map(tracing::field::display),
+        );
+        let job_status = status.clone();
+        let task = tokio::spawn(
+            async move {
+                let response = match request_id {
+                    Some(request_id) => request_id.scope(draw).await,
+                    None => draw.await,
+                };
+                record_outcome(&job_status, response).await;
+            }
+            .instrument(span),
+        );
+
+        let job = DrawJob {
+            status,
+            task: task.abort_handle(),
+        };
+        let snapshot = job.snapshot();
+        let mut jobs = self.jobs.lock().unwrap();
+        jobs.push_back(job);
+        while jobs.len() > MAX_RETAINED_JOBS {
+            let Some(index) = jobs
+                .iter()
+                .position(|job| job.snapshot().state != JobState::Running)
+            else {
+                break;
+            };
+            jobs.remove(index);
+        }
+        snapshot
+    }
+
+    pub fn status(&self, id: &str) -> Option<DrawJobStatus> {
+        self.find(id, DrawJob::snapshot)
+    }
+
+    /// Stops job `id` if it is still running and returns its status.
+    pub fn cancel(&self, id: &str) -> Option<DrawJobStatus> {
+        self.find(id, |job| {
+            let mut status = job.status.lock().unwrap();
+            if status.finish(JobState::Cancelled) {
+                job.task.abort();
+                info!(job = %status.id, "draw job cancelled");
+            }
+            status.clone()
+        })
+    }
+
+    fn find<T>(&self, id: &str, apply: impl FnOnce(&DrawJob) -> T) -> Option<T> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.iter()
+            .find(|job| job.status.lock().unwrap().id == id)
+            .map(apply)
+    }
+}
+
+/// Marks the job finished according to the response the synchronous route
+/// would have sent.
+async fn record_outcome(status: &Mutex<DrawJobStatus>, response: Response) {
+    if response.status().is_success() {
+        status.lock().unwrap().finish(JobState::Succeeded);
+        return;
+    }
+    let body = to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+    let mut status = status.lock().unwrap();
+    if status.finish(JobState::Failed) {
+        status.error = serde_json::from_slice(&body).ok();
+    }
+}
+
+/// `202 Accepted` pointing at the job's status.
+pub fn accepted(job: DrawJobStatus) -> Response {
+    let location = format!("/jobs/{}", job.id);
+    (
+        StatusCode::ACCEPTED,
+        [(LOCATION, location)],
+        axum::Json(job),
+    )
+        .into_response()
+}
+
+fn job_not_found() -> Response {
+    PixooHttpErrorResponse::new(
+        StatusCode::NOT_FOUND,
+        PixooHttpErrorKind::NotFound,
+        "job not found",
+    )
+    .into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/jobs/{id}",
+    tag = "draw",
+    params(("id" = String, Path, description = "Job id returned by an asynchronous draw")),
+    responses(
+        (status = 200, description = "Progress and outcome of the draw job", body = DrawJobStatus),
+        (status = 404, description = "Unknown or expired job")
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_job_status(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    match state.draw_jobs.status(&id) {
+        Some(status) => axum::Json(status).into_response(),
+        None => job_not_found(),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/jobs/{id}",
+    tag = "draw",
+    params(("id" = String, Path, description = "Job id returned by an asynchronous draw")),
+    responses(
+        (status = 200, description = "Job cancelled (no-op when it already finished)", body = DrawJobStatus),
+        (status = 404, description = "Unknown or expired job")
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_job_cancel(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    match state.draw_jobs.cancel(&id) {
+        Some(status) => axum::Json(status).into_response(),
+        None => job_not_found(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    async fn wait_until_finished(jobs: &DrawJobs, id: &str) -> DrawJobStatus {
+        for _ in 0..100 {
+            let status = jobs.status(id).expect("job");
+            if status.state != JobState::Running {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("job did not finish");
+    }
+
+    #[tokio::test]
+    async fn records_progress_and_success() {
+        let jobs = DrawJobs::default();
+        let job = jobs.spawn(JobStage::Decoding, |progress| async move {
+            progress.frames(2);
+            progress.frame_encoded();
+            progress.frame_encoded();
+            progress.stage(JobStage::Sending);
+            progress.frame_sent();
+            progress.frame_sent();
+            progress.stage(JobStage::Done);
+            StatusCode::OK.into_response()
+        });
+        assert_eq!(job.state, JobState::Running);
+
+        let status = wait_until_finished(&jobs, &job.id).await;
+        assert_eq!(status.state, JobState::Succeeded);
+        assert_eq!(status.stage, JobStage::Done);
+        assert_eq!(status.frames_total, Some(2));
+        assert_eq!(status.frames_sent, 2);
+        assert!(status.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn jobs_keep_the_request_id_and_span() {
+        use tracing::Span;
+        use tracing_subscriber::registry::{LookupSpan, Registry};
+
+        let _subscriber = tracing::subscriber::set_default(Registry::default());
+        let jobs = DrawJobs::default();
+        let request_id = RequestId::new();
+        let seen = Arc::new(Mutex::new(None));
+        let recorder = seen.clone();
+        let job = {
+            let _request = info_span!("request").entered();
+            request_id
+                .clone()
+                .scope(async {
+                    jobs.spawn(JobStage::Sending, move |_| async move {
+                        let current = Span::current().id();
+                        let spans = tracing::dispatcher::get_default(|dispatch| {
+                            let registry = dispatch.downcast_ref::<Registry>()?;
+                            let span = registry.span(current.as_ref()?)?;
+                            Some((span.name(), span.parent()?.name()))
+                        });
+                        *recorder.lock().unwrap() = Some((RequestId::current(), spans));
+                        StatusCode::OK.into_response()
+                    })
+                })
+                .await
+        };
+
+        wait_until_finished(&jobs, &job.id).await;
+        assert_eq!(
+            seen.lock().unwrap().take(),
+            Some((Some(request_id), Some(("draw_job", "request"))))
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_error_envelope_of_failed_jobs() {
+        let jobs = DrawJobs::default();
+        let job = jobs.spawn(JobStage::Sending, |progress| async move {
+            progress.frame_failed(3, "device timed out");
+            job_not_found()
+        });
+
+        let status = wait_until_finished(&jobs, &job.id).await;
+        assert_eq!(status.state, JobState::Failed);
+        assert_eq!(status.frame_failures[0].frame, 3);
+        assert_eq!(status.error.unwrap()["error_kind"], "not-found");
+    }
+
+    #[tokio::test]
+    async fn cancels_running_jobs_only() {
+        let jobs = DrawJobs::default();
+        let job = jobs.spawn(JobStage::Sending, |_| async {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            StatusCode::OK.into_response()
+        });
+
+        let cancelled = jobs.cancel(&job.id).expect("job");
+        assert_eq!(cancelled.state, JobState::Cancelled);
+        let again = jobs.cancel(&job.id).expect("job");
+        assert_eq!(again.finished_at, cancelled.finished_at);
+        assert!(jobs.cancel("unknown").is_none());
+    }
+
+    #[tokio::test]
+    async fn drops_the_oldest_finished_jobs() {
+        let jobs = DrawJobs::default();
+        let first = jobs.spawn(JobStage::Sending, |_| async {
+            StatusCode::OK.into_response()
+        });
+        wait_until_finished(&jobs, &first.id).await;
+
+        for _ in 0..MAX_RETAINED_JOBS {
+            jobs.spawn(JobStage::Sending, |_| async {
+                tokio::time::sleep(Duration::from_secs(30)).await;
+                StatusCode::OK.into_response()
+            });
+        }
+
+        assert!(jobs.status(&first.id).is_none());
+        assert_eq!(jobs.jobs.lock().unwrap().len(), MAX_RETAINED_JOBS);
+    }
+}
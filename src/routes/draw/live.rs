@@ -0,0 +1,535 @@
+//! Live remote mode: periodically re-fetches a remote image and redraws the
+//! display only when the downloaded content changes.
+//!
+//! A single live session runs per bridge. Starting a new session replaces the
+//! running one; stopping aborts the background task. Fetch, decode, and device
+//! failures never stop the session: they are recorded on the status, logged
+//! once per failure streak, and the poll interval backs off so a broken source
+//! does not hammer the remote host or the device.
+
+use axum::body::to_bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use tracing::{debug, info, warn};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use validator::Validate;
+
+use super::{remote_frames, send_frames, validate_remote_link, JobProgress};
+use crate::events::EventKind;
+use crate::pixoo::error::ValidationErrorResponse;
+use crate::routes::common::ValidatedJson;
+use crate::state::AppState;
+
+/// Upper bound for the failure backoff, expressed as a multiple of the interval.
+const MAX_BACKOFF_MULTIPLIER: u32 = 8;
+
+pub fn live_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(draw_remote_live_status, draw_remote_live_start))
+        .routes(routes!(draw_remote_live_stop))
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct LiveRemoteRequest {
+    #[validate(custom(function = "validate_remote_link"))]
+    link: String,
+    /// Seconds between two fetches of `link`.
+    #[validate(range(min = 1, max = 86_400))]
+    interval_seconds: u64,
+}
+
+/// Current state of the live remote session.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveRemoteStatus {
+    /// Whether a live session is currently running.
+    active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval_seconds: Option<u64>,
+    /// Number of times the display was redrawn because the content changed.
+    redraws: u64,
+    /// Failed polls since the last successful one.
+    consecutive_failures: u32,
+    /// RFC 3339 timestamp of the last successful fetch.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_fetch_at: Option<String>,
+    /// RFC 3339 timestamp of the last redraw.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_redraw_at: Option<String>,
+    /// Description of the most recent failure, cleared on recovery.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_error: Option<String>,
+}
+
+struct LiveRemoteSession {
+    task: JoinHandle<()>,
+    status: Arc<Mutex<LiveRemoteStatus>>,
+}
+
+/// Holds the (at most one) running live remote session.
+#[derive(Clone, Default)]
+pub struct LiveRemote {
+    session: Arc<Mutex<Option<LiveRemoteSession>>>,
+}
+
+impl LiveRemote {
+    /// Starts tracking `session`. Returns `true` when a running session was
+    /// replaced.
+    fn replace(&self, session: LiveRemoteSession) -> bool {
+        let previous = self.session.lock().unwrap().replace(session);
+        if let Some(previous) = previous {
+            previous.task.abort();
+            return true;
+        }
+        false
+    }
+
+    /// Stops the running session. Returns `false` when nothing was running.
+    pub fn stop(&self) -> bool {
+        match self.session.lock().unwrap().take() {
+            Some(session) => {
+                session.task.abort();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn status(&self) -> LiveRemoteStatus {
+        self.session
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|session| session.status.lock().unwrap().clone())
+            .unwrap_or_default()
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/remote/live",
+    tag = "draw",
+    request_body = LiveRemoteRequest,
+    responses(
+        (status = 200, description = "Live remote session started (replaces any running session)", body = LiveRemoteStatus),
+        (status = 400, response = ValidationErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn draw_remote_live_start(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<LiveRemoteRequest>,
+) -> Response {
+    let status = Arc::new(Mutex::new(LiveRemoteStatus {
+        active: true,
+        link: Some(payload.link.clone()),
+        interval_seconds: Some(payload.interval_seconds),
+        ..LiveRemoteStatus::default()
+    }));
+    let interval = Duration::from_secs(payload.interval_seconds);
+    let task = tokio::spawn(run_live_remote(
+        state.clone(),
+        payload.link.clone(),
+        interval,
+        status.clone(),
+    ));
+    let snapshot = status.lock().unwrap().clone();
+    if state
+        .live_remote
+        .replace(LiveRemoteSession { task, status })
+    {
+        state.events.publish(EventKind::LiveRemoteStopped);
+    }
+    state.events.publish(EventKind::LiveRemoteStarted {
+        link: payload.link,
+        interval_seconds: payload.interval_seconds,
+    });
+
+    (StatusCode::OK, axum::Json(snapshot)).into_response()
+}
+
+#[utoipa::path(
+    get,
+    path = "/draw/remote/live",
+    tag = "draw",
+    responses(
+        (status = 200, description = "Status of the live remote session", body = LiveRemoteStatus)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_remote_live_status(State(state): State<Arc<AppState>>) -> Response {
+    axum::Json(state.live_remote.status()).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/remote/live/stop",
+    tag = "draw",
+    responses(
+        (status = 200, description = "Live remote session stopped (no-op when none is running)")
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_remote_live_stop(State(state): State<Arc<AppState>>) -> Response {
+    if state.live_remote.stop() {
+        info!("live remote session stopped");
+        state.events.publish(EventKind::LiveRemoteStopped);
+    }
+    StatusCode::OK.into_response()
+}
+
+async fn run_live_remote(
+    state: Arc<AppState>,
+    link: String,
+    interval: Duration,
+    status: Arc<Mutex<LiveRemoteStatus>>,
+) {
+    let mut last_hash = None;
+    info!(link = %link, interval = ?interval, "live remote session started");
+
+    loop {
+        let outcome = poll_once(&state, &link, &mut last_hash).await;
+        let failures = record_outcome(&status, &link, outcome);
+        sleep(backoff_delay(interval, failures)).await;
+    }
+}
+
+fn record_outcome(
+    status: &Mutex<LiveRemoteStatus>,
+    link: &str,
+    outcome: Result<bool, String>,
+) -> u32 {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let mut status = status.lock().unwrap();
+    match outcome {
+        Ok(redrawn) => {
+            if status.consecutive_failures > 0 {
+                info!(
+                    link = %link,
+                    failures = status.consecutive_failures,
+                    "live remote session recovered"
+                );
+            }
+            status.consecutive_failures = 0;
+            status.last_error = None;
+            status.last_fetch_at = Some(now.clone());
+            if redrawn {
+                status.redraws += 1;
+                status.last_redraw_at = Some(now);
+            }
+        }
+        Err(message) => {
+            status.consecutive_failures = status.consecutive_failures.saturating_add(1);
+            // Only the first failure of a streak is worth a warning; the rest
+            // would repeat the same message every interval.
+            if status.consecutive_failures == 1 {
+                warn!(link = %link, error = %message, "live remote poll failed");
+            } else {
+                debug!(
+                    link = %link,
+                    error = %message,
+                    failures = status.consecutive_failures,
+                    "live remote poll still failing"
+                );
+            }
+            status.last_error = Some(message);
+        }
+    }
+    status.consecutive_failures
+}
+
+/// Fetches `link` once and redraws when its content differs from the last
+/// successfully drawn content. Returns whether the display was redrawn.
+async fn poll_once(
+    state: &AppState,
+    link: &str,
+    last_hash: &mut Option<u64>,
+) -> Result<bool, String> {
+    let asset = state
+        .remote_fetcher
+        .fetch(link)
+        .await
+        .map_err(|err| format!("remote fetch failed: {err}"))?;
+
+    let hash = content_hash(&asset.bytes);
+    if *last_hash == Some(hash) {
+        return Ok(false);
+    }
+
+    let frames = match remote_frames(state, link, &asset) {
+        Ok(frames) => frames,
+        Err(resp) => return Err(response_message(resp).await),
+    };
+
+    let resp = send_frames(
+        state,
+        &frames,
+        state.settings.get().animation_speed_factor,
+        false,
+        &JobProgress::default(),
+    )
+    .await;
+    if resp.status() != StatusCode::OK {
+        return Err(response_message(resp).await);
+    }
+
+    *last_hash = Some(hash);
+    Ok(true)
+}
+
+fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn backoff_delay(interval: Duration, failures: u32) -> Duration {
+    let multiplier = failures.saturating_add(1).min(MAX_BACKOFF_MULTIPLIER);
+    interval.saturating_mul(multiplier)
+}
+
+/// Extracts a readable message from an error envelope produced by the shared
+/// draw helpers.
+async fn response_message(resp: Response) -> String {
+    let status = resp.status();
+    let body = to_bytes(resp.into_body(), usize::MAX)
+        .await
+        .unwrap_or_default();
+    serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|value| {
+            let message = value.get("message")?.as_str()?.to_string();
+            match value.get("details") {
+                Some(details) => Some(format!("{message}: {details}")),
+                None => Some(message),
+            }
+        })
+        .unwrap_or_else(|| format!("request failed with status {status}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backoff_delay, live_router, poll_once, record_outcome, LiveRemoteStatus};
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::common::testing::send_json_request;
+    use crate::state::AppState;
+    use axum::http::{Method, StatusCode};
+    use axum::Router;
+    use httpmock::{Method as MockMethod, MockServer};
+    use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba, RgbaImage};
+    use serde_json::{json, Value};
+    use std::io::Cursor;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn png(color: [u8; 4]) -> Vec<u8> {
+        let img: RgbaImage = ImageBuffer::from_fn(8, 8, |_, _| Rgba(color));
+        let mut buf = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .expect("write png");
+        buf
+    }
+
+    fn device_mock(server: &MockServer) {
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Draw/GetHttpGifId");
+            then.status(200).body(r#"{"error_code":0,"PicId":7}"#);
+        });
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Draw/SendHttpGif");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+    }
+
+    fn live_state(base_url: &str) -> Arc<AppState> {
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        Arc::new(AppState::with_client(client))
+    }
+
+    fn build_live_app(state: Arc<AppState>) -> Router {
+        let (router, _api) = live_router().with_state(state).split_for_parts();
+        router
+    }
+
+    #[tokio::test]
+    async fn poll_redraws_only_when_content_changes() {
+        let device = MockServer::start_async().await;
+        device_mock(&device);
+        let remote = MockServer::start_async().await;
+        let mut image = remote.mock(|when, then| {
+            when.method(MockMethod::GET).path("/cam.png");
+            then.status(200)
+                .header("content-type", "image/png")
+                .body(png([255, 0, 0, 255]));
+        });
+
+        let state = live_state(&device.base_url());
+        let link = format!("{}/cam.png", remote.base_url());
+        let mut last_hash = None;
+
+        assert_eq!(poll_once(&state, &link, &mut last_hash).await, Ok(true));
+        assert_eq!(poll_once(&state, &link, &mut last_hash).await, Ok(false));
+
+        image.delete();
+        remote.mock(|when, then| {
+            when.method(MockMethod::GET).path("/cam.png");
+            then.status(200)
+                .header("content-type", "image/png")
+                .body(png([0, 0, 255, 255]));
+        });
+        assert_eq!(poll_once(&state, &link, &mut last_hash).await, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn poll_reports_fetch_failures_without_touching_device() {
+        let device = MockServer::start_async().await;
+        let device_calls = device.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let remote = MockServer::start_async().await;
+        remote.mock(|when, then| {
+            when.method(MockMethod::GET).path("/cam.png");
+            then.status(500);
+        });
+
+        let state = live_state(&device.base_url());
+        let link = format!("{}/cam.png", remote.base_url());
+        let err = poll_once(&state, &link, &mut None)
+            .await
+            .expect_err("expected failure");
+
+        assert!(err.contains("remote fetch failed"));
+        device_calls.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn poll_keeps_previous_hash_when_device_fails() {
+        let device = MockServer::start_async().await;
+        device.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+        let remote = MockServer::start_async().await;
+        remote.mock(|when, then| {
+            when.method(MockMethod::GET).path("/cam.png");
+            then.status(200)
+                .header("content-type", "image/png")
+                .body(png([255, 0, 0, 255]));
+        });
+
+        let state = live_state(&device.base_url());
+        let link = format!("{}/cam.png", remote.base_url());
+        let mut last_hash = None;
+        let err = poll_once(&state, &link, &mut last_hash)
+            .await
+            .expect_err("expected device failure");
+
+        assert!(err.contains("error_code"));
+        assert!(last_hash.is_none());
+    }
+
+    #[test]
+    fn record_outcome_tracks_failure_streaks() {
+        let status = Mutex::new(LiveRemoteStatus::default());
+
+        assert_eq!(record_outcome(&status, "l", Err("boom".to_string())), 1);
+        assert_eq!(record_outcome(&status, "l", Err("boom".to_string())), 2);
+        assert_eq!(status.lock().unwrap().last_error.as_deref(), Some("boom"));
+
+        assert_eq!(record_outcome(&status, "l", Ok(true)), 0);
+        let status = status.lock().unwrap();
+        assert!(status.last_error.is_none());
+        assert_eq!(status.redraws, 1);
+        assert!(status.last_redraw_at.is_some());
+    }
+
+    #[test]
+    fn backoff_grows_with_failures_and_is_capped() {
+        let interval = Duration::from_secs(10);
+        assert_eq!(backoff_delay(interval, 0), interval);
+        assert_eq!(backoff_delay(interval, 2), Duration::from_secs(30));
+        assert_eq!(backoff_delay(interval, 50), Duration::from_secs(80));
+    }
+
+    #[tokio::test]
+    async fn start_status_and_stop_round_trip() {
+        let device = MockServer::start_async().await;
+        device_mock(&device);
+        let remote = MockServer::start_async().await;
+        remote.mock(|when, then| {
+            when.method(MockMethod::GET).path("/cam.png");
+            then.status(200)
+                .header("content-type", "image/png")
+                .body(png([0, 255, 0, 255]));
+        });
+
+        let state = live_state(&device.base_url());
+        let app = build_live_app(state.clone());
+        let link = format!("{}/cam.png", remote.base_url());
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/remote/live",
+            Some(json!({ "link": link, "intervalSeconds": 60 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let started: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(started["active"], true);
+        assert_eq!(started["intervalSeconds"], 60);
+
+        let (status, body) = send_json_request(&app, Method::GET, "/draw/remote/live", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let current: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(current["link"], link);
+
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/draw/remote/live/stop", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, body) = send_json_request(&app, Method::GET, "/draw/remote/live", None).await;
+        let stopped: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(stopped["active"], false);
+        assert!(stopped.get("link").is_none());
+    }
+
+    #[tokio::test]
+    async fn start_rejects_invalid_interval() {
+        let device = MockServer::start_async().await;
+        let app = build_live_app(live_state(&device.base_url()));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/remote/live",
+            Some(json!({ "link": "http://example.com/cam.png", "intervalSeconds": 0 })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert!(json_body["details"]["interval_seconds"].is_array());
+    }
+}
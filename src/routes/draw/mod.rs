@@ -1,30 +1,53 @@
+use crate::events::EventKind;
+use crate::metrics::metrics;
 use crate::pixels::{
     decode_upload, encode_pic_data, uniform_pixel_buffer, DecodedFrame, ImageError, PIXOO_FRAME_DIM,
 };
-use crate::pixoo::fields::{request as req, response as resp};
-use crate::pixoo::PixooCommand;
-use crate::remote::RemoteFetchError;
+use crate::pixoo::protocol::{GetGifId, PixooRequest, SendGif};
+use crate::remote::{RemoteAsset, RemoteFetchError};
+use crate::request_tracing::RequestId;
 use crate::state::AppState;
-use axum::extract::{Multipart, State};
+use axum::extract::{Multipart, Query, State};
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::hash_map::DefaultHasher;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use tracing::error;
+use tracing::{error, info, warn};
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
-use validator::{Validate, ValidationError};
+use validator::Validate;
 
 use crate::pixoo::error::{
-    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, InternalErrorResponse,
-    PayloadTooLargeResponse, PixooHttpErrorKind, PixooHttpErrorResponse, ValidationErrorResponse,
+    ConflictResponse, DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse,
+    InternalErrorResponse, PayloadTooLargeResponse, PixooError, PixooHttpErrorKind,
+    PixooHttpErrorResponse, ValidationErrorResponse,
 };
 
+mod gate;
+mod items;
+mod jobs;
+mod live;
+mod notify;
+mod text;
+
+pub use gate::{DrawConflictPolicy, DrawGate};
+pub use jobs::DrawJobs;
+pub use live::LiveRemote;
+pub use notify::Notifications;
+pub use text::TextSlots;
+
+use gate::{Displayed, DrawRejected};
+use jobs::{DrawJobStatus, JobProgress, JobStage};
+
 use super::common::{
-    dispatch_pixoo_command, dispatch_pixoo_query, internal_server_error, service_unavailable,
-    validation_error_simple, ValidatedJson,
+    dispatch_pixoo_query, internal_server_error, pixoo_error_response, validation_error_simple,
+    ValidatedJson,
 };
 
 const SINGLE_FRAME_PIC_SPEED_MS: u32 = 9999;
@@ -34,8 +57,22 @@ pub fn draw_router() -> OpenApiRouter<Arc<AppState>> {
         .routes(routes!(draw_fill))
         .routes(routes!(draw_upload))
         .routes(routes!(draw_remote))
-        .routes(routes!(draw_text))
-        .routes(routes!(draw_text_clear))
+        .merge(text::text_router())
+        .merge(items::items_router())
+        .merge(notify::notify_router())
+        .merge(live::live_router())
+        .merge(jobs::jobs_router())
+}
+
+/// Query options of the routes that upload frames.
+#[derive(Debug, Default, Deserialize)]
+struct DrawOptions {
+    /// Run the draw as a background job and answer `202 Accepted`.
+    #[serde(default, rename = "async")]
+    run_async: bool,
+    /// Upload even when the display already shows the same animation.
+    #[serde(default)]
+    force: bool,
 }
 
 /// Multipart form for `/draw/upload`. Used for documentation only; the handler
@@ -64,73 +101,6 @@ struct DrawRemoteRequest {
     link: String,
 }
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
-#[serde(rename_all = "camelCase")]
-struct DrawTextRequest {
-    #[validate(range(min = 0, max = 20))]
-    id: u16,
-    position: TextPosition,
-    scroll_direction: ScrollDirection,
-    #[validate(range(min = 0, max = 7))]
-    font: u16,
-    #[validate(range(min = 16, max = 64))]
-    text_width: u16,
-    #[validate(length(max = 512))]
-    text: String,
-    #[validate(range(min = 0, max = 100))]
-    scroll_speed: u16,
-    #[validate(custom(function = "validate_rgb_color"))]
-    color: RgbColor,
-    text_alignment: TextAlignment,
-}
-
-#[derive(Debug, Deserialize, ToSchema)]
-struct TextPosition {
-    x: u16,
-    y: u16,
-}
-
-#[derive(Debug, Deserialize, Serialize, ToSchema)]
-struct RgbColor {
-    red: u16,
-    green: u16,
-    blue: u16,
-}
-
-#[derive(Debug, Deserialize, ToSchema)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-enum ScrollDirection {
-    Left,
-    Right,
-}
-
-impl ScrollDirection {
-    fn key(&self) -> u8 {
-        match self {
-            Self::Left => 0,
-            Self::Right => 1,
-        }
-    }
-}
-
-#[derive(Debug, Deserialize, ToSchema)]
-#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
-enum TextAlignment {
-    Left,
-    Middle,
-    Right,
-}
-
-impl TextAlignment {
-    fn key(&self) -> u8 {
-        match self {
-            Self::Left => 1,
-            Self::Middle => 2,
-            Self::Right => 3,
-        }
-    }
-}
-
 fn validate_remote_link(link: &str) -> Result<(), validator::ValidationError> {
     let url = reqwest::Url::parse(link).map_err(|_| {
         let mut error = validator::ValidationError::new("invalid_url");
@@ -154,29 +124,20 @@ fn validate_remote_link(link: &str) -> Result<(), validator::ValidationError> {
     Ok(())
 }
 
-fn validate_rgb_color(color: &RgbColor) -> Result<(), ValidationError> {
-    let mut error = ValidationError::new("invalid_color");
-    error.message = Some("color values must be between 0 and 255".into());
-
-    if color.red > 255 || color.green > 255 || color.blue > 255 {
-        return Err(error);
-    }
-
-    Ok(())
-}
-
-fn rgb_to_hex(color: &RgbColor) -> String {
-    format!("#{:02X}{:02X}{:02X}", color.red, color.green, color.blue)
-}
-
 #[utoipa::path(
     post,
     path = "/draw/fill",
     tag = "draw",
+    params(
+        ("async" = Option<bool>, Query, description = "Run as a background job and answer `202` with its status"),
+        ("force" = Option<bool>, Query, description = "Upload even when the display already shows the same animation")
+    ),
     request_body = DrawFillRequest,
     responses(
         (status = 200, description = "Display filled with the requested color"),
+        (status = 202, description = "Draw started as a background job", body = DrawJobStatus),
         (status = 400, response = ValidationErrorResponse),
+        (status = 409, response = ConflictResponse),
         (status = 500, response = InternalErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
         (status = 503, response = DeviceErrorResponse),
@@ -186,6 +147,7 @@ fn rgb_to_hex(color: &RgbColor) -> String {
 #[tracing::instrument(skip(state, payload))]
 async fn draw_fill(
     State(state): State<Arc<AppState>>,
+    Query(options): Query<DrawOptions>,
     ValidatedJson(payload): ValidatedJson<DrawFillRequest>,
 ) -> Response {
     let Ok(red) = u8::try_from(payload.red) else {
@@ -198,31 +160,37 @@ async fn draw_fill(
         return internal_server_error("invalid blue value");
     };
 
-    let buffer = uniform_pixel_buffer(red, green, blue);
-    let pic_data = match encode_pic_data(&buffer) {
-        Ok(value) => value,
-        Err(err) => {
-            error!(error = %err, "failed to encode draw payload");
-            return internal_server_error("failed to encode draw payload");
-        }
-    };
-
-    let pic_id = match get_next_pic_id(&state).await {
-        Ok(value) => value,
-        Err(resp) => return resp,
+    let frame = DecodedFrame {
+        rgb_buffer: uniform_pixel_buffer(red, green, blue),
+        delay_ms: 0,
     };
-
-    send_draw_frame(&state, pic_id, 1, 0, SINGLE_FRAME_PIC_SPEED_MS, pic_data).await
+    let force = options.force;
+    run_draw(
+        &state,
+        &options,
+        JobStage::Encoding,
+        |state, progress| async move {
+            let speed_factor = state.settings.get().animation_speed_factor;
+            send_frames(&state, &[frame], speed_factor, force, &progress).await
+        },
+    )
+    .await
 }
 
 #[utoipa::path(
     post,
     path = "/draw/upload",
     tag = "draw",
+    params(
+        ("async" = Option<bool>, Query, description = "Run as a background job and answer `202` with its status"),
+        ("force" = Option<bool>, Query, description = "Upload even when the display already shows the same animation")
+    ),
     request_body(content = inline(UploadForm), content_type = "multipart/form-data"),
     responses(
         (status = 200, description = "Image uploaded and rendered"),
+        (status = 202, description = "Draw started as a background job", body = DrawJobStatus),
         (status = 400, response = ValidationErrorResponse),
+        (status = 409, response = ConflictResponse),
         (status = 413, response = PayloadTooLargeResponse),
         (status = 500, response = InternalErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
@@ -231,7 +199,11 @@ async fn draw_fill(
     )
 )]
 #[tracing::instrument(skip(state, multipart))]
-async fn draw_upload(State(state): State<Arc<AppState>>, mut multipart: Multipart) -> Response {
+async fn draw_upload(
+    State(state): State<Arc<AppState>>,
+    Query(options): Query<DrawOptions>,
+    mut multipart: Multipart,
+) -> Response {
     // Extract the file field from the multipart request
     let (bytes, content_type) = match extract_file_field(&mut multipart).await {
         Ok(result) => result,
@@ -239,26 +211,48 @@ async fn draw_upload(State(state): State<Arc<AppState>>, mut multipart: Multipar
     };
 
     // Check file size against configured limit
-    if bytes.len() > state.max_image_size {
-        return payload_too_large(state.max_image_size, bytes.len());
+    let settings = state.settings.get();
+    if bytes.len() > settings.max_image_size {
+        return payload_too_large(settings.max_image_size, bytes.len());
     }
 
-    let frames = match decode_frames(&bytes, content_type.as_deref(), "file") {
-        Ok(frames) => frames,
-        Err(resp) => return resp,
-    };
-
-    send_frames(&state, frames, state.animation_speed_factor).await
+    let force = options.force;
+    run_draw(
+        &state,
+        &options,
+        JobStage::Decoding,
+        |state, progress| async move {
+            let frames = match decode_frames(&bytes, content_type.as_deref(), "file") {
+                Ok(frames) => frames,
+                Err(resp) => return resp,
+            };
+            send_frames(
+                &state,
+                &frames,
+                settings.animation_speed_factor,
+                force,
+                &progress,
+            )
+            .await
+        },
+    )
+    .await
 }
 
 #[utoipa::path(
     post,
     path = "/draw/remote",
     tag = "draw",
+    params(
+        ("async" = Option<bool>, Query, description = "Run as a background job and answer `202` with its status"),
+        ("force" = Option<bool>, Query, description = "Upload even when the display already shows the same animation")
+    ),
     request_body = DrawRemoteRequest,
     responses(
         (status = 200, description = "Remote image fetched and rendered"),
+        (status = 202, description = "Draw started as a background job", body = DrawJobStatus),
         (status = 400, response = ValidationErrorResponse),
+        (status = 409, response = ConflictResponse),
         (status = 413, response = PayloadTooLargeResponse),
         (status = 500, response = InternalErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
@@ -269,84 +263,73 @@ async fn draw_upload(State(state): State<Arc<AppState>>, mut multipart: Multipar
 #[tracing::instrument(skip(state, payload))]
 async fn draw_remote(
     State(state): State<Arc<AppState>>,
+    Query(options): Query<DrawOptions>,
     ValidatedJson(payload): ValidatedJson<DrawRemoteRequest>,
 ) -> Response {
-    let asset = match state.remote_fetcher.fetch(&payload.link).await {
+    let force = options.force;
+    run_draw(
+        &state,
+        &options,
+        JobStage::Fetching,
+        |state, progress| async move {
+            let frames = match fetch_remote_frames(&state, &payload.link, &progress).await {
+                Ok(frames) => frames,
+                Err(resp) => return resp,
+            };
+            let speed_factor = state.settings.get().animation_speed_factor;
+            send_frames(&state, &frames, speed_factor, force, &progress).await
+        },
+    )
+    .await
+}
+
+/// Runs `draw` now, or as a background job answered with `202 Accepted` when
+/// the caller asked for `async`.
+async fn run_draw<F, Fut>(
+    state: &Arc<AppState>,
+    options: &DrawOptions,
+    first_stage: JobStage,
+    draw: F,
+) -> Response
+where
+    F: FnOnce(Arc<AppState>, JobProgress) -> Fut,
+    Fut: Future<Output = Response> + Send + 'static,
+{
+    if options.run_async {
+        let job = state
+            .draw_jobs
+            .spawn(first_stage, |progress| draw(state.clone(), progress));
+        return jobs::accepted(job);
+    }
+    draw(state.clone(), JobProgress::default()).await
+}
+
+#[allow(clippy::result_large_err)]
+async fn fetch_remote_frames(
+    state: &AppState,
+    link: &str,
+    progress: &JobProgress,
+) -> Result<Arc<Vec<DecodedFrame>>, Response> {
+    let asset = match state.remote_fetcher.fetch(link).await {
         Ok(asset) => asset,
         Err(RemoteFetchError::TooLarge { limit, actual }) => {
-            return payload_too_large(limit, actual);
+            return Err(payload_too_large(limit, actual));
+        }
+        Err(RemoteFetchError::Blocked(reason)) => {
+            warn!(link = %link, reason = %reason, "remote link blocked by policy");
+            return Err(validation_error_simple(
+                "link",
+                &format!("link not allowed: {reason}"),
+            ));
         }
         Err(err) => {
             error!(error = %err, "remote fetch failed");
-            return remote_fetch_failed(&err.to_string());
+            return Err(remote_fetch_failed(&err.to_string()));
         }
     };
 
-    let frames = match decode_frames(&asset.bytes, asset.content_type.as_deref(), "link") {
-        Ok(frames) => frames,
-        Err(resp) => return resp,
-    };
-
-    send_frames(&state, frames, state.animation_speed_factor).await
-}
-
-#[utoipa::path(
-    post,
-    path = "/draw/text",
-    tag = "draw",
-    request_body = DrawTextRequest,
-    responses(
-        (status = 200, description = "Text sent to the display"),
-        (status = 400, response = ValidationErrorResponse),
-        (status = 502, response = DeviceUnreachableResponse),
-        (status = 503, response = DeviceErrorResponse),
-        (status = 504, response = DeviceTimeoutResponse)
-    )
-)]
-#[tracing::instrument(skip(state, payload))]
-async fn draw_text(
-    State(state): State<Arc<AppState>>,
-    ValidatedJson(payload): ValidatedJson<DrawTextRequest>,
-) -> Response {
-    let mut args = Map::new();
-    args.insert(req::LCD_ID.to_string(), Value::from(0));
-    args.insert(req::TEXT_ID.to_string(), Value::from(payload.id));
-    args.insert(req::X.to_string(), Value::from(payload.position.x));
-    args.insert(req::Y.to_string(), Value::from(payload.position.y));
-    args.insert(
-        req::DIR.to_string(),
-        Value::from(payload.scroll_direction.key()),
-    );
-    args.insert(req::FONT.to_string(), Value::from(payload.font));
-    args.insert(req::TEXT_WIDTH.to_string(), Value::from(payload.text_width));
-    args.insert(req::SPEED.to_string(), Value::from(payload.scroll_speed));
-    args.insert(req::TEXT_STRING.to_string(), Value::String(payload.text));
-    args.insert(
-        req::COLOR.to_string(),
-        Value::String(rgb_to_hex(&payload.color)),
-    );
-    args.insert(
-        req::ALIGN.to_string(),
-        Value::from(payload.text_alignment.key()),
-    );
-
-    dispatch_pixoo_command(&state, PixooCommand::DrawSendText, args).await
-}
-
-#[utoipa::path(
-    post,
-    path = "/draw/text/clear",
-    tag = "draw",
-    responses(
-        (status = 200, description = "Text cleared from the display"),
-        (status = 502, response = DeviceUnreachableResponse),
-        (status = 503, response = DeviceErrorResponse),
-        (status = 504, response = DeviceTimeoutResponse)
-    )
-)]
-#[tracing::instrument(skip(state))]
-async fn draw_text_clear(State(state): State<Arc<AppState>>) -> Response {
-    dispatch_pixoo_command(&state, PixooCommand::DrawClearText, Map::new()).await
+    progress.stage(JobStage::Decoding);
+    remote_frames(state, link, &asset)
 }
 
 async fn extract_file_field(
@@ -395,54 +378,156 @@ fn decode_frames(
     Ok(frames)
 }
 
-async fn get_next_pic_id(state: &AppState) -> Result<i64, Response> {
-    let response = dispatch_pixoo_query(state, PixooCommand::DrawGetGifId).await?;
-
-    let Some(value) = response.get(resp::PIC_ID) else {
-        error!(response = ?response, "missing PicId in draw response");
-        return Err(service_unavailable());
+/// Decodes an image sent inline as base64, enforcing the upload size limit.
+#[allow(clippy::result_large_err)]
+fn decode_base64_frames(
+    state: &AppState,
+    data: &str,
+    field: &str,
+) -> Result<Vec<DecodedFrame>, Response> {
+    let Ok(bytes) = STANDARD.decode(data) else {
+        return Err(validation_error_simple(
+            field,
+            "image must be base64-encoded",
+        ));
     };
+    let limit = state.settings.get().max_image_size;
+    if bytes.len() > limit {
+        return Err(payload_too_large(limit, bytes.len()));
+    }
+    decode_frames(&bytes, None, field)
+}
 
-    let parsed = match value {
-        Value::Number(number) => number
-            .as_i64()
-            .or_else(|| number.as_u64().and_then(|v| i64::try_from(v).ok()))
-            .ok_or_else(|| "PicId is not an integer".to_string()),
-        Value::String(text) => text
-            .parse::<i64>()
-            .map_err(|_| "PicId is not an integer".to_string()),
-        _ => Err("PicId is not an integer".to_string()),
-    };
+/// Returns the frames for a fetched remote asset, reusing frames cached with
+/// the asset and remembering freshly decoded ones for the next fetch.
+#[allow(clippy::result_large_err)]
+fn remote_frames(
+    state: &AppState,
+    link: &str,
+    asset: &RemoteAsset,
+) -> Result<Arc<Vec<DecodedFrame>>, Response> {
+    if let Some(frames) = &asset.frames {
+        return Ok(frames.clone());
+    }
 
-    match parsed {
-        Ok(value) => Ok(value),
-        Err(err) => {
-            error!(error = %err, response = ?response, "invalid PicId in draw response");
-            Err(service_unavailable())
-        }
+    let frames = Arc::new(decode_frames(
+        &asset.bytes,
+        asset.content_type.as_deref(),
+        "link",
+    )?);
+    state
+        .remote_fetcher
+        .remember_frames(link, asset, frames.clone());
+    Ok(frames)
+}
+
+async fn get_next_pic_id(state: &AppState) -> Result<i64, Response> {
+    dispatch_pixoo_query(state, &GetGifId)
+        .await
+        .map(|reply| reply.pic_id)
+}
+
+/// Duration of a frame on the device. Single images have no delay and are
+/// shown for [`SINGLE_FRAME_PIC_SPEED_MS`].
+fn frame_speed(frame: &DecodedFrame, speed_factor: f64) -> u32 {
+    if frame.delay_ms == 0 {
+        return SINGLE_FRAME_PIC_SPEED_MS;
     }
+    // f64::from(u32) is lossless; speed_factor > 0 is validated at config time
+    let speed = (f64::from(frame.delay_ms) * speed_factor).round().max(1.0);
+    // Saturating cast: guaranteed ≥ 1.0; values > u32::MAX saturate to u32::MAX
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let ms = speed as u32;
+    ms
 }
 
-async fn send_draw_frame(
+/// Encodes every frame, then uploads them as one animation once the draw
+/// gate lets this draw through. Unless `force` is set, the upload is skipped
+/// when the display still shows exactly these frames.
+async fn send_frames(
     state: &AppState,
-    pic_id: i64,
-    pic_num: u32,
-    pic_offset: u32,
-    pic_speed: u32,
-    pic_data: String,
+    frames: &[DecodedFrame],
+    speed_factor: f64,
+    force: bool,
+    progress: &JobProgress,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::PIC_ID.to_string(), Value::from(pic_id));
-    args.insert(req::PIC_NUM.to_string(), Value::from(pic_num));
-    args.insert(req::PIC_OFFSET.to_string(), Value::from(pic_offset));
-    args.insert(req::PIC_WIDTH.to_string(), Value::from(PIXOO_FRAME_DIM));
-    args.insert(req::PIC_SPEED.to_string(), Value::from(pic_speed));
-    args.insert(req::PIC_DATA.to_string(), Value::String(pic_data));
-
-    dispatch_pixoo_command(state, PixooCommand::DrawSendGif, args).await
+    progress.frames(frames.len());
+    let mut encoded = Vec::with_capacity(frames.len());
+    for (offset, frame) in frames.iter().enumerate() {
+        match encode_pic_data(&frame.rgb_buffer) {
+            Ok(pic_data) => encoded.push(pic_data),
+            Err(err) => {
+                error!(error = %err, frame = offset, "failed to encode frame");
+                progress.frame_failed(offset, format!("failed to encode frame: {err}"));
+                return internal_server_error("failed to encode frame");
+            }
+        }
+        progress.frame_encoded();
+    }
+    let hash = frames_hash(frames, &encoded, speed_factor);
+
+    progress.stage(JobStage::Waiting);
+    let permit = match state.draw_gate.acquire().await {
+        Ok(permit) => permit,
+        Err(rejected) => return draw_conflict(rejected),
+    };
+    let commands_sent = state.pixoo_client.commands_sent();
+    if !force
+        && permit.displayed()
+            == Some(Displayed {
+                hash,
+                commands_sent,
+            })
+    {
+        info!("display already shows these frames; skipping upload");
+        metrics().record_upload_skipped();
+        progress.stage(JobStage::Done);
+        return StatusCode::OK.into_response();
+    }
+    permit.set_displayed(None);
+
+    progress.stage(JobStage::Sending);
+    let response = tokio::select! {
+        response = upload_frames(state, frames, encoded, speed_factor, progress) => response,
+        () = permit.preempted() => {
+            info!("draw preempted by a newer draw");
+            return draw_conflict(DrawRejected::Preempted);
+        }
+    };
+    if response.status() == StatusCode::OK {
+        state
+            .notifications
+            .remember_frames(Arc::new(frames.to_vec()), speed_factor);
+    }
+    // Only remember the upload when nothing else reached the device meanwhile.
+    let frame_count = u64::try_from(frames.len()).unwrap_or(u64::MAX);
+    let sent_after = state.pixoo_client.commands_sent();
+    if response.status() == StatusCode::OK && sent_after == commands_sent + frame_count {
+        permit.set_displayed(Some(Displayed {
+            hash,
+            commands_sent: sent_after,
+        }));
+    }
+    response
+}
+
+/// Identifies what an upload puts on the display.
+fn frames_hash(frames: &[DecodedFrame], encoded: &[String], speed_factor: f64) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for (frame, pic_data) in frames.iter().zip(encoded) {
+        pic_data.hash(&mut hasher);
+        frame_speed(frame, speed_factor).hash(&mut hasher);
+    }
+    hasher.finish()
 }
 
-async fn send_frames(state: &AppState, frames: Vec<DecodedFrame>, speed_factor: f64) -> Response {
+async fn upload_frames(
+    state: &AppState,
+    frames: &[DecodedFrame],
+    encoded: Vec<String>,
+    speed_factor: f64,
+    progress: &JobProgress,
+) -> Response {
     let pic_id = match get_next_pic_id(state).await {
         Ok(value) => value,
         Err(resp) => return resp,
@@ -451,37 +536,58 @@ async fn send_frames(state: &AppState, frames: Vec<DecodedFrame>, speed_factor:
     // Frame count is capped at 60, so this conversion is safe.
     let pic_num = u32::try_from(frames.len()).unwrap();
 
-    for (offset, frame) in frames.iter().enumerate() {
-        let pic_data = match encode_pic_data(&frame.rgb_buffer) {
-            Ok(value) => value,
-            Err(err) => {
-                error!(error = %err, frame = offset, "failed to encode frame");
-                return internal_server_error("failed to encode frame");
-            }
-        };
-
-        let pic_speed = if frame.delay_ms == 0 {
-            SINGLE_FRAME_PIC_SPEED_MS
-        } else {
-            // f64::from(u32) is lossless; speed_factor > 0 is validated at config time
-            let speed = (f64::from(frame.delay_ms) * speed_factor).round().max(1.0);
-            // Saturating cast: guaranteed ≥ 1.0; values > u32::MAX saturate to u32::MAX
-            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
-            let ms = speed as u32;
-            ms
+    for (offset, (frame, pic_data)) in frames.iter().zip(encoded).enumerate() {
+        let request = SendGif {
+            pic_id,
+            pic_num,
+            // offset is max 59, so this conversion is safe
+            pic_offset: u32::try_from(offset).unwrap(),
+            pic_width: PIXOO_FRAME_DIM,
+            pic_speed: frame_speed(frame, speed_factor),
+            pic_data,
         };
-
-        // offset is max 59, so this conversion is safe
-        let pic_offset = u32::try_from(offset).unwrap();
-        let resp = send_draw_frame(state, pic_id, pic_num, pic_offset, pic_speed, pic_data).await;
-        if resp.status() != StatusCode::OK {
-            return resp;
+        if let Err(err) = state.pixoo_client.execute(&request).await {
+            progress.frame_failed(offset, err.to_string());
+            publish_upload(state, Some(&err));
+            return pixoo_error_response(&SendGif::COMMAND, &err);
         }
+        metrics().record_frame_sent();
+        progress.frame_sent();
     }
 
+    publish_upload(state, None);
+    progress.stage(JobStage::Done);
+    publish_draw_completed(state, frames.len());
     StatusCode::OK.into_response()
 }
 
+fn draw_conflict(rejected: DrawRejected) -> Response {
+    let message = match rejected {
+        DrawRejected::Busy => "another draw is still uploading",
+        DrawRejected::Preempted => "draw was preempted by a newer draw",
+    };
+    PixooHttpErrorResponse::new(StatusCode::CONFLICT, PixooHttpErrorKind::Conflict, message)
+        .into_response()
+}
+
+/// Reports a frame upload as a single `Draw/SendHttpGif` command event; the
+/// client does not publish one per frame.
+fn publish_upload(state: &AppState, error: Option<&PixooError>) {
+    state.events.publish(EventKind::Command {
+        command: SendGif::COMMAND.as_str().to_string(),
+        request_id: RequestId::current().map(|id| id.to_string()),
+        success: error.is_none(),
+        error: error.map(ToString::to_string),
+    });
+}
+
+fn publish_draw_completed(state: &AppState, frames: usize) {
+    state.events.publish(EventKind::DrawCompleted {
+        frames,
+        request_id: RequestId::current().map(|id| id.to_string()),
+    });
+}
+
 fn payload_too_large(limit: usize, actual: usize) -> Response {
     PixooHttpErrorResponse::with_details(
         StatusCode::PAYLOAD_TOO_LARGE,
@@ -504,12 +610,13 @@ fn remote_fetch_failed(message: &str) -> Response {
 #[cfg(test)]
 mod tests {
     use super::SINGLE_FRAME_PIC_SPEED_MS;
-    use super::{
-        draw_router, DrawTextRequest, RgbColor, ScrollDirection, TextAlignment, TextPosition,
-    };
+    use super::{draw_router, DrawConflictPolicy, DrawGate};
+    use crate::config::RuntimeSettings;
+    use crate::events::EventKind;
     use crate::pixels::{encode_pic_data, uniform_pixel_buffer};
+    use crate::pixoo::protocol::SetBrightness;
     use crate::pixoo::{PixooClient, PixooClientConfig};
-    use crate::remote::{RemoteFetchConfig, RemoteFetcher};
+    use crate::remote::{RemoteFetchConfig, RemoteFetcher, RemotePolicy};
     use crate::routes::common::testing::send_json_request;
     use crate::state::AppState;
     use axum::body::{to_bytes, Body};
@@ -522,7 +629,6 @@ mod tests {
     use std::sync::{Arc, Mutex};
     use tokio::net::TcpListener;
     use tower::ServiceExt;
-    use validator::Validate;
 
     #[derive(Clone)]
     struct PixooMockState {
@@ -548,7 +654,7 @@ mod tests {
         }
     }
 
-    async fn start_pixoo_mock() -> (String, Arc<Mutex<Vec<Value>>>) {
+    pub(super) async fn start_pixoo_mock() -> (String, Arc<Mutex<Vec<Value>>>) {
         let requests = Arc::new(Mutex::new(Vec::new()));
         let state = PixooMockState {
             requests: requests.clone(),
@@ -564,29 +670,11 @@ mod tests {
         (format!("http://{addr}"), requests)
     }
 
-    fn build_draw_app(state: Arc<AppState>) -> Router {
+    pub(super) fn build_draw_app(state: Arc<AppState>) -> Router {
         let (router, _api) = draw_router().with_state(state).split_for_parts();
         router
     }
 
-    fn base_text_payload() -> DrawTextRequest {
-        DrawTextRequest {
-            id: 4,
-            position: TextPosition { x: 0, y: 40 },
-            scroll_direction: ScrollDirection::Left,
-            font: 4,
-            text_width: 56,
-            text: "hello, Divoom".to_string(),
-            scroll_speed: 10,
-            color: RgbColor {
-                red: 255,
-                green: 255,
-                blue: 0,
-            },
-            text_alignment: TextAlignment::Left,
-        }
-    }
-
     #[tokio::test]
     async fn draw_fill_sends_expected_command_sequence() {
         let (base_url, requests) = start_pixoo_mock().await;
@@ -619,191 +707,98 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn draw_fill_rejects_invalid_payload() {
-        let (base_url, _requests) = start_pixoo_mock().await;
+    async fn draw_fill_skips_frames_the_display_already_shows() {
+        let (base_url, requests) = start_pixoo_mock().await;
         let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let app = build_draw_app(Arc::new(AppState::with_client(client)));
-        let (status, body) = send_json_request(
-            &app,
-            Method::POST,
-            "/draw/fill",
-            Some(json!({ "red": 999, "green": 0, "blue": 0 })),
-        )
-        .await;
+        let state = Arc::new(AppState::with_client(client));
+        let app = build_draw_app(state.clone());
+        let fill = |uri: &'static str| {
+            send_json_request(
+                &app,
+                Method::POST,
+                uri,
+                Some(json!({ "red": 5, "green": 6, "blue": 7 })),
+            )
+        };
+        let sent = || {
+            requests
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|request| request["Command"] == "Draw/SendHttpGif")
+                .count()
+        };
 
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        let json_body: Value = serde_json::from_str(&body).unwrap();
-        assert_eq!(json_body["error_status"], 400);
-        assert_eq!(json_body["error_kind"], "validation");
-        assert!(json_body["message"].is_string());
-        assert!(json_body["details"]["red"].is_array());
-    }
+        assert_eq!(fill("/draw/fill").await.0, StatusCode::OK);
+        assert_eq!(fill("/draw/fill").await.0, StatusCode::OK);
+        assert_eq!(sent(), 1);
+
+        assert_eq!(fill("/draw/fill?force=true").await.0, StatusCode::OK);
+        assert_eq!(sent(), 2);
 
-    #[test]
-    fn text_payload_with_out_of_range_fields() {
-        let mut payload = base_text_payload();
-        payload.id = 25;
-        payload.font = 9;
-        payload.text_width = 10;
-        payload.scroll_speed = 120;
-        payload.color.red = 300;
-        payload.text = "a".repeat(513);
-
-        let errors = payload.validate().expect_err("expected validation error");
-        let fields = errors.field_errors();
-        assert!(fields.contains_key("id"));
-        assert!(fields.contains_key("font"));
-        assert!(fields.contains_key("text_width"));
-        assert!(fields.contains_key("scroll_speed"));
-        assert!(fields.contains_key("color"));
-        assert!(fields.contains_key("text"));
+        state
+            .pixoo_client
+            .execute(&SetBrightness { brightness: 50 })
+            .await
+            .expect("brightness");
+        assert_eq!(fill("/draw/fill").await.0, StatusCode::OK);
+        assert_eq!(sent(), 3);
     }
 
     #[tokio::test]
-    async fn text_request_sends_pixoo_text_command() {
+    async fn draw_fill_conflicts_with_a_running_upload_under_reject_policy() {
         let (base_url, requests) = start_pixoo_mock().await;
         let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        let state = AppState {
+            draw_gate: DrawGate::new(DrawConflictPolicy::Reject),
+            ..AppState::with_client(client)
+        };
+        let permit = state.draw_gate.acquire().await.expect("free gate");
+        let app = build_draw_app(Arc::new(state));
 
         let (status, body) = send_json_request(
             &app,
             Method::POST,
-            "/draw/text",
-            Some(json!({
-                "id": 4,
-                "position": { "x": 0, "y": 40 },
-                "scrollDirection": "LEFT",
-                "font": 4,
-                "textWidth": 56,
-                "scrollSpeed": 10,
-                "text": "hello, Divoom",
-                "color": { "red": 255, "green": 255, "blue": 0 },
-                "textAlignment": "LEFT"
-            })),
+            "/draw/fill",
+            Some(json!({ "red": 1, "green": 2, "blue": 3 })),
         )
         .await;
 
-        assert_eq!(status, StatusCode::OK);
-        assert!(body.is_empty());
-
-        let captured = requests.lock().unwrap();
-        assert_eq!(captured.len(), 1);
-        assert_eq!(captured[0]["Command"], "Draw/SendHttpText");
-        assert_eq!(captured[0]["LcdId"], 0);
-        assert_eq!(captured[0]["TextId"], 4);
-        assert_eq!(captured[0]["x"], 0);
-        assert_eq!(captured[0]["y"], 40);
-        assert_eq!(captured[0]["dir"], 0);
-        assert_eq!(captured[0]["font"], 4);
-        assert_eq!(captured[0]["TextWidth"], 56);
-        assert_eq!(captured[0]["speed"], 10);
-        assert_eq!(captured[0]["TextString"], "hello, Divoom");
-        assert_eq!(captured[0]["color"], "#FFFF00");
-        assert_eq!(captured[0]["align"], 1);
-    }
-
-    #[tokio::test]
-    async fn text_request_with_invalid_payload() {
-        let (base_url, requests) = start_pixoo_mock().await;
-        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        assert_eq!(status, StatusCode::CONFLICT);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "conflict");
+        assert!(requests.lock().unwrap().is_empty());
 
-        let (status, body) = send_json_request(
+        drop(permit);
+        let (status, _) = send_json_request(
             &app,
             Method::POST,
-            "/draw/text",
-            Some(json!({
-                "id": 25,
-                "position": { "x": 0, "y": 40 },
-                "scrollDirection": "LEFT",
-                "font": 4,
-                "textWidth": 10,
-                "scrollSpeed": 10,
-                "text": "hello",
-                "color": { "red": 300, "green": 255, "blue": 0 },
-                "textAlignment": "LEFT"
-            })),
+            "/draw/fill",
+            Some(json!({ "red": 1, "green": 2, "blue": 3 })),
         )
         .await;
-
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        let json_body: Value = serde_json::from_str(&body).unwrap();
-        assert_eq!(json_body["error_kind"], "validation");
-        assert!(json_body["details"]["id"].is_array());
-        assert!(json_body["details"]["text_width"].is_array());
-        assert!(json_body["details"]["color"].is_array());
-        let captured = requests.lock().unwrap();
-        assert!(captured.is_empty());
-    }
-
-    #[tokio::test]
-    async fn text_clear_request_sends_pixoo_command() {
-        let (base_url, requests) = start_pixoo_mock().await;
-        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let app = build_draw_app(Arc::new(AppState::with_client(client)));
-
-        let (status, body) = send_json_request(&app, Method::POST, "/draw/text/clear", None).await;
-
         assert_eq!(status, StatusCode::OK);
-        assert!(body.is_empty());
-
-        let captured = requests.lock().unwrap();
-        assert_eq!(captured.len(), 1);
-        assert_eq!(captured[0]["Command"], "Draw/ClearHttpText");
-    }
-
-    #[tokio::test]
-    async fn text_clear_request_with_pixoo_error() {
-        let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":1}"#);
-        });
-
-        let client =
-            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
-        let app = build_draw_app(Arc::new(AppState::with_client(client)));
-        let (status, body) = send_json_request(&app, Method::POST, "/draw/text/clear", None).await;
-
-        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
-        let json_body: Value = serde_json::from_str(&body).unwrap();
-        assert_eq!(json_body["error_kind"], "device-error");
-        assert_eq!(json_body["details"]["error_code"], 1);
     }
 
     #[tokio::test]
-    async fn text_request_with_pixoo_error() {
-        let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":1}"#);
-        });
-
-        let client =
-            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+    async fn draw_fill_rejects_invalid_payload() {
+        let (base_url, _requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
         let app = build_draw_app(Arc::new(AppState::with_client(client)));
         let (status, body) = send_json_request(
             &app,
             Method::POST,
-            "/draw/text",
-            Some(json!({
-                "id": 4,
-                "position": { "x": 0, "y": 40 },
-                "scrollDirection": "LEFT",
-                "font": 4,
-                "textWidth": 56,
-                "scrollSpeed": 10,
-                "text": "hello, Divoom",
-                "color": { "red": 255, "green": 255, "blue": 0 },
-                "textAlignment": "LEFT"
-            })),
+            "/draw/fill",
+            Some(json!({ "red": 999, "green": 0, "blue": 0 })),
         )
         .await;
 
-        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
         let json_body: Value = serde_json::from_str(&body).unwrap();
-        assert_eq!(json_body["error_kind"], "device-error");
-        assert_eq!(json_body["details"]["error_code"], 1);
+        assert_eq!(json_body["error_status"], 400);
+        assert_eq!(json_body["error_kind"], "validation");
+        assert!(json_body["message"].is_string());
+        assert!(json_body["details"]["red"].is_array());
     }
 
     // --- draw_upload tests ---
@@ -896,19 +891,25 @@ mod tests {
     }
 
     fn remote_test_state(base_url: String, max_image_size: usize) -> Arc<AppState> {
+        remote_test_state_with(
+            base_url,
+            RemoteFetchConfig::new(Duration::from_secs(5), max_image_size),
+        )
+    }
+
+    fn remote_test_state_with(base_url: String, config: RemoteFetchConfig) -> Arc<AppState> {
         let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let remote_fetcher = RemoteFetcher::new(RemoteFetchConfig::new(
-            Duration::from_secs(5),
-            max_image_size,
-        ))
-        .expect("remote fetcher");
-        Arc::new(AppState {
-            health_forward: false,
-            pixoo_client: client,
-            animation_speed_factor: 1.4,
+        let (max_image_size, remote_timeout) = (config.max_image_size, config.timeout);
+        let state = AppState {
+            remote_fetcher: RemoteFetcher::new(config).expect("remote fetcher"),
+            ..AppState::with_client(client)
+        };
+        state.settings.set(RuntimeSettings {
             max_image_size,
-            remote_fetcher,
-        })
+            remote_timeout,
+            ..state.settings.get()
+        });
+        Arc::new(state)
     }
 
     async fn send_remote_request(app: &Router, link: &str) -> (StatusCode, String) {
@@ -942,15 +943,67 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn upload_animated_gif_sends_multiple_frames() {
+    async fn async_upload_returns_a_job_that_tracks_progress() {
         let (base_url, requests) = start_pixoo_mock().await;
         let app = build_draw_app(upload_test_state(base_url));
 
+        let (ct, body) = multipart_body("file", "image/png", &create_test_png());
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/draw/upload?async=true")
+            .header("content-type", ct)
+            .body(Body::from(body))
+            .unwrap();
+        let response = app.clone().oneshot(req).await.unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let location = response.headers()["location"].to_str().unwrap().to_string();
+        let job: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap())
+                .unwrap();
+        assert_eq!(location, format!("/jobs/{}", job["id"].as_str().unwrap()));
+
+        let mut status = Value::Null;
+        for _ in 0..100 {
+            let (code, body) = send_json_request(&app, Method::GET, &location, None).await;
+            assert_eq!(code, StatusCode::OK);
+            status = serde_json::from_str(&body).unwrap();
+            if status["state"] != "RUNNING" {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        assert_eq!(status["state"], "SUCCEEDED");
+        assert_eq!(status["stage"], "DONE");
+        assert_eq!(status["framesTotal"], 1);
+        assert_eq!(status["framesSent"], 1);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        let (code, _) = send_json_request(&app, Method::GET, "/jobs/unknown", None).await;
+        assert_eq!(code, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn upload_animated_gif_sends_multiple_frames() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let state = upload_test_state(base_url);
+        let mut events = state.events.subscribe();
+        let app = build_draw_app(state);
+
         let gif_data = create_test_gif(3);
         let (ct, body) = multipart_body("file", "image/gif", &gif_data);
         let (status, _) = send_multipart_request(&app, &ct, body).await;
 
         assert_eq!(status, StatusCode::OK);
+        // One command event for the whole upload, not one per frame.
+        let mut uploads = 0;
+        while let Ok(event) = events.try_recv() {
+            if let EventKind::Command { command, .. } = &event.kind {
+                assert_eq!(command, "Draw/SendHttpGif");
+                uploads += 1;
+            }
+        }
+        assert_eq!(uploads, 1);
 
         let captured = requests.lock().unwrap();
         // 1 GetHttpGifId + 3 SendHttpGif
@@ -994,8 +1047,11 @@ mod tests {
     async fn upload_oversized_file_returns_413() {
         let (base_url, _) = start_pixoo_mock().await;
         let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
-        let mut state = AppState::with_client(client);
-        state.max_image_size = 100; // 100 byte limit
+        let state = AppState::with_client(client);
+        state.settings.set(RuntimeSettings {
+            max_image_size: 100, // 100 byte limit
+            ..state.settings.get()
+        });
         let app = build_draw_app(Arc::new(state));
 
         let png_data = create_test_png(); // larger than 100 bytes
@@ -1063,6 +1119,30 @@ mod tests {
         assert_eq!(json_body["error_kind"], "validation");
     }
 
+    #[tokio::test]
+    async fn remote_blocked_link_returns_400_without_touching_device() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let app = build_draw_app(remote_test_state_with(
+            base_url,
+            RemoteFetchConfig::new(Duration::from_secs(5), 1024).with_policy(RemotePolicy {
+                block_private: true,
+                ..RemotePolicy::default()
+            }),
+        ));
+
+        let (status, body) =
+            send_remote_request(&app, "http://169.254.169.254/latest/meta-data").await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "validation");
+        assert!(json_body["details"]["link"]
+            .as_str()
+            .unwrap()
+            .contains("not allowed"));
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
     #[tokio::test]
     async fn remote_oversized_payload_returns_413() {
         let (base_url, _) = start_pixoo_mock().await;
@@ -0,0 +1,643 @@
+//! Notifications that cover the display for a while and then give it back.
+//!
+//! A notification is one frame, a background color with an optional icon,
+//! plus a line of text. Notifications wait in a queue ordered by priority; a
+//! more urgent one interrupts the one on screen, which goes back to the queue
+//! with the time it had left. Once the queue is empty the display is
+//! restored: the last frames the bridge uploaded and its text slots, or else
+//! the channel the device showed before the first notification.
+
+use crate::pixels::{draw_scaled, uniform_pixel_buffer, DecodedFrame, PIXOO_FRAME_DIM};
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, InternalErrorResponse,
+    PayloadTooLargeResponse, ValidationErrorResponse,
+};
+use crate::pixoo::protocol::{
+    Channel, ClearText, GetChannel, SendText, SetChannel, TextAlign, TextDirection,
+};
+use crate::state::AppState;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+use tokio::time::{sleep_until, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use super::jobs::JobProgress;
+use super::text::{resend_text_slots, rgb_to_hex, validate_rgb_color, RgbColor};
+use super::{decode_base64_frames, fetch_remote_frames, send_frames, validate_remote_link};
+use crate::routes::common::{internal_server_error, ValidatedJson};
+
+/// Edge of the icon square, centered at the top of the screen.
+const ICON_SIZE: usize = 32;
+const ICON_LEFT: usize = (PIXOO_FRAME_DIM as usize - ICON_SIZE) / 2;
+const ICON_TOP: usize = 4;
+/// Text slot used for the notification text.
+const NOTIFY_TEXT_ID: u16 = 0;
+
+pub(super) fn notify_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new().routes(routes!(notify))
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct NotifyRequest {
+    #[validate(length(min = 1, max = 512))]
+    text: String,
+    #[serde(default)]
+    #[validate(custom(function = "validate_icon"))]
+    icon: Option<NotifyIcon>,
+    #[serde(default = "white")]
+    #[validate(custom(function = "validate_rgb_color"))]
+    text_color: RgbColor,
+    #[serde(default = "black")]
+    #[validate(custom(function = "validate_rgb_color"))]
+    background_color: RgbColor,
+    #[serde(default)]
+    priority: NotifyPriority,
+    /// Seconds on screen.
+    #[serde(default = "default_duration")]
+    #[validate(range(min = 1, max = 300))]
+    duration: u64,
+}
+
+fn white() -> RgbColor {
+    RgbColor {
+        red: 255,
+        green: 255,
+        blue: 255,
+    }
+}
+
+fn black() -> RgbColor {
+    RgbColor {
+        red: 0,
+        green: 0,
+        blue: 0,
+    }
+}
+
+fn default_duration() -> u64 {
+    10
+}
+
+/// Picture shown above the text. Exactly one key is expected.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+enum NotifyIcon {
+    /// Base64-encoded image (PNG, GIF, WebP, or JPEG).
+    Image(String),
+    /// Absolute http or https link to an image.
+    Link(String),
+}
+
+fn validate_icon(icon: &NotifyIcon) -> Result<(), ValidationError> {
+    match icon {
+        NotifyIcon::Image(data) if data.is_empty() => {
+            let mut error = ValidationError::new("empty_image");
+            error.message = Some("image must not be empty".into());
+            Err(error)
+        }
+        NotifyIcon::Image(_) => Ok(()),
+        NotifyIcon::Link(link) => validate_remote_link(link),
+    }
+}
+
+/// Notifications with a higher priority are shown first and interrupt
+/// lower ones.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize, ToSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum NotifyPriority {
+    #[default]
+    Info,
+    Warning,
+    Critical,
+}
+
+/// Reply of `POST /notify`.
+#[derive(Debug, Serialize, ToSchema)]
+struct NotificationQueued {
+    id: String,
+    /// Notifications that will be shown before this one, including the one
+    /// on screen unless this notification interrupts it.
+    ahead: usize,
+}
+
+struct Notification {
+    id: String,
+    priority: NotifyPriority,
+    frame: Arc<Vec<DecodedFrame>>,
+    text: SendText,
+    remaining: Duration,
+}
+
+impl Notification {
+    /// One frame of `canvas` with `text` below the icon, or centered when
+    /// there is none.
+    fn new(
+        priority: NotifyPriority,
+        canvas: Vec<u8>,
+        text: String,
+        text_color: &RgbColor,
+        has_icon: bool,
+        duration: Duration,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            priority,
+            frame: Arc::new(vec![DecodedFrame {
+                rgb_buffer: canvas,
+                delay_ms: 0,
+            }]),
+            text: SendText {
+                lcd_id: 0,
+                text_id: NOTIFY_TEXT_ID,
+                x: 0,
+                y: if has_icon { 44 } else { 26 },
+                dir: TextDirection::Left,
+                font: 2,
+                text_width: 64,
+                speed: 40,
+                color: rgb_to_hex(text_color),
+                text_string: text,
+                align: TextAlign::Middle,
+            },
+            remaining: duration,
+        }
+    }
+}
+
+/// Frames the bridge uploaded last, to show again after a notification.
+#[derive(Clone)]
+struct ShownFrames {
+    frames: Arc<Vec<DecodedFrame>>,
+    speed_factor: f64,
+}
+
+#[derive(Default)]
+struct NotifyState {
+    /// Highest priority first; equal priorities in arrival order.
+    queue: Vec<Notification>,
+    /// Priority of the notification on screen.
+    showing: Option<NotifyPriority>,
+    running: bool,
+    last_frames: Option<ShownFrames>,
+}
+
+/// The notification queue and what to restore once it is empty.
+#[derive(Clone, Default)]
+pub struct Notifications {
+    state: Arc<Mutex<NotifyState>>,
+    preempt: Arc<Notify>,
+}
+
+impl Notifications {
+    /// Remembers frames that were just uploaded by a draw.
+    pub(super) fn remember_frames(&self, frames: Arc<Vec<DecodedFrame>>, speed_factor: f64) {
+        self.state.lock().unwrap().last_frames = Some(ShownFrames {
+            frames,
+            speed_factor,
+        });
+    }
+
+    /// Forgets the uploaded frames after the device left its animation layer.
+    pub fn forget_frames(&self) {
+        self.state.lock().unwrap().last_frames = None;
+    }
+
+    fn last_frames(&self) -> Option<ShownFrames> {
+        self.state.lock().unwrap().last_frames.clone()
+    }
+
+    fn set_last_frames(&self, frames: Option<ShownFrames>) {
+        self.state.lock().unwrap().last_frames = frames;
+    }
+
+    /// Queues `notification` and returns how many are ahead of it, starting
+    /// the worker when it is idle.
+    fn enqueue(&self, state: &Arc<AppState>, notification: Notification) -> usize {
+        let mut guard = self.state.lock().unwrap();
+        let priority = notification.priority;
+        let position = guard
+            .queue
+            .iter()
+            .position(|queued| queued.priority < priority)
+            .unwrap_or(guard.queue.len());
+        guard.queue.insert(position, notification);
+
+        let interrupts = guard.showing.is_some_and(|showing| showing < priority);
+        if interrupts {
+            self.preempt.notify_one();
+        }
+        if !guard.running {
+            guard.running = true;
+            tokio::spawn(run(state.clone()));
+        }
+        position + usize::from(guard.showing.is_some() && !interrupts)
+    }
+
+    /// Queues a `WARNING` notification with white text on black for another
+    /// feature, such as an expired timer, and returns its id.
+    pub fn queue_text(&self, state: &Arc<AppState>, text: String) -> String {
+        let notification = Notification::new(
+            NotifyPriority::Warning,
+            uniform_pixel_buffer(0, 0, 0),
+            text,
+            &white(),
+            false,
+            Duration::from_secs(default_duration()),
+        );
+        let id = notification.id.clone();
+        let ahead = self.enqueue(state, notification);
+        info!(id = %id, ahead, "notification queued");
+        id
+    }
+
+    /// Takes the most urgent notification and marks it as on screen.
+    fn start_next(&self) -> Option<Notification> {
+        let mut guard = self.state.lock().unwrap();
+        if guard.queue.is_empty() {
+            return None;
+        }
+        let next = guard.queue.remove(0);
+        guard.showing = Some(next.priority);
+        Some(next)
+    }
+
+    /// Whether a notification more urgent than `priority` is waiting.
+    fn outranked(&self, priority: NotifyPriority) -> bool {
+        self.state
+            .lock()
+            .unwrap()
+            .queue
+            .first()
+            .is_some_and(|queued| queued.priority > priority)
+    }
+
+    /// Puts an interrupted notification back in front of its priority.
+    fn requeue(&self, notification: Notification) {
+        let mut guard = self.state.lock().unwrap();
+        let position = guard
+            .queue
+            .iter()
+            .position(|queued| queued.priority <= notification.priority)
+            .unwrap_or(guard.queue.len());
+        guard.queue.insert(position, notification);
+        guard.showing = None;
+    }
+
+    fn finish(&self) {
+        self.state.lock().unwrap().showing = None;
+    }
+
+    /// Stops the worker unless notifications arrived while restoring.
+    fn stop_if_idle(&self) -> bool {
+        let mut guard = self.state.lock().unwrap();
+        if guard.queue.is_empty() {
+            guard.running = false;
+        }
+        !guard.running
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/notify",
+    tag = "draw",
+    request_body = NotifyRequest,
+    responses(
+        (status = 202, description = "Notification queued", body = NotificationQueued),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 413, response = PayloadTooLargeResponse),
+        (status = 500, response = InternalErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn notify(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<NotifyRequest>,
+) -> Response {
+    let Some([red, green, blue]) = payload.background_color.to_rgb() else {
+        return internal_server_error("invalid background color");
+    };
+    let mut canvas = uniform_pixel_buffer(red, green, blue);
+    let icon = match &payload.icon {
+        None => None,
+        Some(NotifyIcon::Image(data)) => match decode_base64_frames(&state, data, "icon") {
+            Ok(frames) => frames.into_iter().next(),
+            Err(resp) => return resp,
+        },
+        Some(NotifyIcon::Link(link)) => {
+            match fetch_remote_frames(&state, link, &JobProgress::default()).await {
+                Ok(frames) => frames.first().cloned(),
+                Err(resp) => return resp,
+            }
+        }
+    };
+    if let Some(icon) = &icon {
+        draw_scaled(
+            &mut canvas,
+            &icon.rgb_buffer,
+            ICON_SIZE,
+            ICON_LEFT,
+            ICON_TOP,
+        );
+    }
+
+    let notification = Notification::new(
+        payload.priority,
+        canvas,
+        payload.text,
+        &payload.text_color,
+        icon.is_some(),
+        Duration::from_secs(payload.duration),
+    );
+    let id = notification.id.clone();
+    let ahead = state.notifications.enqueue(&state, notification);
+    info!(id = %id, ahead, "notification queued");
+    (
+        StatusCode::ACCEPTED,
+        axum::Json(NotificationQueued { id, ahead }),
+    )
+        .into_response()
+}
+
+/// Shows queued notifications until none are left, then restores the
+/// display.
+async fn run(state: Arc<AppState>) {
+    let notifications = &state.notifications;
+    loop {
+        let channel = previous_channel(&state).await;
+        while let Some(mut notification) = notifications.start_next() {
+            let shown_at = Instant::now();
+            show(&state, &notification).await;
+            let ends_at = Instant::now() + notification.remaining;
+            loop {
+                tokio::select! {
+                    () = sleep_until(ends_at) => {
+                        notifications.finish();
+                        break;
+                    }
+                    () = notifications.preempt.notified() => {
+                        // The permit may predate this notification, for
+                        // example when the one it was meant for ended first.
+                        if !notifications.outranked(notification.priority) {
+                            continue;
+                        }
+                        info!(id = %notification.id, "notification interrupted");
+                        notification.remaining =
+                            notification.remaining.saturating_sub(shown_at.elapsed());
+                        notifications.requeue(notification);
+                        break;
+                    }
+                }
+            }
+        }
+        restore(&state, channel).await;
+        if notifications.stop_if_idle() {
+            return;
+        }
+    }
+}
+
+/// The channel to go back to when the bridge has nothing of its own on
+/// screen.
+async fn previous_channel(state: &AppState) -> Option<Channel> {
+    if state.notifications.last_frames().is_some() || !state.text_slots.is_empty() {
+        return None;
+    }
+    match state.pixoo_client.execute(&GetChannel).await {
+        Ok(reply) => Channel::from_index(reply.select_index),
+        Err(err) => {
+            warn!(error = %err, "cannot read the channel to restore after notifications");
+            None
+        }
+    }
+}
+
+async fn show(state: &AppState, notification: &Notification) {
+    info!(id = %notification.id, "showing notification");
+    if let Err(err) = state.pixoo_client.execute(&ClearText).await {
+        warn!(error = %err, "failed to clear text for notification");
+    }
+    // The notification is not something to restore later.
+    let shown = state.notifications.last_frames();
+    let response = send_frames(
+        state,
+        &notification.frame,
+        1.0,
+        true,
+        &JobProgress::default(),
+    )
+    .await;
+    state.notifications.set_last_frames(shown);
+    if response.status() != StatusCode::OK {
+        warn!(status = %response.status(), "failed to upload notification");
+        return;
+    }
+    if let Err(err) = state.pixoo_client.execute(&notification.text).await {
+        warn!(error = %err, "failed to send notification text");
+    }
+}
+
+async fn restore(state: &AppState, channel: Option<Channel>) {
+    if let Some(shown) = state.notifications.last_frames() {
+        let response = send_frames(
+            state,
+            &shown.frames,
+            shown.speed_factor,
+            true,
+            &JobProgress::default(),
+        )
+        .await;
+        if response.status() != StatusCode::OK {
+            warn!(status = %response.status(), "failed to restore frames after notifications");
+        }
+    } else if let Some(channel) = channel {
+        if let Err(err) = state.pixoo_client.execute(&SetChannel { channel }).await {
+            warn!(error = %err, "failed to restore channel after notifications");
+        }
+        return;
+    }
+    if let Err(err) = state.pixoo_client.execute(&ClearText).await {
+        warn!(error = %err, "failed to clear notification text");
+    }
+    if let Err(err) = resend_text_slots(state).await {
+        warn!(error = %err, "failed to restore text after notifications");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{build_draw_app, start_pixoo_mock};
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::common::testing::send_json_request;
+    use crate::state::AppState;
+    use axum::http::{Method, StatusCode};
+    use serde_json::{json, Value};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tokio::time::{sleep, Instant};
+
+    /// Waits until the device received `count` commands, then returns them.
+    async fn wait_for_commands(requests: &Arc<Mutex<Vec<Value>>>, count: usize) -> Vec<Value> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            let captured = requests.lock().unwrap().clone();
+            if captured.len() >= count || Instant::now() > deadline {
+                return captured;
+            }
+            sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    fn texts(captured: &[Value]) -> Vec<&str> {
+        captured
+            .iter()
+            .filter(|request| request["Command"] == "Draw/SendHttpText")
+            .map(|request| request["TextString"].as_str().unwrap_or_default())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn notification_restores_the_previous_frames() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let fill = json!({ "red": 0, "green": 0, "blue": 200 });
+        let (status, _) = send_json_request(&app, Method::POST, "/draw/fill", Some(fill)).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/notify",
+            Some(json!({ "text": "door open", "duration": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+        let queued: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(queued["ahead"], 0);
+
+        // fill, then clear + upload + text, then upload + clear
+        let captured = wait_for_commands(&requests, 9).await;
+        let commands: Vec<_> = captured
+            .iter()
+            .map(|request| request["Command"].as_str().unwrap_or_default())
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                "Draw/GetHttpGifId",
+                "Draw/SendHttpGif",
+                "Draw/ClearHttpText",
+                "Draw/GetHttpGifId",
+                "Draw/SendHttpGif",
+                "Draw/SendHttpText",
+                "Draw/GetHttpGifId",
+                "Draw/SendHttpGif",
+                "Draw/ClearHttpText",
+            ]
+        );
+        assert_eq!(captured[5]["TextString"], "door open");
+        assert_eq!(captured[7]["PicData"], captured[1]["PicData"]);
+        assert_ne!(captured[4]["PicData"], captured[1]["PicData"]);
+    }
+
+    #[tokio::test]
+    async fn urgent_notifications_interrupt_and_resume_the_current_one() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        let notify = |body: Value| send_json_request(&app, Method::POST, "/notify", Some(body));
+
+        let (status, _) = notify(json!({ "text": "info", "duration": 2 })).await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        wait_for_commands(&requests, 4).await;
+        let (_, body) = notify(json!({ "text": "later", "duration": 1 })).await;
+        let queued: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(queued["ahead"], 1);
+        let (_, body) =
+            notify(json!({ "text": "fire", "priority": "CRITICAL", "duration": 1 })).await;
+        let queued: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(queued["ahead"], 0);
+
+        let deadline = Instant::now() + Duration::from_secs(8);
+        while texts(&requests.lock().unwrap()).len() < 4 && Instant::now() < deadline {
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(
+            texts(&requests.lock().unwrap()),
+            ["info", "fire", "info", "later"]
+        );
+    }
+
+    #[tokio::test]
+    async fn leftover_preempt_permit_does_not_interrupt() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let state = Arc::new(AppState::with_client(client));
+        let app = build_draw_app(state.clone());
+
+        // A permit stored while nothing waited, as when an urgent notification
+        // arrives just as the one on screen ends.
+        state.notifications.preempt.notify_one();
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/notify",
+            Some(json!({ "text": "alone", "priority": "CRITICAL", "duration": 1 })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+
+        // clear + upload + text, then the channel is restored after a second
+        let started = Instant::now();
+        let deadline = started + Duration::from_secs(5);
+        while state.notifications.state.lock().unwrap().running && Instant::now() < deadline {
+            sleep(Duration::from_millis(20)).await;
+        }
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        assert_eq!(texts(&requests.lock().unwrap()), ["alone"]);
+    }
+
+    #[tokio::test]
+    async fn notify_rejects_invalid_payload() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/notify",
+            Some(json!({
+                "text": "",
+                "duration": 0,
+                "icon": { "link": "file:///etc/passwd" },
+                "textColor": { "red": 300, "green": 0, "blue": 0 }
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        for field in ["text", "duration", "icon", "text_color"] {
+            assert!(json_body["details"][field].is_array(), "{field}");
+        }
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,1005 @@
+//! Scrolling text items, optionally drawn over a background.
+//!
+//! `Draw/SendHttpText` only renders on top of an animation sent with
+//! `Draw/SendHttpGif`, so text sent to a freshly booted device shows nothing.
+//! A request may therefore carry a background, which is uploaded through
+//! [`send_frames`] before the text items are sent.
+//!
+//! The device cannot report which text it shows, so the bridge remembers
+//! every item it sent in [`TextSlots`]. That record lets callers list the
+//! slots and change or blank one of them without resending the others.
+
+use crate::pixels::{uniform_pixel_buffer, DecodedFrame};
+use crate::pixoo::error::{
+    ConflictResponse, DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse,
+    InternalErrorResponse, PayloadTooLargeResponse, PixooError, PixooHttpErrorKind,
+    PixooHttpErrorResponse, ValidationErrorResponse,
+};
+use crate::pixoo::protocol::{ClearText, PixooRequest, SendText, TextAlign, TextDirection};
+use crate::state::AppState;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+use super::jobs::{DrawJobStatus, JobProgress, JobStage};
+use super::{
+    decode_base64_frames, fetch_remote_frames, run_draw, send_frames, validate_remote_link,
+    DrawOptions,
+};
+use crate::routes::common::{
+    dispatch_pixoo_command, internal_server_error, pixoo_error_response, validation_error_simple,
+    ValidatedJson,
+};
+
+/// Number of text slots the device offers (ids `0..=20`).
+const TEXT_SLOTS: u64 = 21;
+
+pub(super) fn text_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(draw_text, draw_text_slots))
+        .routes(routes!(draw_text_batch))
+        .routes(routes!(draw_text_clear))
+        .routes(routes!(draw_text_slot_update, draw_text_slot_clear))
+}
+
+/// Text items the bridge sent, by slot id. Cleared whenever the device drops
+/// its text: on `/draw/text/clear`, a channel switch, or a reboot.
+#[derive(Clone, Default)]
+pub struct TextSlots {
+    slots: Arc<Mutex<BTreeMap<u16, TextItem>>>,
+}
+
+impl TextSlots {
+    fn set(&self, item: TextItem) {
+        self.slots.lock().unwrap().insert(item.id, item);
+    }
+
+    fn get(&self, id: u16) -> Option<TextItem> {
+        self.slots.lock().unwrap().get(&id).cloned()
+    }
+
+    fn remove(&self, id: u16) {
+        self.slots.lock().unwrap().remove(&id);
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.slots.lock().unwrap().is_empty()
+    }
+
+    fn list(&self) -> Vec<TextItem> {
+        self.slots.lock().unwrap().values().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.slots.lock().unwrap().clear();
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct TextItem {
+    #[validate(range(min = 0, max = 20))]
+    id: u16,
+    position: TextPosition,
+    scroll_direction: ScrollDirection,
+    #[validate(range(min = 0, max = 7))]
+    font: u16,
+    #[validate(range(min = 16, max = 64))]
+    text_width: u16,
+    #[validate(length(max = 512))]
+    text: String,
+    #[validate(range(min = 0, max = 100))]
+    scroll_speed: u16,
+    #[validate(custom(function = "validate_rgb_color"))]
+    color: RgbColor,
+    text_alignment: TextAlignment,
+}
+
+impl TextItem {
+    fn to_request(&self) -> SendText {
+        SendText {
+            lcd_id: 0,
+            text_id: self.id,
+            x: self.position.x,
+            y: self.position.y,
+            dir: self.scroll_direction.direction(),
+            font: self.font,
+            text_width: self.text_width,
+            speed: self.scroll_speed,
+            color: rgb_to_hex(&self.color),
+            text_string: self.text.clone(),
+            align: self.text_alignment.align(),
+        }
+    }
+}
+
+/// Changes to a text slot; missing fields keep their current value.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct TextSlotUpdate {
+    position: Option<TextPosition>,
+    scroll_direction: Option<ScrollDirection>,
+    #[validate(range(min = 0, max = 7))]
+    font: Option<u16>,
+    #[validate(range(min = 16, max = 64))]
+    text_width: Option<u16>,
+    #[validate(length(max = 512))]
+    text: Option<String>,
+    #[validate(range(min = 0, max = 100))]
+    scroll_speed: Option<u16>,
+    #[validate(custom(function = "validate_rgb_color"))]
+    color: Option<RgbColor>,
+    text_alignment: Option<TextAlignment>,
+}
+
+impl TextSlotUpdate {
+    fn apply(self, item: &mut TextItem) {
+        if let Some(position) = self.position {
+            item.position = position;
+        }
+        if let Some(scroll_direction) = self.scroll_direction {
+            item.scroll_direction = scroll_direction;
+        }
+        if let Some(font) = self.font {
+            item.font = font;
+        }
+        if let Some(text_width) = self.text_width {
+            item.text_width = text_width;
+        }
+        if let Some(text) = self.text {
+            item.text = text;
+        }
+        if let Some(scroll_speed) = self.scroll_speed {
+            item.scroll_speed = scroll_speed;
+        }
+        if let Some(color) = self.color {
+            item.color = color;
+        }
+        if let Some(text_alignment) = self.text_alignment {
+            item.text_alignment = text_alignment;
+        }
+    }
+}
+
+/// A single text item, with the item fields at the top level of the body.
+#[derive(Debug, Deserialize, ToSchema)]
+struct DrawTextRequest {
+    #[serde(flatten)]
+    item: TextItem,
+    #[serde(default)]
+    background: Option<TextBackground>,
+}
+
+// Hand-written so the flattened item's errors stay top-level keys.
+impl Validate for DrawTextRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = self.item.validate().err().unwrap_or_default();
+        if let Some(Err(error)) = self.background.as_ref().map(validate_background) {
+            errors.add("background", error);
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+struct DrawTextBatchRequest {
+    #[serde(default)]
+    #[validate(custom(function = "validate_background"))]
+    background: Option<TextBackground>,
+    #[validate(length(min = 1, max = TEXT_SLOTS), nested)]
+    items: Vec<TextItem>,
+}
+
+/// What the display shows behind the text. Exactly one key is expected.
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+enum TextBackground {
+    /// Solid color filling the display.
+    Color(RgbColor),
+    /// Base64-encoded image (PNG, GIF, WebP, or JPEG).
+    Image(String),
+    /// Absolute http or https link to an image.
+    Link(String),
+}
+
+impl TextBackground {
+    fn first_stage(&self) -> JobStage {
+        match self {
+            Self::Color(_) => JobStage::Encoding,
+            Self::Image(_) => JobStage::Decoding,
+            Self::Link(_) => JobStage::Fetching,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub(super) struct TextPosition {
+    pub(super) x: u16,
+    pub(super) y: u16,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub(super) struct RgbColor {
+    pub(super) red: u16,
+    pub(super) green: u16,
+    pub(super) blue: u16,
+}
+
+impl RgbColor {
+    /// The channels as bytes, or `None` when one is out of range.
+    pub(super) fn to_rgb(&self) -> Option<[u8; 3]> {
+        Some([
+            u8::try_from(self.red).ok()?,
+            u8::try_from(self.green).ok()?,
+            u8::try_from(self.blue).ok()?,
+        ])
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(super) enum ScrollDirection {
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    pub(super) fn direction(&self) -> TextDirection {
+        match self {
+            Self::Left => TextDirection::Left,
+            Self::Right => TextDirection::Right,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub(super) enum TextAlignment {
+    Left,
+    Middle,
+    Right,
+}
+
+impl TextAlignment {
+    pub(super) fn align(&self) -> TextAlign {
+        match self {
+            Self::Left => TextAlign::Left,
+            Self::Middle => TextAlign::Middle,
+            Self::Right => TextAlign::Right,
+        }
+    }
+}
+
+pub(super) fn validate_rgb_color(color: &RgbColor) -> Result<(), ValidationError> {
+    let mut error = ValidationError::new("invalid_color");
+    error.message = Some("color values must be between 0 and 255".into());
+
+    if color.red > 255 || color.green > 255 || color.blue > 255 {
+        return Err(error);
+    }
+
+    Ok(())
+}
+
+fn validate_background(background: &TextBackground) -> Result<(), ValidationError> {
+    match background {
+        TextBackground::Color(color) => validate_rgb_color(color),
+        TextBackground::Image(data) if data.is_empty() => {
+            let mut error = ValidationError::new("empty_image");
+            error.message = Some("image must not be empty".into());
+            Err(error)
+        }
+        TextBackground::Image(_) => Ok(()),
+        TextBackground::Link(link) => validate_remote_link(link),
+    }
+}
+
+pub(super) fn rgb_to_hex(color: &RgbColor) -> String {
+    format!("#{:02X}{:02X}{:02X}", color.red, color.green, color.blue)
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/text",
+    tag = "draw",
+    params(
+        ("async" = Option<bool>, Query, description = "Run as a background job and answer `202` with its status"),
+        ("force" = Option<bool>, Query, description = "Upload the background even when the display already shows it")
+    ),
+    request_body = DrawTextRequest,
+    responses(
+        (status = 200, description = "Text sent to the display"),
+        (status = 202, description = "Draw started as a background job", body = DrawJobStatus),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 409, response = ConflictResponse),
+        (status = 413, response = PayloadTooLargeResponse),
+        (status = 500, response = InternalErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn draw_text(
+    State(state): State<Arc<AppState>>,
+    Query(options): Query<DrawOptions>,
+    ValidatedJson(payload): ValidatedJson<DrawTextRequest>,
+) -> Response {
+    draw_text_items(&state, &options, payload.background, vec![payload.item]).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/text/batch",
+    tag = "draw",
+    params(
+        ("async" = Option<bool>, Query, description = "Run as a background job and answer `202` with its status"),
+        ("force" = Option<bool>, Query, description = "Upload the background even when the display already shows it")
+    ),
+    request_body = DrawTextBatchRequest,
+    responses(
+        (status = 200, description = "Every text item sent to the display"),
+        (status = 202, description = "Draw started as a background job", body = DrawJobStatus),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 409, response = ConflictResponse),
+        (status = 413, response = PayloadTooLargeResponse),
+        (status = 500, response = InternalErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn draw_text_batch(
+    State(state): State<Arc<AppState>>,
+    Query(options): Query<DrawOptions>,
+    ValidatedJson(payload): ValidatedJson<DrawTextBatchRequest>,
+) -> Response {
+    draw_text_items(&state, &options, payload.background, payload.items).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/draw/text/clear",
+    tag = "draw",
+    responses(
+        (status = 200, description = "Text cleared from the display"),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_text_clear(State(state): State<Arc<AppState>>) -> Response {
+    let response = dispatch_pixoo_command(&state, &ClearText).await;
+    if response.status() == StatusCode::OK {
+        state.text_slots.clear();
+    }
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/draw/text",
+    tag = "draw",
+    responses(
+        (status = 200, description = "Text items the bridge sent, ordered by slot id", body = [TextItem])
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_text_slots(State(state): State<Arc<AppState>>) -> Response {
+    axum::Json(state.text_slots.list()).into_response()
+}
+
+#[utoipa::path(
+    patch,
+    path = "/draw/text/{id}",
+    tag = "draw",
+    params(("id" = u16, Path, description = "Text slot 0-20")),
+    request_body = TextSlotUpdate,
+    responses(
+        (status = 200, description = "Slot updated on the display", body = TextItem),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 404, description = "The bridge has not sent text to this slot"),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, update))]
+async fn draw_text_slot_update(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    ValidatedJson(update): ValidatedJson<TextSlotUpdate>,
+) -> Response {
+    let mut item = match tracked_slot(&state, &id) {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+    update.apply(&mut item);
+    if let Err(resp) = send_text_item(&state, item.clone()).await {
+        return resp;
+    }
+    axum::Json(item).into_response()
+}
+
+#[utoipa::path(
+    delete,
+    path = "/draw/text/{id}",
+    tag = "draw",
+    params(("id" = u16, Path, description = "Text slot 0-20")),
+    responses(
+        (status = 200, description = "Slot blanked; the other slots keep their text"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 404, description = "The bridge has not sent text to this slot"),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn draw_text_slot_clear(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let mut item = match tracked_slot(&state, &id) {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+    // The device has no per-slot clear, so the slot is overwritten with
+    // empty text in its current layout.
+    item.text.clear();
+    if let Err(err) = state.pixoo_client.execute(&item.to_request()).await {
+        return pixoo_error_response(&SendText::COMMAND, &err);
+    }
+    state.text_slots.remove(item.id);
+    StatusCode::OK.into_response()
+}
+
+/// Parses a slot id path segment and returns what the bridge last sent to
+/// that slot.
+#[allow(clippy::result_large_err)]
+fn tracked_slot(state: &AppState, raw: &str) -> Result<TextItem, Response> {
+    let id = match raw.parse::<u16>() {
+        Ok(id) if u64::from(id) < TEXT_SLOTS => id,
+        _ => return Err(validation_error_simple("id", "id must be between 0 and 20")),
+    };
+    state.text_slots.get(id).ok_or_else(|| {
+        PixooHttpErrorResponse::new(
+            StatusCode::NOT_FOUND,
+            PixooHttpErrorKind::NotFound,
+            "text slot not in use",
+        )
+        .into_response()
+    })
+}
+
+/// Sends every tracked slot again, after something covered the text layer.
+pub(super) async fn resend_text_slots(state: &AppState) -> Result<(), PixooError> {
+    for item in state.text_slots.list() {
+        state.pixoo_client.execute(&item.to_request()).await?;
+    }
+    Ok(())
+}
+
+/// Sends `item` and records it as the content of its slot.
+#[allow(clippy::result_large_err)]
+async fn send_text_item(state: &AppState, item: TextItem) -> Result<(), Response> {
+    state
+        .pixoo_client
+        .execute(&item.to_request())
+        .await
+        .map_err(|err| pixoo_error_response(&SendText::COMMAND, &err))?;
+    state.text_slots.set(item);
+    Ok(())
+}
+
+/// Uploads the background, if any, then sends the items in order. Stops at
+/// the first failure.
+async fn draw_text_items(
+    state: &Arc<AppState>,
+    options: &DrawOptions,
+    background: Option<TextBackground>,
+    items: Vec<TextItem>,
+) -> Response {
+    let first_stage = background
+        .as_ref()
+        .map_or(JobStage::Sending, TextBackground::first_stage);
+    let force = options.force;
+    run_draw(state, options, first_stage, |state, progress| async move {
+        if let Some(background) = background {
+            let frames = match background_frames(&state, &background, &progress).await {
+                Ok(frames) => frames,
+                Err(resp) => return resp,
+            };
+            let speed_factor = state.settings.get().animation_speed_factor;
+            let response = send_frames(&state, &frames, speed_factor, force, &progress).await;
+            if response.status() != StatusCode::OK {
+                return response;
+            }
+        }
+        for item in items {
+            if let Err(resp) = send_text_item(&state, item).await {
+                return resp;
+            }
+        }
+        StatusCode::OK.into_response()
+    })
+    .await
+}
+
+#[allow(clippy::result_large_err)]
+async fn background_frames(
+    state: &AppState,
+    background: &TextBackground,
+    progress: &JobProgress,
+) -> Result<Arc<Vec<DecodedFrame>>, Response> {
+    match background {
+        TextBackground::Color(color) => {
+            let Some([red, green, blue]) = color.to_rgb() else {
+                return Err(internal_server_error("invalid background color"));
+            };
+            Ok(Arc::new(vec![DecodedFrame {
+                rgb_buffer: uniform_pixel_buffer(red, green, blue),
+                delay_ms: 0,
+            }]))
+        }
+        TextBackground::Image(data) => {
+            decode_base64_frames(state, data, "background").map(Arc::new)
+        }
+        TextBackground::Link(link) => fetch_remote_frames(state, link, progress).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{build_draw_app, start_pixoo_mock};
+    use super::{RgbColor, ScrollDirection, TextAlignment, TextItem, TextPosition};
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::common::testing::send_json_request;
+    use crate::state::AppState;
+    use axum::http::{Method, StatusCode};
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use httpmock::{Method as MockMethod, MockServer};
+    use image::{DynamicImage, ImageBuffer, ImageFormat, Rgba, RgbaImage};
+    use serde_json::{json, Value};
+    use std::io::Cursor;
+    use std::sync::Arc;
+    use validator::Validate;
+
+    fn base_text_payload() -> TextItem {
+        TextItem {
+            id: 4,
+            position: TextPosition { x: 0, y: 40 },
+            scroll_direction: ScrollDirection::Left,
+            font: 4,
+            text_width: 56,
+            text: "hello, Divoom".to_string(),
+            scroll_speed: 10,
+            color: RgbColor {
+                red: 255,
+                green: 255,
+                blue: 0,
+            },
+            text_alignment: TextAlignment::Left,
+        }
+    }
+
+    fn text_json(id: u16, text: &str) -> Value {
+        json!({
+            "id": id,
+            "position": { "x": 0, "y": 40 },
+            "scrollDirection": "LEFT",
+            "font": 4,
+            "textWidth": 56,
+            "scrollSpeed": 10,
+            "text": text,
+            "color": { "red": 255, "green": 255, "blue": 0 },
+            "textAlignment": "LEFT"
+        })
+    }
+
+    fn png_base64() -> String {
+        let img: RgbaImage = ImageBuffer::from_fn(8, 8, |_, _| Rgba([0, 0, 255, 255]));
+        let mut buf = Vec::new();
+        DynamicImage::ImageRgba8(img)
+            .write_to(&mut Cursor::new(&mut buf), ImageFormat::Png)
+            .expect("write png");
+        STANDARD.encode(buf)
+    }
+
+    fn commands(requests: &[Value]) -> Vec<&str> {
+        requests
+            .iter()
+            .map(|request| request["Command"].as_str().unwrap_or_default())
+            .collect()
+    }
+
+    #[test]
+    fn text_payload_with_out_of_range_fields() {
+        let mut payload = base_text_payload();
+        payload.id = 25;
+        payload.font = 9;
+        payload.text_width = 10;
+        payload.scroll_speed = 120;
+        payload.color.red = 300;
+        payload.text = "a".repeat(513);
+
+        let errors = payload.validate().expect_err("expected validation error");
+        let fields = errors.field_errors();
+        assert!(fields.contains_key("id"));
+        assert!(fields.contains_key("font"));
+        assert!(fields.contains_key("text_width"));
+        assert!(fields.contains_key("scroll_speed"));
+        assert!(fields.contains_key("color"));
+        assert!(fields.contains_key("text"));
+    }
+
+    #[tokio::test]
+    async fn text_request_sends_pixoo_text_command() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text",
+            Some(json!({
+                "id": 4,
+                "position": { "x": 0, "y": 40 },
+                "scrollDirection": "LEFT",
+                "font": 4,
+                "textWidth": 56,
+                "scrollSpeed": 10,
+                "text": "hello, Divoom",
+                "color": { "red": 255, "green": 255, "blue": 0 },
+                "textAlignment": "LEFT"
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+
+        let captured = requests.lock().unwrap();
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0]["Command"], "Draw/SendHttpText");
+        assert_eq!(captured[0]["LcdId"], 0);
+        assert_eq!(captured[0]["TextId"], 4);
+        assert_eq!(captured[0]["x"], 0);
+        assert_eq!(captured[0]["y"], 40);
+        assert_eq!(captured[0]["dir"], 0);
+        assert_eq!(captured[0]["font"], 4);
+        assert_eq!(captured[0]["TextWidth"], 56);
+        assert_eq!(captured[0]["speed"], 10);
+        assert_eq!(captured[0]["TextString"], "hello, Divoom");
+        assert_eq!(captured[0]["color"], "#FFFF00");
+        assert_eq!(captured[0]["align"], 1);
+    }
+
+    #[tokio::test]
+    async fn text_request_with_invalid_payload() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text",
+            Some(json!({
+                "id": 25,
+                "position": { "x": 0, "y": 40 },
+                "scrollDirection": "LEFT",
+                "font": 4,
+                "textWidth": 10,
+                "scrollSpeed": 10,
+                "text": "hello",
+                "color": { "red": 300, "green": 255, "blue": 0 },
+                "textAlignment": "LEFT"
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "validation");
+        assert!(json_body["details"]["id"].is_array());
+        assert!(json_body["details"]["text_width"].is_array());
+        assert!(json_body["details"]["color"].is_array());
+        let captured = requests.lock().unwrap();
+        assert!(captured.is_empty());
+    }
+
+    #[tokio::test]
+    async fn text_clear_request_sends_pixoo_command() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(&app, Method::POST, "/draw/text/clear", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+
+        let captured = requests.lock().unwrap();
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0]["Command"], "Draw/ClearHttpText");
+    }
+
+    #[tokio::test]
+    async fn text_clear_request_with_pixoo_error() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        let (status, body) = send_json_request(&app, Method::POST, "/draw/text/clear", None).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "device-error");
+        assert_eq!(json_body["details"]["error_code"], 1);
+    }
+
+    #[tokio::test]
+    async fn text_request_with_pixoo_error() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text",
+            Some(json!({
+                "id": 4,
+                "position": { "x": 0, "y": 40 },
+                "scrollDirection": "LEFT",
+                "font": 4,
+                "textWidth": 56,
+                "scrollSpeed": 10,
+                "text": "hello, Divoom",
+                "color": { "red": 255, "green": 255, "blue": 0 },
+                "textAlignment": "LEFT"
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "device-error");
+        assert_eq!(json_body["details"]["error_code"], 1);
+    }
+
+    #[tokio::test]
+    async fn text_request_with_background_color_uploads_it_first() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let mut payload = text_json(4, "hello");
+        payload["background"] = json!({ "color": { "red": 0, "green": 0, "blue": 64 } });
+        let (status, body) =
+            send_json_request(&app, Method::POST, "/draw/text", Some(payload)).await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let captured = requests.lock().unwrap();
+        assert_eq!(
+            commands(&captured),
+            ["Draw/GetHttpGifId", "Draw/SendHttpGif", "Draw/SendHttpText"]
+        );
+        assert_eq!(captured[1]["PicNum"], 1);
+        assert_eq!(captured[2]["TextString"], "hello");
+    }
+
+    #[tokio::test]
+    async fn text_batch_sends_background_image_then_every_item() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text/batch",
+            Some(json!({
+                "background": { "image": png_base64() },
+                "items": [text_json(0, "top"), text_json(1, "bottom")]
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let captured = requests.lock().unwrap();
+        assert_eq!(
+            commands(&captured),
+            [
+                "Draw/GetHttpGifId",
+                "Draw/SendHttpGif",
+                "Draw/SendHttpText",
+                "Draw/SendHttpText"
+            ]
+        );
+        assert_eq!(captured[2]["TextId"], 0);
+        assert_eq!(captured[3]["TextString"], "bottom");
+    }
+
+    #[tokio::test]
+    async fn text_batch_without_background_only_sends_text() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text/batch",
+            Some(json!({ "items": [text_json(2, "solo")] })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(commands(&requests.lock().unwrap()), ["Draw/SendHttpText"]);
+    }
+
+    #[tokio::test]
+    async fn text_batch_rejects_invalid_items_and_background() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text/batch",
+            Some(json!({
+                "background": { "link": "ftp://example.com/a.png" },
+                "items": [text_json(0, "ok"), text_json(30, "bad")]
+            })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert!(json_body["details"]["background"].is_array());
+        assert!(json_body["details"]["items[1].id"].is_array());
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/draw/text/batch",
+            Some(json!({ "items": [] })),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert!(json_body["details"]["items"].is_array());
+        assert!(requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn text_is_not_sent_when_the_background_upload_fails() {
+        let server = MockServer::start_async().await;
+        let text = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Draw/SendHttpText");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+        let mut payload = text_json(4, "hello");
+        payload["background"] = json!({ "color": { "red": 1, "green": 2, "blue": 3 } });
+        let (status, _) = send_json_request(&app, Method::POST, "/draw/text", Some(payload)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        text.assert_calls(0);
+    }
+
+    #[tokio::test]
+    async fn text_slots_are_listed_updated_and_blanked_one_at_a_time() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        for (id, text) in [(7, "seven"), (2, "two")] {
+            let (status, _) =
+                send_json_request(&app, Method::POST, "/draw/text", Some(text_json(id, text)))
+                    .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+        let (status, body) = send_json_request(&app, Method::GET, "/draw/text", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let slots: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(slots[0]["id"], 2);
+        assert_eq!(slots[1]["text"], "seven");
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::PATCH,
+            "/draw/text/7",
+            Some(json!({ "text": "eight", "color": { "red": 0, "green": 255, "blue": 0 } })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        let updated: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(updated["text"], "eight");
+        assert_eq!(updated["font"], 4);
+
+        let (status, _) = send_json_request(&app, Method::DELETE, "/draw/text/2", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        {
+            let captured = requests.lock().unwrap();
+            assert_eq!(captured.len(), 4);
+            assert_eq!(captured[2]["TextId"], 7);
+            assert_eq!(captured[2]["TextString"], "eight");
+            assert_eq!(captured[2]["color"], "#00FF00");
+            assert_eq!(captured[3]["TextId"], 2);
+            assert_eq!(captured[3]["TextString"], "");
+        }
+        let (_, body) = send_json_request(&app, Method::GET, "/draw/text", None).await;
+        let slots: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(slots.as_array().unwrap().len(), 1);
+        assert_eq!(slots[0]["text"], "eight");
+
+        let (status, _) = send_json_request(&app, Method::POST, "/draw/text/clear", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send_json_request(&app, Method::GET, "/draw/text", None).await;
+        assert_eq!(body, "[]");
+    }
+
+    #[tokio::test]
+    async fn text_slot_requests_need_a_known_slot() {
+        let (base_url, requests) = start_pixoo_mock().await;
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let app = build_draw_app(Arc::new(AppState::with_client(client)));
+
+        let (status, body) = send_json_request(&app, Method::DELETE, "/draw/text/3", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "not-found");
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::PATCH,
+            "/draw/text/21",
+            Some(json!({ "text": "x" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["details"]["id"], "id must be between 0 and 20");
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,430 @@
+use crate::pixoo::protocol::{
+    Channel, ChannelIndex, ClockInfo, CloudSource, GetChannel, GetClockInfo, PixooRequest,
+    SetChannel, SetClockSelectId, SetCloudIndex, SetCustomPageIndex, SetEqPosition,
+};
+use crate::state::AppState;
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
+};
+use crate::routes::common::{
+    dispatch_pixoo_command, dispatch_pixoo_query, validation_error_simple, PathParam, ValidatedPath,
+};
+
+const MAX_CUSTOM_PAGE: u8 = 2;
+
+pub fn channel_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(manage_channel))
+        .routes(routes!(manage_channel_clock))
+        .routes(routes!(manage_channel_select))
+        .routes(routes!(manage_channel_face))
+        .routes(routes!(manage_channel_custom_page))
+        .routes(routes!(manage_channel_visualizer))
+        .routes(routes!(manage_channel_cloud))
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageChannel {
+    /// `faces`, `cloud`, `visualizer`, or `custom`; absent when the device
+    /// reports an index the bridge does not know.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    channel: Option<&'static str>,
+    index: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ManageChannelClock {
+    clock_id: i64,
+    brightness: i64,
+}
+
+fn channel_name(channel: Channel) -> &'static str {
+    match channel {
+        Channel::Faces => "faces",
+        Channel::Cloud => "cloud",
+        Channel::Visualizer => "visualizer",
+        Channel::Custom => "custom",
+    }
+}
+
+impl PathParam for Channel {
+    fn allowed_values() -> &'static [&'static str] {
+        &["faces", "cloud", "visualizer", "custom"]
+    }
+}
+
+impl FromStr for Channel {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "faces" => Ok(Self::Faces),
+            "cloud" => Ok(Self::Cloud),
+            "visualizer" => Ok(Self::Visualizer),
+            "custom" => Ok(Self::Custom),
+            _ => Err(()),
+        }
+    }
+}
+
+impl PathParam for CloudSource {
+    fn allowed_values() -> &'static [&'static str] {
+        &["recommended", "favourites", "artists"]
+    }
+}
+
+impl FromStr for CloudSource {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "recommended" => Ok(Self::Recommended),
+            "favourites" => Ok(Self::Favourites),
+            "artists" => Ok(Self::Artists),
+            _ => Err(()),
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/manage/channel",
+    tag = "manage",
+    responses(
+        (status = 200, description = "Channel the device is showing", body = ManageChannel),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel(State(state): State<Arc<AppState>>) -> Response {
+    match dispatch_pixoo_query(&state, &GetChannel).await {
+        Ok(ChannelIndex { select_index }) => axum::Json(ManageChannel {
+            channel: Channel::from_index(select_index).map(channel_name),
+            index: select_index,
+        })
+        .into_response(),
+        Err(err) => err,
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/manage/channel/clock",
+    tag = "manage",
+    responses(
+        (status = 200, description = "Selected clock face", body = ManageChannelClock),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_clock(State(state): State<Arc<AppState>>) -> Response {
+    match dispatch_pixoo_query(&state, &GetClockInfo).await {
+        Ok(ClockInfo {
+            clock_id,
+            brightness,
+        }) => axum::Json(ManageChannelClock {
+            clock_id,
+            brightness,
+        })
+        .into_response(),
+        Err(err) => err,
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/manage/channel/{channel}",
+    tag = "manage",
+    params(("channel" = String, Path, description = "One of: faces, cloud, visualizer, custom")),
+    responses(
+        (status = 200, description = "Channel switched"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_select(
+    State(state): State<Arc<AppState>>,
+    ValidatedPath(channel): ValidatedPath<Channel>,
+) -> Response {
+    switch_channel(&state, &SetChannel { channel }).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/manage/channel/faces/{id}",
+    tag = "manage",
+    params(("id" = u32, Path, description = "Clock face id as listed in the Divoom app")),
+    responses(
+        (status = 200, description = "Clock face shown"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_face(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Response {
+    let Ok(clock_id) = id.parse::<u32>() else {
+        return validation_error_simple("id", "id must be a non-negative integer");
+    };
+
+    switch_channel(&state, &SetClockSelectId { clock_id }).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/manage/channel/custom/{page}",
+    tag = "manage",
+    params(("page" = u8, Path, description = "Custom page 0-2")),
+    responses(
+        (status = 200, description = "Custom page shown"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_custom_page(
+    State(state): State<Arc<AppState>>,
+    Path(page): Path<String>,
+) -> Response {
+    let custom_page_index = match page.parse::<u8>() {
+        Ok(val) if val <= MAX_CUSTOM_PAGE => val,
+        _ => return validation_error_simple("page", "page must be 0, 1, or 2"),
+    };
+
+    switch_channel(&state, &SetCustomPageIndex { custom_page_index }).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/manage/channel/visualizer/{position}",
+    tag = "manage",
+    params(("position" = u32, Path, description = "Visualizer position, counted from 0 in the Divoom app")),
+    responses(
+        (status = 200, description = "Visualizer shown"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_visualizer(
+    State(state): State<Arc<AppState>>,
+    Path(position): Path<String>,
+) -> Response {
+    let Ok(eq_position) = position.parse::<u32>() else {
+        return validation_error_simple("position", "position must be a non-negative integer");
+    };
+
+    switch_channel(&state, &SetEqPosition { eq_position }).await
+}
+
+#[utoipa::path(
+    post,
+    path = "/manage/channel/cloud/{source}",
+    tag = "manage",
+    params(("source" = String, Path, description = "One of: recommended, favourites, artists")),
+    responses(
+        (status = 200, description = "Cloud gallery shown"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+pub async fn manage_channel_cloud(
+    State(state): State<Arc<AppState>>,
+    ValidatedPath(index): ValidatedPath<CloudSource>,
+) -> Response {
+    switch_channel(&state, &SetCloudIndex { index }).await
+}
+
+/// Sends a channel switch. The device drops its text items and animation
+/// when it leaves the animation layer, so the bridge forgets them too.
+async fn switch_channel<R: PixooRequest>(state: &AppState, request: &R) -> Response {
+    let response = dispatch_pixoo_command(state, request).await;
+    if response.status() == StatusCode::OK {
+        state.forget_display();
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::routes::common::testing::send_json_request;
+    use crate::routes::manage::manage_router;
+    use crate::state::AppState;
+    use axum::http::{Method, StatusCode};
+    use axum::Router;
+    use httpmock::{Method as MockMethod, MockServer};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    fn build_manage_app(base_url: &str) -> Router {
+        let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
+        let state = Arc::new(AppState::with_client(client));
+        let (router, _api) = manage_router().with_state(state).split_for_parts();
+        router
+    }
+
+    #[tokio::test]
+    async fn channel_reports_the_selected_channel() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/GetIndex\"");
+            then.status(200).body(r#"{"error_code":0,"SelectIndex":2}"#);
+        });
+
+        let app = build_manage_app(&server.base_url());
+        let (status, body) = send_json_request(&app, Method::GET, "/manage/channel", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body, json!({ "channel": "visualizer", "index": 2 }));
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn channel_clock_reports_the_selected_face() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/GetClockInfo\"");
+            then.status(200)
+                .body(r#"{"error_code":0,"ClockId":182,"Brightness":80}"#);
+        });
+
+        let app = build_manage_app(&server.base_url());
+        let (status, body) =
+            send_json_request(&app, Method::GET, "/manage/channel/clock", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body, json!({ "clockId": 182, "brightness": 80 }));
+    }
+
+    #[tokio::test]
+    async fn channel_select_switches_channel() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/SetIndex\"")
+                .body_includes("\"SelectIndex\":3");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_manage_app(&server.base_url());
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/manage/channel/custom", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn channel_select_rejects_unknown_channel() {
+        let server = MockServer::start_async().await;
+        let app = build_manage_app(&server.base_url());
+        let (status, body) =
+            send_json_request(&app, Method::POST, "/manage/channel/radio", None).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(
+            json_body["details"]["action"]["allowed"],
+            json!(["faces", "cloud", "visualizer", "custom"])
+        );
+    }
+
+    #[tokio::test]
+    async fn channel_face_selects_clock_id() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/SetClockSelectId\"")
+                .body_includes("\"ClockId\":182");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_manage_app(&server.base_url());
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/manage/channel/faces/182", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        mock.assert();
+    }
+
+    #[tokio::test]
+    async fn channel_custom_page_rejects_out_of_range() {
+        let server = MockServer::start_async().await;
+        let app = build_manage_app(&server.base_url());
+        let (status, body) =
+            send_json_request(&app, Method::POST, "/manage/channel/custom/3", None).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let json_body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["details"]["page"], "page must be 0, 1, or 2");
+    }
+
+    #[tokio::test]
+    async fn channel_cloud_and_visualizer_send_their_index() {
+        let server = MockServer::start_async().await;
+        let cloud = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/CloudIndex\"")
+                .body_includes("\"Index\":1");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let visualizer = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Channel/SetEqPosition\"")
+                .body_includes("\"EqPosition\":4");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_manage_app(&server.base_url());
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/manage/channel/cloud/favourites", None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/manage/channel/visualizer/4", None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        cloud.assert();
+        visualizer.assert();
+    }
+}
@@ -1,10 +1,11 @@
-use crate::pixoo::fields::request as req;
-use crate::pixoo::PixooCommand;
+use crate::pixoo::protocol::{
+    Rotation, SetBrightness, SetHighLightMode, SetMirrorMode, SetRotation, SetWhiteBalance,
+    SwitchScreen,
+};
 use crate::state::AppState;
 use axum::extract::{Path, State};
 use axum::response::Response;
 use serde::Deserialize;
-use serde_json::{Map, Value};
 use std::str::FromStr;
 use std::sync::Arc;
 use utoipa::ToSchema;
@@ -32,11 +33,11 @@ pub fn display_router() -> OpenApiRouter<Arc<AppState>> {
 #[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct WhiteBalanceRequest {
     #[validate(range(min = 0, max = 100))]
-    pub red: i64,
+    pub red: u8,
     #[validate(range(min = 0, max = 100))]
-    pub green: i64,
+    pub green: u8,
     #[validate(range(min = 0, max = 100))]
-    pub blue: i64,
+    pub blue: u8,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -47,11 +48,8 @@ pub enum OnOffAction {
 }
 
 impl OnOffAction {
-    pub fn flag_value(&self) -> i32 {
-        match self {
-            Self::On => 1,
-            Self::Off => 0,
-        }
+    pub fn is_on(&self) -> bool {
+        matches!(self, Self::On)
     }
 }
 
@@ -91,10 +89,8 @@ pub async fn manage_display_on(
     State(state): State<Arc<AppState>>,
     ValidatedPath(action): ValidatedPath<OnOffAction>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::ON_OFF.to_string(), Value::from(action.flag_value()));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayPower, args).await
+    let request = SwitchScreen { on: action.is_on() };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
@@ -115,15 +111,12 @@ pub async fn manage_display_brightness(
     State(state): State<Arc<AppState>>,
     Path(value): Path<String>,
 ) -> Response {
-    let brightness_value = match value.parse::<i32>() {
-        Ok(val) if (0..=100).contains(&val) => val,
+    let brightness = match value.parse::<u8>() {
+        Ok(val) if val <= 100 => val,
         _ => return validation_error_simple("value", "value must be an integer between 0 and 100"),
     };
 
-    let mut args = Map::new();
-    args.insert(req::BRIGHTNESS.to_string(), Value::from(brightness_value));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayBrightness, args).await
+    dispatch_pixoo_command(&state, &SetBrightness { brightness }).await
 }
 
 #[utoipa::path(
@@ -144,15 +137,11 @@ pub async fn manage_display_rotation(
     State(state): State<Arc<AppState>>,
     Path(angle): Path<String>,
 ) -> Response {
-    let mode_value = match angle.parse::<i32>() {
-        Ok(val) if [0, 90, 180, 270].contains(&val) => val / 90,
-        _ => return validation_error_simple("angle", "angle must be 0, 90, 180, or 270"),
+    let Some(mode) = angle.parse::<i32>().ok().and_then(Rotation::from_degrees) else {
+        return validation_error_simple("angle", "angle must be 0, 90, 180, or 270");
     };
 
-    let mut args = Map::new();
-    args.insert(req::MODE.to_string(), Value::from(mode_value));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayRotation, args).await
+    dispatch_pixoo_command(&state, &SetRotation { mode }).await
 }
 
 #[utoipa::path(
@@ -173,10 +162,10 @@ pub async fn manage_display_mirror(
     State(state): State<Arc<AppState>>,
     ValidatedPath(action): ValidatedPath<OnOffAction>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::MODE.to_string(), Value::from(action.flag_value()));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayMirror, args).await
+    let request = SetMirrorMode {
+        mode: action.is_on(),
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
@@ -197,10 +186,10 @@ pub async fn manage_display_overclock(
     State(state): State<Arc<AppState>>,
     ValidatedPath(action): ValidatedPath<OnOffAction>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::MODE.to_string(), Value::from(action.flag_value()));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayOverclock, args).await
+    let request = SetHighLightMode {
+        mode: action.is_on(),
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
@@ -221,12 +210,12 @@ pub async fn manage_display_white_balance(
     State(state): State<Arc<AppState>>,
     ValidatedJson(payload): ValidatedJson<WhiteBalanceRequest>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::R_VALUE.to_string(), Value::from(payload.red));
-    args.insert(req::G_VALUE.to_string(), Value::from(payload.green));
-    args.insert(req::B_VALUE.to_string(), Value::from(payload.blue));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageDisplayWhiteBalance, args).await
+    let request = SetWhiteBalance {
+        red: payload.red,
+        green: payload.green,
+        blue: payload.blue,
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[cfg(test)]
@@ -1,21 +1,19 @@
+mod channel;
 mod display;
 mod time;
 mod weather;
 
-use crate::pixoo::client::PixooResponse;
-use crate::pixoo::fields::response as resp;
-use crate::pixoo::PixooCommand;
+use crate::pixoo::protocol::{DeviceSettings, GetAllConf};
 use crate::state::AppState;
 use axum::extract::State;
 use axum::response::{IntoResponse, Response};
 use serde::Serialize;
 use std::sync::Arc;
-use tracing::error;
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
-use super::common::{dispatch_pixoo_query, service_unavailable};
+use super::common::dispatch_pixoo_query;
 use crate::pixoo::error::{DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse};
 
 pub fn manage_router() -> OpenApiRouter<Arc<AppState>> {
@@ -24,6 +22,7 @@ pub fn manage_router() -> OpenApiRouter<Arc<AppState>> {
         .merge(time::time_router())
         .merge(weather::weather_router())
         .merge(display::display_router())
+        .merge(channel::channel_router())
 }
 
 #[derive(Serialize, ToSchema)]
@@ -51,75 +50,21 @@ struct ManageSettings {
 )]
 #[tracing::instrument(skip(state))]
 async fn manage_settings(State(state): State<Arc<AppState>>) -> Response {
-    let response = match dispatch_pixoo_query(&state, PixooCommand::ManageGetSettings).await {
-        Ok(resp) => resp,
-        Err(err) => return err,
-    };
-
-    match map_settings(&response) {
-        Ok(settings) => axum::Json(settings).into_response(),
-        Err(msg) => {
-            error!(error = %msg, "failed to map settings response");
-            service_unavailable()
-        }
+    match dispatch_pixoo_query(&state, &GetAllConf).await {
+        Ok(settings) => axum::Json(map_settings(&settings)).into_response(),
+        Err(err) => err,
     }
 }
 
-fn map_settings(response: &PixooResponse) -> Result<ManageSettings, String> {
-    Ok(ManageSettings {
-        display_on: parsing::flag_bool(response, resp::LIGHT_SWITCH)?,
-        brightness: parsing::parse_i64(response, resp::BRIGHTNESS)?,
-        time_mode: time::time_mode(response)?,
-        rotation_angle: rotation_angle(response)?,
-        mirrored: parsing::flag_bool(response, resp::MIRROR_FLAG)?,
-        temperature_unit: weather::temperature_unit(response)?,
-        current_clock_id: parsing::parse_i64(response, resp::CUR_CLOCK_ID)?,
-    })
-}
-
-fn rotation_angle(response: &PixooResponse) -> Result<i64, String> {
-    let flag = parsing::parse_string(response, resp::ROTATION_FLAG)?;
-    if flag == "0" {
-        return Ok(0);
-    }
-    let rotation = flag
-        .parse::<i64>()
-        .map_err(|err| format!("RotationFlag is not integer: {err}"))?;
-    Ok(rotation * 90)
-}
-
-/// Shared response-parsing helpers used across manage sub-modules.
-pub(crate) mod parsing {
-    use crate::pixoo::client::PixooResponse;
-    use serde_json::Value;
-
-    pub fn parse_string(response: &PixooResponse, key: &str) -> Result<String, String> {
-        response
-            .get(key)
-            .ok_or_else(|| format!("missing field {key}"))
-            .and_then(|value| match value {
-                Value::String(text) => Ok(text.clone()),
-                Value::Number(number) => Ok(number.to_string()),
-                other => Err(format!("unexpected type for {key}: {other}")),
-            })
-    }
-
-    pub fn flag_bool(response: &PixooResponse, key: &str) -> Result<bool, String> {
-        Ok(parse_string(response, key)? == "1")
-    }
-
-    pub fn parse_i64(response: &PixooResponse, key: &str) -> Result<i64, String> {
-        let value = parse_string(response, key)?;
-        value
-            .parse::<i64>()
-            .map_err(|err| format!("{key} is not an integer: {err}"))
-    }
-
-    pub fn parse_f64(response: &PixooResponse, key: &str) -> Result<f64, String> {
-        let value = parse_string(response, key)?;
-        value
-            .parse::<f64>()
-            .map_err(|err| format!("{key} is not a float: {err}"))
+fn map_settings(settings: &DeviceSettings) -> ManageSettings {
+    ManageSettings {
+        display_on: settings.light_switch,
+        brightness: settings.brightness,
+        time_mode: time::time_mode(settings.time_24_flag),
+        rotation_angle: settings.rotation_flag * 90,
+        mirrored: settings.mirror_flag,
+        temperature_unit: weather::temperature_unit(settings.temperature_mode),
+        current_clock_id: settings.cur_clock_id,
     }
 }
 
@@ -1,12 +1,9 @@
-use crate::pixoo::client::PixooResponse;
-use crate::pixoo::fields::{request as req, response as resp};
-use crate::pixoo::PixooCommand;
+use crate::pixoo::protocol::{DeviceTime, GetDeviceTime, SetTime24Flag, SetTimeZone, SetUtc};
 use crate::state::AppState;
 use axum::extract::{Path, State};
 use axum::response::{IntoResponse, Response};
 use chrono::{NaiveDateTime, TimeZone, Utc};
 use serde::Serialize;
-use serde_json::{Map, Value};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{debug, error};
@@ -23,8 +20,6 @@ use crate::routes::common::{
     validation_error_simple,
 };
 
-use super::parsing::{parse_i64, parse_string};
-
 pub fn time_router() -> OpenApiRouter<Arc<AppState>> {
     OpenApiRouter::new()
         .routes(routes!(manage_time, manage_set_time))
@@ -52,7 +47,7 @@ pub struct ManageTime {
 )]
 #[tracing::instrument(skip(state))]
 pub async fn manage_time(State(state): State<Arc<AppState>>) -> Response {
-    let response = match dispatch_pixoo_query(&state, PixooCommand::ManageGetTime).await {
+    let response = match dispatch_pixoo_query(&state, &GetDeviceTime).await {
         Ok(resp) => resp,
         Err(err) => return err,
     };
@@ -89,10 +84,7 @@ pub async fn manage_set_time(State(state): State<Arc<AppState>>) -> Response {
     };
 
     debug!(utc = utc_secs, "setting device UTC clock");
-    let mut args = Map::new();
-    args.insert(req::UTC.to_string(), Value::from(utc_secs));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageSetUtc, args).await
+    dispatch_pixoo_command(&state, &SetUtc { utc: utc_secs }).await
 }
 
 #[utoipa::path(
@@ -117,21 +109,8 @@ pub async fn manage_set_timezone(
         Ok(value) => value,
         Err(message) => return offset_validation_error(&message),
     };
-    // Pixoo expects the opposite sign from what humans call "timezone offset",
-    // so sending +3 requires the command `GMT-3` when interacting with `/post`.
-    let timezone_value = match offset_value {
-        0 => "GMT+0".to_string(),
-        value if value > 0 => format!("GMT-{value}"),
-        value => format!("GMT+{}", value.abs()),
-    };
-
-    let mut args = Map::new();
-    args.insert(
-        req::TIMEZONE_VALUE.to_string(),
-        Value::String(timezone_value),
-    );
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageSetTimezone, args).await
+    let request = SetTimeZone::from_utc_offset(offset_value);
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
@@ -152,25 +131,21 @@ pub async fn manage_set_time_mode(
     State(state): State<Arc<AppState>>,
     Path(mode): Path<String>,
 ) -> Response {
-    let mode_value = match mode.to_ascii_lowercase().as_str() {
-        "12h" => 0,
-        "24h" => 1,
+    let twenty_four = match mode.to_ascii_lowercase().as_str() {
+        "12h" => false,
+        "24h" => true,
         _ => return validation_error_simple("mode", "mode must be '12h' or '24h'"),
     };
 
-    let mut args = Map::new();
-    args.insert(req::MODE.to_string(), Value::from(mode_value));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageSetTimeMode, args).await
+    dispatch_pixoo_command(&state, &SetTime24Flag { mode: twenty_four }).await
 }
 
-pub fn time_mode(response: &PixooResponse) -> Result<String, String> {
-    let flag = parse_string(response, resp::TIME_24_FLAG)?;
-    Ok(if flag == "1" {
+pub fn time_mode(time_24_flag: bool) -> String {
+    if time_24_flag {
         "TWENTY_FOUR".to_string()
     } else {
         "TWELVE".to_string()
-    })
+    }
 }
 
 fn offset_validation_error(message: &str) -> Response {
@@ -202,16 +177,15 @@ fn current_utc_seconds() -> Result<i64, String> {
     Ok(secs)
 }
 
-fn map_time(response: &PixooResponse) -> Result<ManageTime, String> {
-    let utc_secs = parse_i64(response, resp::UTC_TIME)?;
+fn map_time(response: &DeviceTime) -> Result<ManageTime, String> {
+    let utc_secs = response.utc_time;
     let utc_time = Utc
         .timestamp_opt(utc_secs, 0)
         .single()
         .ok_or_else(|| format!("UTCTime {utc_secs} out of range"))?;
     let utc_iso = utc_time.format("%Y-%m-%dT%H:%M:%S").to_string();
 
-    let local_value = parse_string(response, resp::LOCAL_TIME)?;
-    let local_naive = NaiveDateTime::parse_from_str(&local_value, "%Y-%m-%d %H:%M:%S")
+    let local_naive = NaiveDateTime::parse_from_str(&response.local_time, "%Y-%m-%d %H:%M:%S")
         .map_err(|err| format!("LocalTime parse error: {err}"))?;
     let local_iso = local_naive.format("%Y-%m-%dT%H:%M:%S").to_string();
 
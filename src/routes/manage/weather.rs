@@ -1,13 +1,9 @@
-use crate::pixoo::client::PixooResponse;
-use crate::pixoo::fields::{request as req, response as resp};
-use crate::pixoo::PixooCommand;
+use crate::pixoo::protocol::{GetWeatherInfo, SetLocation, SetTemperatureMode, WeatherInfo};
 use crate::state::AppState;
 use axum::extract::{Path, State};
 use axum::response::{IntoResponse, Response};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
 use std::sync::Arc;
-use tracing::error;
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
@@ -17,12 +13,9 @@ use crate::pixoo::error::{
     DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
 };
 use crate::routes::common::{
-    dispatch_pixoo_command, dispatch_pixoo_query, service_unavailable, validation_error_simple,
-    ValidatedJson,
+    dispatch_pixoo_command, dispatch_pixoo_query, validation_error_simple, ValidatedJson,
 };
 
-use super::parsing::{parse_f64, parse_i64, parse_string};
-
 pub fn weather_router() -> OpenApiRouter<Arc<AppState>> {
     OpenApiRouter::new()
         .routes(routes!(manage_weather))
@@ -63,17 +56,9 @@ pub struct LocationRequest {
 )]
 #[tracing::instrument(skip(state))]
 pub async fn manage_weather(State(state): State<Arc<AppState>>) -> Response {
-    let response = match dispatch_pixoo_query(&state, PixooCommand::ManageGetWeather).await {
-        Ok(resp) => resp,
-        Err(err) => return err,
-    };
-
-    match map_weather(&response) {
-        Ok(weather) => axum::Json(weather).into_response(),
-        Err(msg) => {
-            error!(error = %msg, "failed to map weather response");
-            service_unavailable()
-        }
+    match dispatch_pixoo_query(&state, &GetWeatherInfo).await {
+        Ok(response) => axum::Json(map_weather(response)).into_response(),
+        Err(err) => err,
     }
 }
 
@@ -95,17 +80,11 @@ pub async fn manage_set_location(
     State(state): State<Arc<AppState>>,
     ValidatedJson(payload): ValidatedJson<LocationRequest>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(
-        req::LONGITUDE.to_string(),
-        Value::String(payload.longitude.to_string()),
-    );
-    args.insert(
-        req::LATITUDE.to_string(),
-        Value::String(payload.latitude.to_string()),
-    );
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageSetLocation, args).await
+    let request = SetLocation {
+        longitude: payload.longitude.to_string(),
+        latitude: payload.latitude.to_string(),
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
@@ -126,37 +105,33 @@ pub async fn manage_set_temperature_unit(
     State(state): State<Arc<AppState>>,
     Path(unit): Path<String>,
 ) -> Response {
-    let mode_value = match unit.to_ascii_lowercase().as_str() {
-        "celsius" => 0,
-        "fahrenheit" => 1,
+    let fahrenheit = match unit.to_ascii_lowercase().as_str() {
+        "celsius" => false,
+        "fahrenheit" => true,
         _ => return validation_error_simple("unit", "unit must be 'celsius' or 'fahrenheit'"),
     };
 
-    let mut args = Map::new();
-    args.insert(req::MODE.to_string(), Value::from(mode_value));
-
-    dispatch_pixoo_command(&state, PixooCommand::ManageSetTemperatureUnit, args).await
+    dispatch_pixoo_command(&state, &SetTemperatureMode { mode: fahrenheit }).await
 }
 
-pub fn temperature_unit(response: &PixooResponse) -> Result<String, String> {
-    let flag = parse_string(response, resp::TEMPERATURE_MODE)?;
-    Ok(if flag == "1" {
+pub fn temperature_unit(temperature_mode: bool) -> String {
+    if temperature_mode {
         "FAHRENHEIT".to_string()
     } else {
         "CELSIUS".to_string()
-    })
+    }
 }
 
-fn map_weather(response: &PixooResponse) -> Result<ManageWeather, String> {
-    Ok(ManageWeather {
-        weather_string: parse_string(response, resp::WEATHER)?,
-        current_temperature: parse_f64(response, resp::CUR_TEMP)?,
-        minimal_temperature: parse_f64(response, resp::MIN_TEMP)?,
-        maximal_temperature: parse_f64(response, resp::MAX_TEMP)?,
-        pressure: parse_i64(response, resp::PRESSURE)?,
-        humidity: parse_i64(response, resp::HUMIDITY)?,
-        wind_speed: parse_f64(response, resp::WIND_SPEED)?,
-    })
+fn map_weather(response: WeatherInfo) -> ManageWeather {
+    ManageWeather {
+        weather_string: response.weather,
+        current_temperature: response.cur_temp,
+        minimal_temperature: response.min_temp,
+        maximal_temperature: response.max_temp,
+        pressure: response.pressure,
+        humidity: response.humidity,
+        wind_speed: response.wind_speed,
+    }
 }
 
 #[cfg(test)]
@@ -4,6 +4,9 @@ mod manage;
 mod system;
 mod tools;
 
+pub use draw::{DrawConflictPolicy, DrawGate, DrawJobs, LiveRemote, Notifications, TextSlots};
+pub use tools::{Scoreboard, TimerTracker};
+
 use axum::http::StatusCode;
 use axum::response::Response;
 use std::sync::Arc;
@@ -1,27 +1,40 @@
-use crate::pixoo::{map_pixoo_error, PixooCommand};
+use crate::events::{BridgeEvent, EventType};
+use crate::pixoo::map_pixoo_error;
+use crate::pixoo::protocol::Reboot;
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Response},
+    extract::{Query, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
-use serde::Serialize;
-use serde_json::{json, Map};
+use futures_util::stream::{self, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
 use std::sync::Arc;
-use tracing::{debug, error};
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tracing::{debug, error, warn};
 use utoipa::ToSchema;
 use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 
-use super::common::dispatch_pixoo_command;
-use crate::pixoo::error::{DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse};
+use super::common::{dispatch_pixoo_command, validation_error_simple};
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
+};
 
+use crate::metrics::metrics;
 use crate::state::AppState;
 
 pub fn system_router() -> OpenApiRouter<Arc<AppState>> {
     OpenApiRouter::new()
         .routes(routes!(health))
         .routes(routes!(reboot))
+        .routes(routes!(prometheus_metrics))
+        .routes(routes!(event_stream))
 }
 
 /// `200 OK` body for `/health`.
@@ -77,19 +90,121 @@ async fn health(State(state): State<Arc<AppState>>) -> Response {
 )]
 #[tracing::instrument(skip(state))]
 async fn reboot(State(state): State<Arc<AppState>>) -> Response {
-    dispatch_pixoo_command(&state, PixooCommand::SystemReboot, Map::new()).await
+    let response = dispatch_pixoo_command(&state, &Reboot).await;
+    if response.status() == StatusCode::OK {
+        state.forget_display();
+    }
+    response
+}
+
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "system",
+    responses(
+        (status = 200, description = "Metrics in the Prometheus text exposition format", content_type = "text/plain; version=0.0.4", body = String)
+    )
+)]
+async fn prometheus_metrics() -> Response {
+    (
+        [(CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+        metrics().render(),
+    )
+        .into_response()
+}
+
+#[derive(Debug, Deserialize)]
+struct EventStreamQuery {
+    types: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "system",
+    params(
+        ("types" = Option<String>, Query, description = "Comma-separated event types to stream (default: all)", example = "command,draw-completed")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events stream of bridge events; each SSE `event` is the event type and `data` the JSON event", content_type = "text/event-stream", body = String),
+        (status = 400, response = ValidationErrorResponse)
+    )
+)]
+async fn event_stream(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventStreamQuery>,
+) -> Response {
+    let types = match query.types.as_deref().map(parse_event_types).transpose() {
+        Ok(types) => types,
+        Err(message) => return validation_error_simple("types", &message),
+    };
+    Sse::new(sse_events(state.events.subscribe(), types))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn parse_event_types(raw: &str) -> Result<Vec<EventType>, String> {
+    raw.split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Turns bus events into SSE events until the bus closes. A subscriber that
+/// falls behind skips the events it missed rather than disconnecting.
+fn sse_events(
+    receiver: Receiver<Arc<BridgeEvent>>,
+    types: Option<Vec<EventType>>,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(receiver, move |mut receiver| {
+        let types = types.clone();
+        async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(skipped)) => {
+                        warn!(
+                            skipped,
+                            "Event stream client fell behind; events were dropped"
+                        );
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                };
+                let event_type = event.kind.event_type();
+                if types
+                    .as_ref()
+                    .is_some_and(|types| !types.contains(&event_type))
+                {
+                    continue;
+                }
+                match Event::default()
+                    .event(event_type.as_str())
+                    .id(event.id.clone())
+                    .json_data(event.as_ref())
+                {
+                    Ok(sse) => return Some((Ok(sse), receiver)),
+                    Err(err) => warn!(error = %err, "Failed to serialize event"),
+                }
+            }
+        }
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::system_router;
     use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::request_tracing::{propagate, RequestId};
     use crate::state::AppState;
     use axum::body::{to_bytes, Body};
     use axum::http::{Method, Request, StatusCode};
+    use axum::middleware::from_fn;
     use axum::Router;
+    use futures_util::StreamExt;
     use httpmock::{Method as MockMethod, MockServer};
     use std::sync::Arc;
+    use std::time::Duration;
     use tower::ServiceExt;
 
     fn build_system_app(state: Arc<AppState>) -> Router {
@@ -233,4 +348,86 @@ mod tests {
 
         assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
     }
+
+    #[tokio::test]
+    async fn metrics_endpoint_exposes_prometheus_text() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let app = build_system_app(system_state(
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client"),
+            false,
+        ));
+
+        let (status, _) = send_request(&app, Method::POST, "/reboot").await;
+        assert_eq!(status, StatusCode::OK);
+
+        let req = Request::builder()
+            .method(Method::GET)
+            .uri("/metrics")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(req).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers()["content-type"]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"));
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains("pixoo_bridge_pixoo_commands_total{command=\"Device/SysReboot\"}"));
+    }
+
+    #[tokio::test]
+    async fn events_stream_commands_with_request_id() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let app = build_system_app(system_state(
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client"),
+            false,
+        ))
+        .layer(from_fn(propagate));
+
+        let (status, _) = send_request(&app, Method::GET, "/events?types=nope").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let req = Request::builder()
+            .uri("/events?types=command")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(req).await.expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+        let mut body = response.into_body().into_data_stream();
+
+        let request_id = RequestId::new();
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/reboot")
+            .header("X-Request-Id", request_id.header_value())
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(
+            app.clone().oneshot(req).await.unwrap().status(),
+            StatusCode::OK
+        );
+
+        let chunk = tokio::time::timeout(Duration::from_secs(2), body.next())
+            .await
+            .expect("event before timeout")
+            .expect("stream open")
+            .expect("chunk");
+        let chunk = String::from_utf8_lossy(&chunk);
+        assert!(chunk.starts_with("event: command\n"), "{chunk}");
+        assert!(chunk.contains(r#""command":"Device/SysReboot""#), "{chunk}");
+        assert!(
+            chunk.contains(&format!(r#""request_id":"{request_id}""#)),
+            "{chunk}"
+        );
+    }
 }
@@ -1,9 +1,11 @@
-use crate::pixoo::fields::request as req;
-use crate::pixoo::PixooCommand;
+use crate::pixoo::melody::{self, Melody};
+use crate::pixoo::protocol::{
+    PixooRequest, PlayBuzzer, SetNoiseStatus, SetStopwatch, StopwatchStatus,
+};
 use axum::extract::State;
-use axum::response::Response;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
 use serde::Deserialize;
-use serde_json::{Map, Value};
 use std::str::FromStr;
 use std::sync::Arc;
 use utoipa::ToSchema;
@@ -11,36 +13,56 @@ use utoipa_axum::router::OpenApiRouter;
 use utoipa_axum::routes;
 use validator::Validate;
 
-use super::common::{dispatch_pixoo_command, PathParam, ValidatedJson, ValidatedPath};
+use super::common::{
+    dispatch_pixoo_command, pixoo_error_response, PathParam, ValidatedJson, ValidatedPath,
+};
 use crate::pixoo::error::{
     DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
 };
 
 use crate::state::AppState;
 
+mod scoreboard;
+mod timer;
+
+pub use scoreboard::Scoreboard;
+pub use timer::TimerTracker;
+
 pub fn tool_router() -> OpenApiRouter<Arc<AppState>> {
     OpenApiRouter::new()
-        .routes(routes!(timer_start))
-        .routes(routes!(timer_stop))
+        .merge(timer::timer_router())
         .routes(routes!(stopwatch))
-        .routes(routes!(scoreboard))
+        .merge(scoreboard::scoreboard_router())
         .routes(routes!(soundmeter))
+        .routes(routes!(buzzer))
+        .routes(routes!(buzzer_melody))
 }
 
+/// Buzzer pattern in milliseconds; the defaults beep twice.
 #[derive(Debug, Deserialize, Validate, ToSchema)]
-struct TimerRequest {
-    #[validate(range(min = 0, max = 59))]
-    minute: u32,
-    #[validate(range(min = 0, max = 59))]
-    second: u32,
+#[allow(clippy::struct_field_names)] // mirrors the device's three timings
+struct BuzzerRequest {
+    #[serde(default = "default_buzzer_active_time")]
+    #[validate(range(min = 1, max = 10_000))]
+    active_time: u32,
+    #[serde(default = "default_buzzer_off_time")]
+    #[validate(range(max = 10_000))]
+    off_time: u32,
+    #[serde(default = "default_buzzer_total_time")]
+    #[validate(range(min = 1, max = 60_000))]
+    total_time: u32,
 }
 
-#[derive(Debug, Deserialize, Validate, ToSchema)]
-struct ScoreboardRequest {
-    #[validate(range(min = 0, max = 999))]
-    blue_score: u16,
-    #[validate(range(min = 0, max = 999))]
-    red_score: u16,
+fn default_buzzer_active_time() -> u32 {
+    500
+}
+
+fn default_buzzer_off_time() -> u32 {
+    500
+}
+
+fn default_buzzer_total_time() -> u32 {
+    1500
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -52,11 +74,11 @@ enum StopwatchAction {
 }
 
 impl StopwatchAction {
-    fn status(&self) -> u8 {
+    fn status(&self) -> StopwatchStatus {
         match self {
-            Self::Start => 1,
-            Self::Stop => 0,
-            Self::Reset => 2,
+            Self::Start => StopwatchStatus::Start,
+            Self::Stop => StopwatchStatus::Stop,
+            Self::Reset => StopwatchStatus::Reset,
         }
     }
 }
@@ -88,11 +110,8 @@ enum SoundmeterAction {
 }
 
 impl SoundmeterAction {
-    fn status(&self) -> u8 {
-        match self {
-            Self::Start => 1,
-            Self::Stop => 0,
-        }
+    fn status(&self) -> bool {
+        matches!(self, Self::Start)
     }
 }
 
@@ -114,58 +133,43 @@ impl FromStr for SoundmeterAction {
     }
 }
 
-#[utoipa::path(
-    post,
-    path = "/tools/timer/start",
-    tag = "tools",
-    request_body = TimerRequest,
-    responses(
-        (status = 200, description = "Timer started"),
-        (status = 400, response = ValidationErrorResponse),
-        (status = 502, response = DeviceUnreachableResponse),
-        (status = 503, response = DeviceErrorResponse),
-        (status = 504, response = DeviceTimeoutResponse)
-    )
-)]
-#[tracing::instrument(skip(state, payload))]
-async fn timer_start(
-    State(state): State<Arc<AppState>>,
-    ValidatedJson(payload): ValidatedJson<TimerRequest>,
-) -> Response {
-    let mut args = Map::new();
-    args.insert(req::MINUTE.to_string(), Value::from(payload.minute));
-    args.insert(req::SECOND.to_string(), Value::from(payload.second));
-    args.insert(req::STATUS.to_string(), Value::from(1));
-
-    dispatch_pixoo_command(&state, PixooCommand::ToolsTimer, args).await
+impl PathParam for Melody {
+    fn allowed_values() -> &'static [&'static str] {
+        Melody::NAMES
+    }
 }
 
 #[utoipa::path(
     post,
-    path = "/tools/timer/stop",
+    path = "/tools/stopwatch/{action}",
     tag = "tools",
+    params(("action" = String, Path, description = "One of: start, stop, reset")),
     responses(
-        (status = 200, description = "Timer stopped"),
+        (status = 200, description = "Stopwatch action applied"),
+        (status = 400, response = ValidationErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
         (status = 503, response = DeviceErrorResponse),
         (status = 504, response = DeviceTimeoutResponse)
     )
 )]
 #[tracing::instrument(skip(state))]
-async fn timer_stop(State(state): State<Arc<AppState>>) -> Response {
-    let mut args = Map::new();
-    args.insert(req::STATUS.to_string(), Value::from(0));
-
-    dispatch_pixoo_command(&state, PixooCommand::ToolsTimer, args).await
+async fn stopwatch(
+    State(state): State<Arc<AppState>>,
+    ValidatedPath(action): ValidatedPath<StopwatchAction>,
+) -> Response {
+    let request = SetStopwatch {
+        status: action.status(),
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
     post,
-    path = "/tools/stopwatch/{action}",
+    path = "/tools/soundmeter/{action}",
     tag = "tools",
-    params(("action" = String, Path, description = "One of: start, stop, reset")),
+    params(("action" = String, Path, description = "One of: start, stop")),
     responses(
-        (status = 200, description = "Stopwatch action applied"),
+        (status = 200, description = "Sound meter action applied"),
         (status = 400, response = ValidationErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
         (status = 503, response = DeviceErrorResponse),
@@ -173,23 +177,23 @@ async fn timer_stop(State(state): State<Arc<AppState>>) -> Response {
     )
 )]
 #[tracing::instrument(skip(state))]
-async fn stopwatch(
+async fn soundmeter(
     State(state): State<Arc<AppState>>,
-    ValidatedPath(action): ValidatedPath<StopwatchAction>,
+    ValidatedPath(action): ValidatedPath<SoundmeterAction>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::STATUS.to_string(), Value::from(action.status()));
-
-    dispatch_pixoo_command(&state, PixooCommand::ToolsStopwatch, args).await
+    let request = SetNoiseStatus {
+        noise_status: action.status(),
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
     post,
-    path = "/tools/scoreboard",
+    path = "/tools/buzzer",
     tag = "tools",
-    request_body = ScoreboardRequest,
+    request_body = BuzzerRequest,
     responses(
-        (status = 200, description = "Scoreboard updated"),
+        (status = 200, description = "Buzzer played"),
         (status = 400, response = ValidationErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
         (status = 503, response = DeviceErrorResponse),
@@ -197,24 +201,25 @@ async fn stopwatch(
     )
 )]
 #[tracing::instrument(skip(state, payload))]
-async fn scoreboard(
+async fn buzzer(
     State(state): State<Arc<AppState>>,
-    ValidatedJson(payload): ValidatedJson<ScoreboardRequest>,
+    ValidatedJson(payload): ValidatedJson<BuzzerRequest>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::BLUE_SCORE.to_string(), Value::from(payload.blue_score));
-    args.insert(req::RED_SCORE.to_string(), Value::from(payload.red_score));
-
-    dispatch_pixoo_command(&state, PixooCommand::ToolsScoreboard, args).await
+    let request = PlayBuzzer {
+        active_time_in_cycle: payload.active_time,
+        off_time_in_cycle: payload.off_time,
+        play_total_time: payload.total_time,
+    };
+    dispatch_pixoo_command(&state, &request).await
 }
 
 #[utoipa::path(
     post,
-    path = "/tools/soundmeter/{action}",
+    path = "/tools/buzzer/melody/{name}",
     tag = "tools",
-    params(("action" = String, Path, description = "One of: start, stop")),
+    params(("name" = String, Path, description = "One of: beep, double, chime, alarm, success, failure")),
     responses(
-        (status = 200, description = "Sound meter action applied"),
+        (status = 200, description = "Melody played"),
         (status = 400, response = ValidationErrorResponse),
         (status = 502, response = DeviceUnreachableResponse),
         (status = 503, response = DeviceErrorResponse),
@@ -222,14 +227,14 @@ async fn scoreboard(
     )
 )]
 #[tracing::instrument(skip(state))]
-async fn soundmeter(
+async fn buzzer_melody(
     State(state): State<Arc<AppState>>,
-    ValidatedPath(action): ValidatedPath<SoundmeterAction>,
+    ValidatedPath(name): ValidatedPath<Melody>,
 ) -> Response {
-    let mut args = Map::new();
-    args.insert(req::NOISE_STATUS.to_string(), Value::from(action.status()));
-
-    dispatch_pixoo_command(&state, PixooCommand::ToolsSoundMeter, args).await
+    match melody::play(&state.pixoo_client, name).await {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(err) => pixoo_error_response(&PlayBuzzer::COMMAND, &err),
+    }
 }
 
 #[cfg(test)]
@@ -244,24 +249,24 @@ mod tests {
     use serde_json::{json, Value};
     use std::sync::Arc;
 
-    fn build_tool_app(state: Arc<AppState>) -> Router {
+    pub(super) fn build_tool_app(state: Arc<AppState>) -> Router {
         let (router, _api) = tool_router().with_state(state).split_for_parts();
         router
     }
 
-    fn assert_validation_failed(body: &str) -> Value {
+    pub(super) fn assert_validation_failed(body: &str) -> Value {
         let parsed: Value = serde_json::from_str(body).unwrap();
         assert_eq!(parsed["error_kind"], "validation");
         parsed
     }
 
-    fn tool_state_with_client(base_url: &str) -> Arc<AppState> {
+    pub(super) fn tool_state_with_client(base_url: &str) -> Arc<AppState> {
         let client = PixooClient::new(base_url, PixooClientConfig::default()).expect("client");
         Arc::new(AppState::with_client(client))
     }
 
     #[tokio::test]
-    async fn timer_start_succeeds() {
+    async fn stopwatch_invalid_action() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
@@ -269,20 +274,20 @@ mod tests {
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(
-            &app,
-            Method::POST,
-            "/tools/timer/start",
-            Some(json!({"minute": 1, "second": 0})),
-        )
-        .await;
+        let (status, body) =
+            send_json_request(&app, Method::POST, "/tools/stopwatch/fly", None).await;
 
-        assert_eq!(status, StatusCode::OK);
-        assert!(body.is_empty());
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body_json = assert_validation_failed(&body);
+        assert_eq!(body_json["details"]["action"]["provided"], "fly");
+        let allowed = body_json["details"]["action"]["allowed"]
+            .as_array()
+            .unwrap();
+        assert_eq!(allowed.len(), 3);
     }
 
     #[tokio::test]
-    async fn timer_start_rejects_invalid_minute() {
+    async fn soundmeter_start_succeeds() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
@@ -290,21 +295,14 @@ mod tests {
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(
-            &app,
-            Method::POST,
-            "/tools/timer/start",
-            Some(json!({"minute": 60, "second": 0})),
-        )
-        .await;
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/tools/soundmeter/start", None).await;
 
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        let body_json = assert_validation_failed(&body);
-        assert!(body_json["details"]["minute"].is_array());
+        assert_eq!(status, StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn timer_stop_succeeds() {
+    async fn soundmeter_stop_succeeds() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
@@ -312,14 +310,14 @@ mod tests {
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(&app, Method::POST, "/tools/timer/stop", None).await;
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/tools/soundmeter/stop", None).await;
 
         assert_eq!(status, StatusCode::OK);
-        assert!(body.is_empty());
     }
 
     #[tokio::test]
-    async fn stopwatch_invalid_action() {
+    async fn soundmeter_invalid_action() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
@@ -328,7 +326,7 @@ mod tests {
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
         let (status, body) =
-            send_json_request(&app, Method::POST, "/tools/stopwatch/fly", None).await;
+            send_json_request(&app, Method::POST, "/tools/soundmeter/fly", None).await;
 
         assert_eq!(status, StatusCode::BAD_REQUEST);
         let body_json = assert_validation_failed(&body);
@@ -336,11 +334,11 @@ mod tests {
         let allowed = body_json["details"]["action"]["allowed"]
             .as_array()
             .unwrap();
-        assert_eq!(allowed.len(), 3);
+        assert_eq!(allowed.len(), 2);
     }
 
     #[tokio::test]
-    async fn scoreboard_rejects_out_of_range_scores() {
+    async fn stopwatch_stop_succeeds() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
@@ -348,142 +346,97 @@ mod tests {
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(
-            &app,
-            Method::POST,
-            "/tools/scoreboard",
-            Some(json!({"blue_score": 1000, "red_score": 0})),
-        )
-        .await;
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/tools/stopwatch/stop", None).await;
 
-        assert_eq!(status, StatusCode::BAD_REQUEST);
-        let body_json = assert_validation_failed(&body);
-        assert!(body_json["details"]["blue_score"].is_array());
+        assert_eq!(status, StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn scoreboard_pixoo_error_returns_service_unavailable() {
+    async fn stopwatch_reset_succeeds() {
         let server = MockServer::start_async().await;
         server.mock(|when, then| {
             when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":1}"#);
+            then.status(200).body(r#"{"error_code":0}"#);
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(
-            &app,
-            Method::POST,
-            "/tools/scoreboard",
-            Some(json!({"blue_score": 100, "red_score": 90})),
-        )
-        .await;
+        let (status, _) =
+            send_json_request(&app, Method::POST, "/tools/stopwatch/reset", None).await;
 
-        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
-        let json_body: serde_json::Value = serde_json::from_str(&body).unwrap();
-        assert_eq!(json_body["error_kind"], "device-error");
-        assert_eq!(json_body["error_status"], 503);
-        assert_eq!(json_body["details"]["error_code"], 1);
+        assert_eq!(status, StatusCode::OK);
     }
 
     #[tokio::test]
-    async fn scoreboard_accepts_valid_scores() {
+    async fn buzzer_applies_defaults() {
         let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
+        let mock = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Device/PlayBuzzer\"")
+                .body_includes("\"ActiveTimeInCycle\":200")
+                .body_includes("\"OffTimeInCycle\":500")
+                .body_includes("\"PlayTotalTime\":1500");
             then.status(200).body(r#"{"error_code":0}"#);
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, body) = send_json_request(
+        let (status, _) = send_json_request(
             &app,
             Method::POST,
-            "/tools/scoreboard",
-            Some(json!({"blue_score": 12, "red_score": 9})),
+            "/tools/buzzer",
+            Some(json!({"active_time": 200})),
         )
         .await;
 
         assert_eq!(status, StatusCode::OK);
-        assert!(body.is_empty());
+        mock.assert();
     }
 
     #[tokio::test]
-    async fn soundmeter_start_succeeds() {
+    async fn buzzer_rejects_long_total_time() {
         let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":0}"#);
-        });
-
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, _) =
-            send_json_request(&app, Method::POST, "/tools/soundmeter/start", None).await;
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/buzzer",
+            Some(json!({"total_time": 60_001})),
+        )
+        .await;
 
-        assert_eq!(status, StatusCode::OK);
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body_json = assert_validation_failed(&body);
+        assert!(body_json["details"]["total_time"].is_array());
     }
 
     #[tokio::test]
-    async fn soundmeter_stop_succeeds() {
+    async fn buzzer_melody_plays_named_melody() {
         let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
+        let mock = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("\"Command\":\"Device/PlayBuzzer\"");
             then.status(200).body(r#"{"error_code":0}"#);
         });
 
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
         let (status, _) =
-            send_json_request(&app, Method::POST, "/tools/soundmeter/stop", None).await;
+            send_json_request(&app, Method::POST, "/tools/buzzer/melody/success", None).await;
 
         assert_eq!(status, StatusCode::OK);
+        mock.assert_calls(2);
     }
 
     #[tokio::test]
-    async fn soundmeter_invalid_action() {
+    async fn buzzer_melody_rejects_unknown_name() {
         let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":0}"#);
-        });
-
         let app = build_tool_app(tool_state_with_client(&server.base_url()));
         let (status, body) =
-            send_json_request(&app, Method::POST, "/tools/soundmeter/fly", None).await;
+            send_json_request(&app, Method::POST, "/tools/buzzer/melody/fanfare", None).await;
 
         assert_eq!(status, StatusCode::BAD_REQUEST);
         let body_json = assert_validation_failed(&body);
-        assert_eq!(body_json["details"]["action"]["provided"], "fly");
-        let allowed = body_json["details"]["action"]["allowed"]
-            .as_array()
-            .unwrap();
-        assert_eq!(allowed.len(), 2);
-    }
-
-    #[tokio::test]
-    async fn stopwatch_stop_succeeds() {
-        let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":0}"#);
-        });
-
-        let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, _) =
-            send_json_request(&app, Method::POST, "/tools/stopwatch/stop", None).await;
-
-        assert_eq!(status, StatusCode::OK);
-    }
-
-    #[tokio::test]
-    async fn stopwatch_reset_succeeds() {
-        let server = MockServer::start_async().await;
-        server.mock(|when, then| {
-            when.method(MockMethod::POST).path("/post");
-            then.status(200).body(r#"{"error_code":0}"#);
-        });
-
-        let app = build_tool_app(tool_state_with_client(&server.base_url()));
-        let (status, _) =
-            send_json_request(&app, Method::POST, "/tools/stopwatch/reset", None).await;
-
-        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body_json["details"]["action"]["provided"], "fanfare");
     }
 }
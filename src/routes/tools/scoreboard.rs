@@ -0,0 +1,454 @@
+//! Scoreboard scores kept by the bridge.
+//!
+//! `Tools/SetScoreBoard` only takes absolute scores, so the bridge remembers
+//! the scores it sent last and computes increments, decrements, and resets
+//! from them. Both scores are 0 when the bridge starts, whatever the device
+//! shows at that point.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use validator::Validate;
+
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, PixooError,
+    ValidationErrorResponse,
+};
+use crate::pixoo::protocol::{PixooRequest, SetScoreboard};
+use crate::pixoo::PixooClient;
+use crate::routes::common::{pixoo_error_response, ValidatedJson};
+use crate::state::AppState;
+
+const MAX_SCORE: u16 = 999;
+
+pub(super) fn scoreboard_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(scoreboard_status, scoreboard))
+        .routes(routes!(scoreboard_increment))
+        .routes(routes!(scoreboard_decrement))
+        .routes(routes!(scoreboard_reset))
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+struct ScoreboardRequest {
+    #[validate(range(min = 0, max = 999))]
+    blue_score: u16,
+    #[validate(range(min = 0, max = 999))]
+    red_score: u16,
+}
+
+/// Points to add to or take from one team's score. Scores stay between 0
+/// and 999.
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+struct ScoreChange {
+    team: Team,
+    #[serde(default = "default_amount")]
+    #[validate(range(min = 1, max = 999))]
+    amount: u16,
+}
+
+fn default_amount() -> u16 {
+    1
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum Team {
+    Blue,
+    Red,
+}
+
+/// Scores the bridge sent to the device last.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct Scores {
+    blue_score: u16,
+    red_score: u16,
+}
+
+impl Scores {
+    /// Adds `delta` to `team`'s score, clamped to `0..=999`.
+    fn add(self, team: &Team, delta: i32) -> Self {
+        let clamp = |score: u16| {
+            let next = (i32::from(score) + delta).clamp(0, i32::from(MAX_SCORE));
+            u16::try_from(next).unwrap_or(MAX_SCORE)
+        };
+        match team {
+            Team::Blue => Self {
+                blue_score: clamp(self.blue_score),
+                ..self
+            },
+            Team::Red => Self {
+                red_score: clamp(self.red_score),
+                ..self
+            },
+        }
+    }
+}
+
+/// The current scores. Updates hold the lock while the device is asked, so
+/// concurrent increments apply one after the other.
+#[derive(Clone, Default)]
+pub struct Scoreboard {
+    scores: Arc<Mutex<Scores>>,
+}
+
+impl Scoreboard {
+    /// Sends the scores `update` computes from the current ones and keeps
+    /// them once the device accepted them.
+    async fn update(
+        &self,
+        client: &PixooClient,
+        update: impl FnOnce(Scores) -> Scores,
+    ) -> Result<Scores, PixooError> {
+        let mut scores = self.scores.lock().await;
+        let next = update(*scores);
+        client
+            .execute(&SetScoreboard {
+                blue_score: next.blue_score,
+                red_score: next.red_score,
+            })
+            .await?;
+        *scores = next;
+        Ok(next)
+    }
+
+    pub async fn scores(&self) -> Scores {
+        *self.scores.lock().await
+    }
+}
+
+async fn update_scores(state: &AppState, update: impl FnOnce(Scores) -> Scores) -> Response {
+    match state.scoreboard.update(&state.pixoo_client, update).await {
+        Ok(scores) => axum::Json(scores).into_response(),
+        Err(err) => pixoo_error_response(&SetScoreboard::COMMAND, &err),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/tools/scoreboard",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Scores the bridge sent last", body = Scores)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn scoreboard_status(State(state): State<Arc<AppState>>) -> Response {
+    axum::Json(state.scoreboard.scores().await).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/scoreboard",
+    tag = "tools",
+    request_body = ScoreboardRequest,
+    responses(
+        (status = 200, description = "Scoreboard updated"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn scoreboard(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<ScoreboardRequest>,
+) -> Response {
+    let scores = Scores {
+        blue_score: payload.blue_score,
+        red_score: payload.red_score,
+    };
+    match state
+        .scoreboard
+        .update(&state.pixoo_client, |_| scores)
+        .await
+    {
+        Ok(_) => StatusCode::OK.into_response(),
+        Err(err) => pixoo_error_response(&SetScoreboard::COMMAND, &err),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/scoreboard/increment",
+    tag = "tools",
+    request_body = ScoreChange,
+    responses(
+        (status = 200, description = "Score raised; returns both scores", body = Scores),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn scoreboard_increment(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<ScoreChange>,
+) -> Response {
+    update_scores(&state, |scores| {
+        scores.add(&payload.team, i32::from(payload.amount))
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/scoreboard/decrement",
+    tag = "tools",
+    request_body = ScoreChange,
+    responses(
+        (status = 200, description = "Score lowered; returns both scores", body = Scores),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn scoreboard_decrement(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<ScoreChange>,
+) -> Response {
+    update_scores(&state, |scores| {
+        scores.add(&payload.team, -i32::from(payload.amount))
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/scoreboard/reset",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Both scores set to 0", body = Scores),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn scoreboard_reset(State(state): State<Arc<AppState>>) -> Response {
+    update_scores(&state, |_| Scores::default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_validation_failed, build_tool_app, tool_state_with_client};
+    use crate::routes::common::testing::send_json_request;
+    use axum::http::{Method, StatusCode};
+    use httpmock::{Method as MockMethod, MockServer};
+    use serde_json::{json, Value};
+
+    #[tokio::test]
+    async fn scoreboard_rejects_out_of_range_scores() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard",
+            Some(json!({"blue_score": 1000, "red_score": 0})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body_json = assert_validation_failed(&body);
+        assert!(body_json["details"]["blue_score"].is_array());
+    }
+
+    #[tokio::test]
+    async fn scoreboard_pixoo_error_returns_service_unavailable() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard",
+            Some(json!({"blue_score": 100, "red_score": 90})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let json_body: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(json_body["error_kind"], "device-error");
+        assert_eq!(json_body["error_status"], 503);
+        assert_eq!(json_body["details"]["error_code"], 1);
+    }
+
+    #[tokio::test]
+    async fn scoreboard_accepts_valid_scores() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard",
+            Some(json!({"blue_score": 12, "red_score": 9})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scoreboard_increments_and_decrements_tracked_scores() {
+        let server = MockServer::start_async().await;
+        let set_12_9 = server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post").json_body(
+                json!({"Command": "Tools/SetScoreBoard", "BlueScore": 12, "RedScore": 9}),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let set_12_10 = server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post").json_body(
+                json!({"Command": "Tools/SetScoreBoard", "BlueScore": 12, "RedScore": 10}),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let set_0_10 = server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post").json_body(
+                json!({"Command": "Tools/SetScoreBoard", "BlueScore": 0, "RedScore": 10}),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/scoreboard", None).await;
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 0, "red_score": 0})
+        );
+
+        send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard",
+            Some(json!({"blue_score": 12, "red_score": 9})),
+        )
+        .await;
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard/increment",
+            Some(json!({"team": "red"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 12, "red_score": 10})
+        );
+
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard/decrement",
+            Some(json!({"team": "blue", "amount": 50})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 0, "red_score": 10})
+        );
+
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/scoreboard", None).await;
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 0, "red_score": 10})
+        );
+        set_12_9.assert_calls(1);
+        set_12_10.assert_calls(1);
+        set_0_10.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn scoreboard_keeps_scores_when_device_rejects_update() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard/increment",
+            Some(json!({"team": "blue", "amount": 3})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/scoreboard", None).await;
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 0, "red_score": 0})
+        );
+    }
+
+    #[tokio::test]
+    async fn scoreboard_reset_and_invalid_changes() {
+        let server = MockServer::start_async().await;
+        let reset = server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post").json_body(
+                json!({"Command": "Tools/SetScoreBoard", "BlueScore": 0, "RedScore": 0}),
+            );
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard/increment",
+            Some(json!({"team": "blue", "amount": 0})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body_json = assert_validation_failed(&body);
+        assert!(body_json["details"]["amount"].is_array());
+
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/scoreboard/decrement",
+            Some(json!({"team": "green"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, body) =
+            send_json_request(&app, Method::POST, "/tools/scoreboard/reset", None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"blue_score": 0, "red_score": 0})
+        );
+        reset.assert_calls(1);
+    }
+}
@@ -0,0 +1,510 @@
+//! Countdown timer tracked by the bridge.
+//!
+//! The device counts down on its own and cannot be asked how much time is
+//! left, so the bridge mirrors every timer it starts. When the mirrored timer
+//! runs out the bridge publishes a `timer-completed` event, which reaches the
+//! `/events` stream and the configured webhooks, and runs the timer's
+//! completion actions. Like the device, the bridge tracks a single timer:
+//! starting one replaces the running one.
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Instant};
+use tracing::{info, warn};
+use utoipa::ToSchema;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_axum::routes;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::events::EventKind;
+use crate::pixoo::error::{
+    DeviceErrorResponse, DeviceTimeoutResponse, DeviceUnreachableResponse, ValidationErrorResponse,
+};
+use crate::pixoo::melody::{self, Melody};
+use crate::pixoo::protocol::{PixooRequest, SetTimer};
+use crate::routes::common::{pixoo_error_response, ValidatedJson};
+use crate::state::AppState;
+
+pub(super) fn timer_router() -> OpenApiRouter<Arc<AppState>> {
+    OpenApiRouter::new()
+        .routes(routes!(timer_status))
+        .routes(routes!(timer_start))
+        .routes(routes!(timer_stop))
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+struct TimerRequest {
+    #[validate(range(min = 0, max = 59))]
+    minute: u32,
+    #[validate(range(min = 0, max = 59))]
+    second: u32,
+    /// Name reported in the status and the `timer-completed` event.
+    #[serde(default)]
+    #[validate(length(min = 1, max = 64))]
+    label: Option<String>,
+    #[serde(default)]
+    #[validate(nested)]
+    on_complete: Option<TimerActions>,
+}
+
+/// What the bridge does when the timer runs out, besides publishing the
+/// `timer-completed` event.
+#[derive(Debug, Clone, Deserialize, Serialize, Validate, ToSchema)]
+struct TimerActions {
+    /// Melody to play on the buzzer: beep, double, chime, alarm, success, or
+    /// failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(custom(function = "validate_melody"))]
+    melody: Option<String>,
+    /// Text of a `WARNING` notification to show.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[validate(length(min = 1, max = 512))]
+    notify: Option<String>,
+}
+
+fn validate_melody(name: &str) -> Result<(), ValidationError> {
+    if name.parse::<Melody>().is_ok() {
+        return Ok(());
+    }
+    let mut error = ValidationError::new("melody");
+    error.message = Some(format!("melody must be one of: {}", Melody::NAMES.join(", ")).into());
+    Err(error)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+enum TimerState {
+    /// No timer was started since the bridge started.
+    #[default]
+    Idle,
+    Running,
+    Completed,
+    Stopped,
+}
+
+/// The timer the bridge started last.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct TimerStatus {
+    state: TimerState,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_seconds: Option<u64>,
+    /// Seconds left, rounded up; what was left when the timer was stopped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remaining_seconds: Option<u64>,
+    /// RFC 3339 timestamp of the start.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    started_at: Option<String>,
+    /// RFC 3339 timestamp of the completion or stop.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finished_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    on_complete: Option<TimerActions>,
+}
+
+struct TrackedTimer {
+    id: String,
+    label: Option<String>,
+    duration: Duration,
+    on_complete: Option<TimerActions>,
+    started_at: DateTime<Utc>,
+    deadline: Instant,
+    state: TimerState,
+    /// Time left when the timer was stopped.
+    left_at_stop: Duration,
+    finished_at: Option<DateTime<Utc>>,
+    expiry: Option<JoinHandle<()>>,
+}
+
+impl TrackedTimer {
+    fn remaining(&self) -> Duration {
+        match self.state {
+            TimerState::Running => self.deadline.saturating_duration_since(Instant::now()),
+            TimerState::Stopped => self.left_at_stop,
+            TimerState::Idle | TimerState::Completed => Duration::ZERO,
+        }
+    }
+
+    fn status(&self) -> TimerStatus {
+        let remaining = self.remaining();
+        TimerStatus {
+            state: self.state,
+            id: Some(self.id.clone()),
+            label: self.label.clone(),
+            duration_seconds: Some(self.duration.as_secs()),
+            remaining_seconds: Some(remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0)),
+            started_at: Some(timestamp(self.started_at)),
+            finished_at: self.finished_at.map(timestamp),
+            on_complete: self.on_complete.clone(),
+        }
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// Holds the timer the bridge started last.
+#[derive(Clone, Default)]
+pub struct TimerTracker {
+    current: Arc<Mutex<Option<TrackedTimer>>>,
+}
+
+impl TimerTracker {
+    /// Tracks `timer` in place of the previous one and schedules its expiry.
+    fn start(&self, state: &Arc<AppState>, mut timer: TrackedTimer) {
+        let mut current = self.current.lock().unwrap();
+        if let Some(expiry) = current.take().and_then(|previous| previous.expiry) {
+            expiry.abort();
+        }
+        timer.expiry = Some(tokio::spawn(expire(
+            state.clone(),
+            timer.id.clone(),
+            timer.deadline,
+        )));
+        *current = Some(timer);
+    }
+
+    /// Marks the running timer as stopped. A finished timer is left as is.
+    fn stop(&self) {
+        let mut current = self.current.lock().unwrap();
+        let Some(timer) = current.as_mut() else {
+            return;
+        };
+        if timer.state != TimerState::Running {
+            return;
+        }
+        timer.left_at_stop = timer.remaining();
+        timer.state = TimerState::Stopped;
+        timer.finished_at = Some(Utc::now());
+        if let Some(expiry) = timer.expiry.take() {
+            expiry.abort();
+        }
+    }
+
+    /// Marks timer `id` as completed and returns its label, duration, and
+    /// actions, or `None` when it was replaced or stopped meanwhile.
+    fn complete(&self, id: &str) -> Option<(Option<String>, Duration, Option<TimerActions>)> {
+        let mut current = self.current.lock().unwrap();
+        let timer = current.as_mut()?;
+        if timer.id != id || timer.state != TimerState::Running {
+            return None;
+        }
+        timer.state = TimerState::Completed;
+        timer.finished_at = Some(Utc::now());
+        timer.expiry = None;
+        Some((
+            timer.label.clone(),
+            timer.duration,
+            timer.on_complete.clone(),
+        ))
+    }
+
+    pub fn status(&self) -> TimerStatus {
+        self.current
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(TrackedTimer::status)
+            .unwrap_or_default()
+    }
+}
+
+/// Waits for timer `id` to run out, then announces it and runs its actions.
+async fn expire(state: Arc<AppState>, id: String, deadline: Instant) {
+    sleep_until(deadline).await;
+    let Some((label, duration, actions)) = state.timer.complete(&id) else {
+        return;
+    };
+    info!(id = %id, label = ?label, "timer completed");
+    state.events.publish(EventKind::TimerCompleted {
+        id,
+        label,
+        duration_seconds: duration.as_secs(),
+    });
+    let Some(actions) = actions else {
+        return;
+    };
+    if let Some(text) = actions.notify {
+        state.notifications.queue_text(&state, text);
+    }
+    if let Some(melody) = actions.melody.and_then(|name| name.parse::<Melody>().ok()) {
+        if let Err(err) = melody::play(&state.pixoo_client, melody).await {
+            warn!(error = %err, melody = melody.as_str(), "failed to play timer melody");
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/tools/timer",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Status of the timer the bridge started last", body = TimerStatus)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn timer_status(State(state): State<Arc<AppState>>) -> Response {
+    axum::Json(state.timer.status()).into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/timer/start",
+    tag = "tools",
+    request_body = TimerRequest,
+    responses(
+        (status = 200, description = "Timer started"),
+        (status = 400, response = ValidationErrorResponse),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state, payload))]
+async fn timer_start(
+    State(state): State<Arc<AppState>>,
+    ValidatedJson(payload): ValidatedJson<TimerRequest>,
+) -> Response {
+    let request = SetTimer::start(payload.minute, payload.second);
+    if let Err(err) = state.pixoo_client.execute(&request).await {
+        return pixoo_error_response(&SetTimer::COMMAND, &err);
+    }
+    let duration = Duration::from_secs(u64::from(payload.minute * 60 + payload.second));
+    let timer = TrackedTimer {
+        id: Uuid::new_v4().to_string(),
+        label: payload.label,
+        duration,
+        on_complete: payload.on_complete,
+        started_at: Utc::now(),
+        deadline: Instant::now() + duration,
+        state: TimerState::Running,
+        left_at_stop: Duration::ZERO,
+        finished_at: None,
+        expiry: None,
+    };
+    info!(id = %timer.id, seconds = duration.as_secs(), "timer started");
+    state.timer.start(&state, timer);
+    StatusCode::OK.into_response()
+}
+
+#[utoipa::path(
+    post,
+    path = "/tools/timer/stop",
+    tag = "tools",
+    responses(
+        (status = 200, description = "Timer stopped"),
+        (status = 502, response = DeviceUnreachableResponse),
+        (status = 503, response = DeviceErrorResponse),
+        (status = 504, response = DeviceTimeoutResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+async fn timer_stop(State(state): State<Arc<AppState>>) -> Response {
+    if let Err(err) = state.pixoo_client.execute(&SetTimer::stop()).await {
+        return pixoo_error_response(&SetTimer::COMMAND, &err);
+    }
+    state.timer.stop();
+    StatusCode::OK.into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::{assert_validation_failed, build_tool_app, tool_state_with_client};
+    use crate::events::EventKind;
+    use crate::routes::common::testing::send_json_request;
+    use axum::http::{Method, StatusCode};
+    use httpmock::{Method as MockMethod, MockServer};
+    use serde_json::{json, Value};
+    use std::time::Duration;
+    use tokio::time::{sleep, timeout, Instant};
+
+    fn mock_ok(server: &MockServer) {
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+    }
+
+    #[tokio::test]
+    async fn timer_start_succeeds() {
+        let server = MockServer::start_async().await;
+        mock_ok(&server);
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/timer/start",
+            Some(json!({"minute": 1, "second": 0})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timer_start_rejects_invalid_minute() {
+        let server = MockServer::start_async().await;
+        mock_ok(&server);
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/timer/start",
+            Some(json!({"minute": 60, "second": 0, "on_complete": {"melody": "polka"}})),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let body_json = assert_validation_failed(&body);
+        assert!(body_json["details"]["minute"].is_array());
+        assert!(body_json["details"]["on_complete.melody"].is_array());
+    }
+
+    #[tokio::test]
+    async fn timer_stop_succeeds() {
+        let server = MockServer::start_async().await;
+        mock_ok(&server);
+
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+        let (status, body) = send_json_request(&app, Method::POST, "/tools/timer/stop", None).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn timer_status_tracks_start_and_stop() {
+        let server = MockServer::start_async().await;
+        mock_ok(&server);
+        let app = build_tool_app(tool_state_with_client(&server.base_url()));
+
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/timer", None).await;
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({"state": "idle"})
+        );
+
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/timer/start",
+            Some(json!({"minute": 25, "second": 0, "label": "pomodoro"})),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/timer", None).await;
+        let running: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(running["state"], "running");
+        assert_eq!(running["label"], "pomodoro");
+        assert_eq!(running["duration_seconds"], 1500);
+        let remaining = running["remaining_seconds"].as_u64().unwrap();
+        assert!((1490..=1500).contains(&remaining), "{remaining}");
+        assert!(running.get("finished_at").is_none());
+
+        send_json_request(&app, Method::POST, "/tools/timer/stop", None).await;
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/timer", None).await;
+        let stopped: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(stopped["state"], "stopped");
+        assert_eq!(stopped["id"], running["id"]);
+        assert!(stopped["remaining_seconds"].as_u64().unwrap() > 0);
+        assert!(stopped["finished_at"].is_string());
+    }
+
+    #[tokio::test]
+    async fn completed_timer_publishes_event_and_runs_actions() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Draw/GetHttpGifId");
+            then.status(200).body(r#"{"error_code":0,"PicId":1}"#);
+        });
+        let buzzer = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Device/PlayBuzzer");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let text = server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_includes("Tea is ready");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        server.mock(|when, then| {
+            when.method(MockMethod::POST)
+                .path("/post")
+                .body_excludes("Draw/GetHttpGifId")
+                .body_excludes("Device/PlayBuzzer")
+                .body_excludes("Tea is ready");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let state = tool_state_with_client(&server.base_url());
+        let mut events = state.events.subscribe();
+        let app = build_tool_app(state);
+
+        let (status, _) = send_json_request(
+            &app,
+            Method::POST,
+            "/tools/timer/start",
+            Some(json!({
+                "minute": 0,
+                "second": 1,
+                "label": "tea",
+                "on_complete": { "melody": "beep", "notify": "Tea is ready" }
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let completed = timeout(Duration::from_secs(5), async {
+            loop {
+                let event = events.recv().await.unwrap();
+                if let EventKind::TimerCompleted { .. } = event.kind {
+                    return event;
+                }
+            }
+        })
+        .await
+        .expect("timer-completed event");
+        let EventKind::TimerCompleted {
+            label,
+            duration_seconds,
+            ..
+        } = &completed.kind
+        else {
+            unreachable!();
+        };
+        assert_eq!(label.as_deref(), Some("tea"));
+        assert_eq!(*duration_seconds, 1);
+
+        let (_, body) = send_json_request(&app, Method::GET, "/tools/timer", None).await;
+        let status: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(status["state"], "completed");
+        assert_eq!(status["remaining_seconds"], 0);
+        assert_eq!(status["on_complete"]["melody"], "beep");
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while (buzzer.calls_async().await < 1 || text.calls_async().await < 1)
+            && Instant::now() < deadline
+        {
+            sleep(Duration::from_millis(20)).await;
+        }
+        buzzer.assert_calls(1);
+        text.assert_calls(1);
+    }
+}
@@ -0,0 +1,837 @@
+//! The bridge server: configuration loading, middleware stack, and the
+//! listener loop behind the `pixoo-bridge` binary.
+
+use axum::{
+    body::Body,
+    http::Request,
+    middleware::{from_fn, from_fn_with_state, Next},
+    response::{Redirect, Response},
+    routing::get,
+    Router,
+};
+use std::{env, net::SocketAddr, path::Path, sync::Arc, time::Instant};
+use tokio::signal;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+
+const APP_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+use crate::config::{
+    self, AppConfig, ConfigSource, EnvConfigSource, FileConfigSource, LayeredConfigSource,
+    SharedSettings, RELOAD_POLL_INTERVAL,
+};
+use crate::events::EventBus;
+use crate::openapi::{self, ApiDoc};
+use crate::pixoo::{self, PixooClient};
+use crate::remote::{RemoteFetchConfig, RemoteFetcher};
+use crate::request_tracing::{self, RequestId};
+use crate::routes::{
+    self, build_router, DrawGate, DrawJobs, LiveRemote, Notifications, Scoreboard, TextSlots,
+    TimerTracker,
+};
+use crate::state::AppState;
+use crate::{auth, metrics, mqtt, webhooks};
+use utoipa::OpenApi;
+use utoipa_axum::router::OpenApiRouter;
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+/// Loads the configuration, starts the background tasks, and serves the
+/// HTTP API until SIGINT or SIGTERM.
+///
+/// # Errors
+///
+/// Returns an error if the configuration is invalid, a client cannot be
+/// built, or the listener cannot be bound.
+pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let config_file = config::resolve_config_file(&EnvConfigSource);
+    let file_source = config_file
+        .as_deref()
+        .map(FileConfigSource::load)
+        .transpose();
+    let source = LayeredConfigSource::new(
+        EnvConfigSource,
+        file_source
+            .as_ref()
+            .ok()
+            .cloned()
+            .flatten()
+            .unwrap_or_default(),
+    );
+
+    let (max_level, invalid_level) = resolve_log_level(&source);
+    tracing_subscriber::fmt().with_max_level(max_level).init();
+
+    if let Some(raw) = invalid_level {
+        warn!(invalid_level = %raw, "Invalid PIXOO_BRIDGE_LOG_LEVEL, defaulting to info");
+    }
+
+    let config = match file_source.and_then(|_| AppConfig::load_from(&source)) {
+        Ok(config) => config,
+        Err(err) => {
+            error!(error = %err, "Configuration error");
+            return Err(err.into());
+        }
+    };
+    let events = EventBus::new();
+    let pixoo_client = PixooClient::new(config.pixoo_base_url.clone(), config.pixoo_client)?
+        .with_events(events.clone());
+    if let Some(webhook_config) = config.webhooks.clone() {
+        webhooks::spawn_webhooks(webhook_config, &events)?;
+    }
+    if let Some(interval) = config.health_poll_interval {
+        pixoo::spawn_health_monitor(pixoo_client.clone(), interval);
+    }
+    let remote_fetcher = RemoteFetcher::new(
+        RemoteFetchConfig::new(config.remote_timeout, config.max_image_size)
+            .with_cache(config.remote_cache_entries, config.remote_cache_dir.clone())
+            .with_policy(config.remote_policy.clone()),
+    )?;
+    let state = Arc::new(AppState {
+        health_forward: config.health_forward,
+        pixoo_client,
+        settings: SharedSettings::new(config.runtime_settings()),
+        remote_fetcher,
+        live_remote: LiveRemote::default(),
+        draw_jobs: DrawJobs::default(),
+        draw_gate: DrawGate::new(config.draw_conflict),
+        text_slots: TextSlots::default(),
+        notifications: Notifications::default(),
+        timer: TimerTracker::default(),
+        scoreboard: Scoreboard::default(),
+        auth: Arc::new(config.auth.clone()),
+        cors: config.cors.clone(),
+        events,
+    });
+    if let Some(path) = config_file.clone() {
+        config::spawn_config_reloader(
+            path,
+            source.into_fallback(),
+            state.clone(),
+            RELOAD_POLL_INTERVAL,
+        );
+    }
+    if let Some(mqtt_config) = config.mqtt.clone() {
+        mqtt::spawn_mqtt(mqtt_config, internal_router(state.clone()));
+    }
+    let app = build_app(state.clone());
+
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.listener_port));
+    log_startup(&config, addr, config_file.as_deref(), max_level);
+
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    axum::serve(listener, app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal())
+        .await?;
+
+    info!("Graceful shutdown complete");
+    Ok(())
+}
+
+/// Logs the resolved configuration and warns about risky combinations.
+fn log_startup(
+    config: &AppConfig,
+    addr: SocketAddr,
+    config_file: Option<&Path>,
+    max_level: LevelFilter,
+) {
+    info!(
+        version = APP_VERSION,
+        address = %addr,
+        config_file = ?config_file,
+        listener_port = config.listener_port,
+        log_level = ?max_level,
+        pixoo_base_url = %config.pixoo_base_url,
+        pixoo_client = true,
+        health_forward = config.health_forward,
+        animation_speed_factor = config.animation_speed_factor,
+        max_image_size = config.max_image_size,
+        remote_timeout = ?config.remote_timeout,
+        pixoo_client_config = ?config.pixoo_client,
+        remote_cache_entries = config.remote_cache_entries,
+        remote_cache_dir = ?config.remote_cache_dir,
+        remote_policy = ?config.remote_policy,
+        auth_enabled = config.auth.is_enabled(),
+        api_keys = config.auth.key_count(),
+        cors = ?config.cors,
+        mqtt = ?config.mqtt,
+        health_poll_interval = ?config.health_poll_interval,
+        webhooks = ?config.webhooks,
+        draw_conflict = %config.draw_conflict,
+        "Pixoo bridge configuration loaded"
+    );
+    if config.auth.is_enabled() && config.cors.is_permissive() {
+        warn!(
+            "API keys are configured but CORS allows any origin; set PIXOO_BRIDGE_CORS_ALLOWED_ORIGINS to restrict browser access"
+        );
+    }
+}
+
+fn build_app(state: Arc<AppState>) -> Router {
+    let (router, mut api) = OpenApiRouter::with_openapi(ApiDoc::openapi())
+        .merge(build_router())
+        .split_for_parts();
+    openapi::apply_security(&mut api);
+
+    router
+        .merge(
+            SwaggerUi::new("/docs")
+                .url("/api-docs/openapi.json", api)
+                .config(Config::from("/api-docs/openapi.json").validator_url("none")),
+        )
+        .route("/", get(|| async { Redirect::permanent("/docs") }))
+        .fallback(fallback_not_found)
+        .layer(from_fn_with_state(
+            state.auth.clone(),
+            auth::require_api_key,
+        ))
+        // Outside auth so rejected requests are counted too.
+        .layer(from_fn(metrics::track_http))
+        .layer(state.cors.layer())
+        .layer(from_fn(access_log))
+        .layer(from_fn(request_tracing::propagate))
+        .with_state(state)
+}
+
+/// Router for in-process callers such as MQTT: the API routes without
+/// authentication, CORS, or docs.
+fn internal_router(state: Arc<AppState>) -> Router {
+    let (router, _api) = build_router().split_for_parts();
+    router
+        .layer(from_fn(request_tracing::propagate))
+        .with_state(state)
+}
+
+async fn fallback_not_found() -> Response {
+    routes::not_found()
+}
+
+async fn access_log(req: Request<Body>, next: Next) -> Response {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let start = Instant::now();
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .cloned()
+        .unwrap_or_default();
+    let response = next.run(req).await;
+    let latency = start.elapsed();
+    let status = response.status();
+    debug!(method=%method, path=%path, status=%status, latency=?latency, request_id=%request_id, "access log");
+    response
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {
+            info!("Received SIGINT, starting graceful shutdown");
+        }
+        () = terminate => {
+            info!("Received SIGTERM, starting graceful shutdown");
+        }
+    }
+}
+
+fn resolve_log_level(source: &impl ConfigSource) -> (LevelFilter, Option<String>) {
+    let raw = source
+        .get("PIXOO_BRIDGE_LOG_LEVEL")
+        .unwrap_or_else(|| "info".to_string());
+    match raw.parse::<LevelFilter>() {
+        Ok(level) => (level, None),
+        Err(_) => (LevelFilter::INFO, Some(raw)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Scope;
+    use crate::config::testing::MockConfig;
+    use crate::pixoo::{PixooClient, PixooClientConfig};
+    use crate::state::AppState;
+    use axum::body::Body;
+    use axum::http::{Method, Request, StatusCode};
+    use httpmock::{Method as MockMethod, MockServer};
+    use std::sync::Arc;
+    use tower::util::ServiceExt;
+
+    #[tokio::test]
+    async fn integration_build_app_includes_tool_routes() {
+        let server = MockServer::start_async().await;
+        let _mock = server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tools/stopwatch/start")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn integration_build_app_includes_system_routes() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::GET).path("/get");
+            then.status(200);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let mut state = AppState::with_client(client);
+        state.health_forward = true;
+        let app = build_app(Arc::new(state));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn request_id_middleware_inserts_header() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/health")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        let header = response
+            .headers()
+            .get("X-Request-Id")
+            .expect("request id header present");
+        assert!(!header.to_str().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn error_path_unreachable_device_returns_502_with_request_id() {
+        let client = PixooClient::new(
+            "http://127.0.0.1:1".to_string(),
+            PixooClientConfig::default(),
+        )
+        .expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tools/stopwatch/start")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+
+        let request_id = response
+            .headers()
+            .get("X-Request-Id")
+            .expect("X-Request-Id header");
+        assert!(!request_id.to_str().unwrap().is_empty());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error_kind"], "unreachable");
+        assert_eq!(json["error_status"], 502);
+        assert!(!json["message"].as_str().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn error_path_device_error_returns_503_with_request_id() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":1}"#);
+        });
+
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tools/stopwatch/start")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let request_id = response
+            .headers()
+            .get("X-Request-Id")
+            .expect("X-Request-Id header");
+        assert!(!request_id.to_str().unwrap().is_empty());
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error_kind"], "device-error");
+        assert_eq!(json["error_status"], 503);
+        assert_eq!(json["details"]["error_code"], 1);
+    }
+
+    #[test]
+    fn resolves_log_level_defaults_to_info() {
+        let config = MockConfig::new();
+        let (level, invalid) = resolve_log_level(&config);
+        assert_eq!(level, LevelFilter::INFO);
+        assert!(invalid.is_none());
+    }
+
+    #[test]
+    fn resolves_log_level_from_env() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_LOG_LEVEL", "debug");
+        let (level, invalid) = resolve_log_level(&config);
+        assert_eq!(level, LevelFilter::DEBUG);
+        assert!(invalid.is_none());
+    }
+
+    #[test]
+    fn resolves_log_level_invalid_falls_back_to_info() {
+        let config = MockConfig::new().with("PIXOO_BRIDGE_LOG_LEVEL", "not-a-level");
+        let (level, invalid) = resolve_log_level(&config);
+        assert_eq!(level, LevelFilter::INFO);
+        assert_eq!(invalid, Some("not-a-level".to_string()));
+    }
+
+    #[tokio::test]
+    async fn openapi_spec_endpoint_returns_document_with_routes() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/api-docs/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(doc["openapi"].as_str().unwrap().chars().next(), Some('3'));
+        assert_eq!(doc["info"]["title"], "Pixoo Bridge");
+        let paths = doc["paths"].as_object().expect("paths object");
+        assert!(paths.contains_key("/draw/fill"));
+        assert!(paths.contains_key("/manage/settings"));
+        assert!(paths.contains_key("/health"));
+        assert!(paths.contains_key("/tools/stopwatch/{action}"));
+        assert!(doc["paths"]["/health"].get("get").is_some());
+        assert!(doc["paths"]["/draw/fill"].get("post").is_some());
+
+        // Schema component still present; no divergent one-off schemas.
+        let schemas = doc["components"]["schemas"].as_object().expect("schemas");
+        assert!(schemas.contains_key("PixooHttpErrorResponse"));
+        assert!(!schemas.contains_key("ValidationErrorBody"));
+        assert!(!schemas.contains_key("PayloadTooLargeBody"));
+
+        // All reusable response components are registered and each one points
+        // back to the canonical envelope schema.
+        let resp_components = doc["components"]["responses"]
+            .as_object()
+            .expect("response components");
+        for name in [
+            "ValidationErrorResponse",
+            "PayloadTooLargeResponse",
+            "UnauthorizedResponse",
+            "ForbiddenResponse",
+            "InternalErrorResponse",
+            "DeviceUnreachableResponse",
+            "DeviceErrorResponse",
+            "DeviceTimeoutResponse",
+        ] {
+            assert!(
+                resp_components.contains_key(name),
+                "missing response component: {name}"
+            );
+            let schema_ref = resp_components[name]["content"]["application/json"]["schema"]["$ref"]
+                .as_str()
+                .unwrap_or_default();
+            assert_eq!(
+                schema_ref, "#/components/schemas/PixooHttpErrorResponse",
+                "{name} must reference the canonical envelope schema"
+            );
+        }
+
+        // Path-level responses now $ref the response components (not inline schema).
+        let fill_responses = &doc["paths"]["/draw/fill"]["post"]["responses"];
+        for status in ["400", "401", "403", "500", "502", "503", "504"] {
+            let ref_val = fill_responses[status]["$ref"].as_str().unwrap_or_default();
+            assert!(
+                ref_val.starts_with("#/components/responses/"),
+                "status {status} must $ref a response component, got: {ref_val:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn openapi_documents_api_key_security_per_scope() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/api-docs/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let schemes = &doc["components"]["securitySchemes"];
+        assert_eq!(schemes["bearer"]["scheme"], "bearer");
+        assert_eq!(schemes["api_key"]["name"], "X-API-Key");
+
+        assert_eq!(
+            doc["paths"]["/reboot"]["post"]["security"][0]["bearer"][0],
+            "system"
+        );
+        assert_eq!(
+            doc["paths"]["/draw/fill"]["post"]["security"][1]["api_key"][0],
+            "draw"
+        );
+        assert_eq!(
+            doc["paths"]["/notify"]["post"]["security"][0]["bearer"][0],
+            "draw"
+        );
+        assert!(doc["paths"]["/health"]["get"].get("security").is_none());
+    }
+
+    #[tokio::test]
+    async fn every_documented_route_is_scoped_or_explicitly_public() {
+        const PUBLIC: [&str; 1] = ["/health"];
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/api-docs/openapi.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let doc: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        for (path, item) in doc["paths"].as_object().expect("paths") {
+            let operations = item.as_object().expect("path item");
+            match Scope::for_path(path) {
+                None => assert!(PUBLIC.contains(&path.as_str()), "{path} is public"),
+                Some(scope) => {
+                    for (method, operation) in operations {
+                        assert_eq!(
+                            operation["security"][0]["bearer"][0],
+                            scope.as_str(),
+                            "{method} {path} documents its scope"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn configured_api_keys_protect_scoped_routes() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let mut state = AppState::with_client(client);
+        state.auth = Arc::new(auth::AuthConfig::new(vec![auth::ApiKey::parse(
+            "tools-key:tools",
+        )
+        .unwrap()]));
+        let app = build_app(Arc::new(state));
+
+        let request = |uri: &str, key: Option<&str>| {
+            let mut builder = Request::builder().method(Method::POST).uri(uri);
+            if let Some(key) = key {
+                builder = builder.header("Authorization", format!("Bearer {key}"));
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request("/tools/stopwatch/start", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(request("/notify", None))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .clone()
+            .oneshot(request("/tools/stopwatch/start", Some("tools-key")))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(request("/reboot", Some("tools-key")))
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn metrics_record_requests_by_route_template() {
+        let server = MockServer::start_async().await;
+        server.mock(|when, then| {
+            when.method(MockMethod::POST).path("/post");
+            then.status(200).body(r#"{"error_code":0}"#);
+        });
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tools/stopwatch/reset")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/metrics")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains(
+            "pixoo_bridge_http_requests_total{method=\"POST\",route=\"/tools/stopwatch/{action}\",status=\"200\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn metrics_count_requests_rejected_by_auth() {
+        let client =
+            PixooClient::new("http://127.0.0.1:9", PixooClientConfig::default()).expect("client");
+        let mut state = AppState::with_client(client);
+        state.auth = Arc::new(auth::AuthConfig::new(vec![
+            auth::ApiKey::parse("admin").unwrap()
+        ]));
+        let app = build_app(Arc::new(state));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method(Method::POST)
+                    .uri("/tools/buzzer")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/metrics")
+                    .header("Authorization", "Bearer admin")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(body.contains(
+            "pixoo_bridge_http_requests_total{method=\"POST\",route=\"/tools/buzzer\",status=\"401\"}"
+        ));
+    }
+
+    #[tokio::test]
+    async fn root_path_redirects_to_docs() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::PERMANENT_REDIRECT);
+        assert_eq!(
+            response
+                .headers()
+                .get("location")
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "/docs"
+        );
+    }
+
+    #[tokio::test]
+    async fn swagger_ui_is_served_at_docs() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/docs/")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn undefined_route_returns_json_404() {
+        let server = MockServer::start_async().await;
+        let client =
+            PixooClient::new(server.base_url(), PixooClientConfig::default()).expect("client");
+        let app = build_app(Arc::new(AppState::with_client(client)));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .method(Method::GET)
+                    .uri("/nonexistent")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .expect("response");
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error_status"], 404);
+        assert_eq!(json["error_kind"], "not-found");
+        assert!(json["message"].is_string());
+        assert!(
+            json.get("details").is_none(),
+            "not-found body must omit details"
+        );
+    }
+}
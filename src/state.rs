@@ -1,7 +1,15 @@
+use crate::auth::AuthConfig;
+use crate::config::{RuntimeSettings, SharedSettings};
+use crate::cors::CorsConfig;
+use crate::events::EventBus;
 use crate::pixoo::PixooClient;
 #[cfg(test)]
 use crate::remote::RemoteFetchConfig;
 use crate::remote::RemoteFetcher;
+use crate::routes::{
+    DrawGate, DrawJobs, LiveRemote, Notifications, Scoreboard, TextSlots, TimerTracker,
+};
+use std::sync::Arc;
 #[cfg(test)]
 use std::time::Duration;
 
@@ -9,14 +17,48 @@ use std::time::Duration;
 pub struct AppState {
     pub health_forward: bool,
     pub pixoo_client: PixooClient,
-    pub animation_speed_factor: f64,
-    pub max_image_size: usize,
+    /// Hot-reloadable settings (animation speed, image size, timeouts).
+    pub settings: SharedSettings,
     pub remote_fetcher: RemoteFetcher,
+    pub live_remote: LiveRemote,
+    pub draw_jobs: DrawJobs,
+    /// Serialises frame uploads; see [`crate::routes::DrawConflictPolicy`].
+    pub draw_gate: DrawGate,
+    /// What the bridge last sent to each text slot.
+    pub text_slots: TextSlots,
+    pub notifications: Notifications,
+    /// The timer last started through `/tools/timer/start`.
+    pub timer: TimerTracker,
+    /// Scores last sent to the scoreboard tool.
+    pub scoreboard: Scoreboard,
+    pub auth: Arc<AuthConfig>,
+    pub cors: CorsConfig,
+    /// Shared with the Pixoo client, which publishes device events.
+    pub events: EventBus,
+}
+
+impl AppState {
+    /// Publishes new runtime settings and pushes the limits into the HTTP
+    /// clients that cache them.
+    pub fn apply_settings(&self, settings: RuntimeSettings) {
+        self.settings.set(settings);
+        self.remote_fetcher
+            .set_limits(settings.remote_timeout, settings.max_image_size);
+        self.pixoo_client.set_timeout(settings.device_timeout);
+    }
+
+    /// Called when the device left its animation layer (channel switch or
+    /// reboot), which drops the text and frames the bridge sent.
+    pub fn forget_display(&self) {
+        self.text_slots.clear();
+        self.notifications.forget_frames();
+    }
 }
 
 #[cfg(test)]
 impl AppState {
     pub fn with_client(client: PixooClient) -> Self {
+        let events = EventBus::new();
         let remote_fetcher = RemoteFetcher::new(RemoteFetchConfig::new(
             Duration::from_secs(10),
             5 * 1024 * 1024,
@@ -24,10 +66,24 @@ impl AppState {
         .expect("remote fetcher");
         Self {
             health_forward: false,
-            pixoo_client: client,
-            animation_speed_factor: 1.4,
-            max_image_size: 5 * 1024 * 1024,
+            pixoo_client: client.with_events(events.clone()),
+            settings: SharedSettings::new(RuntimeSettings {
+                animation_speed_factor: 1.4,
+                max_image_size: 5 * 1024 * 1024,
+                remote_timeout: Duration::from_secs(10),
+                device_timeout: Duration::from_secs(10),
+            }),
             remote_fetcher,
+            live_remote: LiveRemote::default(),
+            draw_jobs: DrawJobs::default(),
+            draw_gate: DrawGate::default(),
+            text_slots: TextSlots::default(),
+            notifications: Notifications::default(),
+            timer: TimerTracker::default(),
+            scoreboard: Scoreboard::default(),
+            auth: Arc::new(AuthConfig::default()),
+            cors: CorsConfig::default(),
+            events,
         }
     }
 }
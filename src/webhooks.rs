@@ -0,0 +1,315 @@
+//! Outbound webhooks for [bridge events](crate::events).
+//!
+//! Each matching event is `POST`ed as JSON to every configured URL. When a
+//! secret is configured the body is signed with HMAC-SHA256 and the hex digest
+//! is sent as `X-Pixoo-Signature: sha256=<digest>`, so receivers can verify
+//! the sender. Failed deliveries (network errors and non-2xx responses) are
+//! retried with exponential backoff; the event id stays the same across
+//! retries so receivers can deduplicate.
+
+use crate::events::{BridgeEvent, EventBus, EventType};
+use crate::metrics::metrics;
+use hmac::{Hmac, Mac};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Url;
+use sha2::Sha256;
+use std::fmt::{self, Write as _};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{debug, warn};
+
+pub const SIGNATURE_HEADER: &str = "X-Pixoo-Signature";
+pub const EVENT_HEADER: &str = "X-Pixoo-Event";
+pub const DELIVERY_HEADER: &str = "X-Pixoo-Delivery";
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    pub urls: Vec<Url>,
+    /// Event types to deliver; `None` delivers the
+    /// [default ones](EventType::is_default).
+    pub events: Option<Vec<EventType>>,
+    /// HMAC-SHA256 signing secret.
+    pub secret: Option<String>,
+    /// Additional attempts after the first failed delivery.
+    pub retries: usize,
+    /// Delay before the first retry; doubled for each further retry.
+    pub backoff: Duration,
+    pub timeout: Duration,
+}
+
+impl fmt::Debug for WebhookConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field(
+                "urls",
+                &self.urls.iter().map(Url::as_str).collect::<Vec<_>>(),
+            )
+            .field("events", &self.events)
+            .field("secret", &self.secret.as_ref().map(|_| "***"))
+            .field("retries", &self.retries)
+            .field("backoff", &self.backoff)
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+impl WebhookConfig {
+    pub fn accepts(&self, event_type: EventType) -> bool {
+        match &self.events {
+            Some(events) => events.contains(&event_type),
+            None => event_type.is_default(),
+        }
+    }
+}
+
+/// Delivers events from `events` to the configured webhooks until aborted.
+///
+/// # Errors
+///
+/// Returns the HTTP client error when the client cannot be built.
+pub fn spawn_webhooks(
+    config: WebhookConfig,
+    events: &EventBus,
+) -> Result<tokio::task::JoinHandle<()>, reqwest::Error> {
+    let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+    let mut receiver = events.subscribe();
+    let config = Arc::new(config);
+
+    Ok(tokio::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(
+                        skipped,
+                        "Webhook dispatcher fell behind; events were dropped"
+                    );
+                    continue;
+                }
+                Err(RecvError::Closed) => return,
+            };
+            if !config.accepts(event.kind.event_type()) {
+                continue;
+            }
+            let body = match serde_json::to_vec(event.as_ref()) {
+                Ok(body) => Arc::new(body),
+                Err(err) => {
+                    warn!(error = %err, "Failed to serialize webhook event");
+                    continue;
+                }
+            };
+            for url in &config.urls {
+                let delivery = Delivery {
+                    http: http.clone(),
+                    config: config.clone(),
+                    url: url.clone(),
+                    event: event.clone(),
+                    body: body.clone(),
+                };
+                tokio::spawn(delivery.run());
+            }
+        }
+    }))
+}
+
+struct Delivery {
+    http: reqwest::Client,
+    config: Arc<WebhookConfig>,
+    url: Url,
+    event: Arc<BridgeEvent>,
+    body: Arc<Vec<u8>>,
+}
+
+impl Delivery {
+    async fn run(self) {
+        let event_type = self.event.kind.event_type();
+        let mut attempt = 0;
+        loop {
+            match self.send().await {
+                Ok(()) => {
+                    debug!(url = %self.url, event = %event_type, id = %self.event.id, "Webhook delivered");
+                    metrics().record_webhook_delivery("delivered");
+                    return;
+                }
+                Err(err) if attempt < self.config.retries => {
+                    let delay = self.config.backoff
+                        * 2u32.saturating_pow(u32::try_from(attempt).unwrap_or(u32::MAX));
+                    debug!(url = %self.url, event = %event_type, error = %err, retry_in = ?delay, "Webhook delivery failed; retrying");
+                    metrics().record_webhook_delivery("retried");
+                    attempt += 1;
+                    tokio::time::sleep(delay).await;
+                }
+                Err(err) => {
+                    warn!(
+                        url = %self.url,
+                        event = %event_type,
+                        id = %self.event.id,
+                        attempts = attempt + 1,
+                        error = %err,
+                        "Webhook delivery failed"
+                    );
+                    metrics().record_webhook_delivery("failed");
+                    return;
+                }
+            }
+        }
+    }
+
+    async fn send(&self) -> Result<(), String> {
+        let mut request = self
+            .http
+            .post(self.url.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, self.event.kind.event_type().as_str())
+            .header(DELIVERY_HEADER, &self.event.id)
+            .body(self.body.as_ref().clone());
+        if let Some(secret) = &self.config.secret {
+            request = request.header(
+                SIGNATURE_HEADER,
+                format!("sha256={}", sign(secret, &self.body)),
+            );
+        }
+        let response = request.send().await.map_err(|err| err.to_string())?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("unexpected HTTP status {}", response.status()))
+        }
+    }
+}
+
+/// Hex-encoded HMAC-SHA256 of `body` keyed with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventKind;
+    use httpmock::{Method::POST, MockServer};
+
+    fn config(url: &str) -> WebhookConfig {
+        WebhookConfig {
+            urls: vec![Url::parse(url).unwrap()],
+            events: None,
+            secret: None,
+            retries: 2,
+            backoff: Duration::from_millis(10),
+            timeout: Duration::from_secs(2),
+        }
+    }
+
+    async fn wait_for_calls(mock: &httpmock::Mock<'_>, calls: usize) {
+        for _ in 0..200 {
+            if mock.calls_async().await >= calls {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[test]
+    fn signs_with_hmac_sha256() {
+        // RFC 4231 test case 2.
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn filters_event_types() {
+        let mut config = config("http://127.0.0.1/hook");
+        assert!(config.accepts(EventType::DeviceError));
+        assert!(!config.accepts(EventType::Command));
+        config.events = Some(vec![EventType::DeviceOffline, EventType::DeviceOnline]);
+        assert!(config.accepts(EventType::DeviceOnline));
+        assert!(!config.accepts(EventType::DeviceError));
+    }
+
+    #[test]
+    fn debug_redacts_secret() {
+        let mut config = config("http://127.0.0.1/hook");
+        config.secret = Some("hunter2".to_string());
+        let rendered = format!("{config:?}");
+        assert!(!rendered.contains("hunter2"));
+    }
+
+    #[tokio::test]
+    async fn delivers_signed_events() {
+        let server = MockServer::start_async().await;
+        let events = EventBus::new();
+        let mut config = config(&server.url("/hook"));
+        config.secret = Some("s3cret".to_string());
+        let handle = spawn_webhooks(config, &events).expect("dispatcher");
+
+        let mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/hook")
+                .header("content-type", "application/json")
+                .header(EVENT_HEADER, "device-rebooted")
+                .header_exists(SIGNATURE_HEADER)
+                .header_exists(DELIVERY_HEADER)
+                .body_includes(r#""type":"device-rebooted""#);
+            then.status(204);
+        });
+        events.publish(EventKind::DeviceRebooted);
+        wait_for_calls(&mock, 1).await;
+        handle.abort();
+
+        mock.assert_calls(1);
+    }
+
+    #[tokio::test]
+    async fn retries_failed_deliveries() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(500);
+        });
+        let events = EventBus::new();
+        let handle = spawn_webhooks(config(&server.url("/hook")), &events).expect("dispatcher");
+
+        events.publish(EventKind::DeviceOnline);
+        wait_for_calls(&mock, 3).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        mock.assert_calls(3);
+    }
+
+    #[tokio::test]
+    async fn skips_filtered_events() {
+        let server = MockServer::start_async().await;
+        let mock = server.mock(|when, then| {
+            when.method(POST).path("/hook");
+            then.status(200);
+        });
+        let events = EventBus::new();
+        let mut config = config(&server.url("/hook"));
+        config.events = Some(vec![EventType::DeviceError]);
+        let handle = spawn_webhooks(config, &events).expect("dispatcher");
+
+        events.publish(EventKind::DeviceOnline);
+        events.publish(EventKind::DeviceError {
+            command: "Channel/SetBrightness".to_string(),
+            error_code: 1,
+        });
+        wait_for_calls(&mock, 1).await;
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        handle.abort();
+
+        mock.assert_calls(1);
+    }
+}